@@ -0,0 +1,277 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Editor features computed from a script text, all locations are byte offsets into the text.
+
+use air_parser::ast;
+use air_parser::ast::visitor;
+use air_parser::ast::CallOutputValue;
+use air_parser::ast::Instruction;
+use air_parser::ast::Visitor;
+use air_parser::AIRLexer;
+use air_parser::Span;
+use air_parser::Token;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ScriptDiagnostic {
+    pub(crate) span: Span,
+    pub(crate) severity: Severity,
+    pub(crate) message: String,
+}
+
+/// Returns parser errors, the parser recovers after most of them and reports them all,
+/// if there are no such errors the script is checked with the static validation.
+pub(crate) fn diagnostics(text: &str) -> Vec<ScriptDiagnostic> {
+    let instruction = match air_parser::parse(text) {
+        Ok(instruction) => instruction,
+        Err(parser_error) => {
+            return parser_error
+                .errors
+                .into_iter()
+                .map(|error| ScriptDiagnostic {
+                    span: error.span,
+                    severity: Severity::Error,
+                    message: error.kind.to_string(),
+                })
+                .collect();
+        }
+    };
+
+    let report = air_parser::validate(&instruction);
+    let errors = report.errors.into_iter().map(|d| (d, Severity::Error));
+    let warnings = report.warnings.into_iter().map(|d| (d, Severity::Warning));
+
+    errors
+        .chain(warnings)
+        .map(|(diagnostic, severity)| ScriptDiagnostic {
+            span: diagnostic.span,
+            severity,
+            message: diagnostic.kind.to_string(),
+        })
+        .collect()
+}
+
+/// Returns locations where a variable under the offset is defined: an iterator of the innermost
+/// enclosing fold with such name or outputs of all calls and aps producing it.
+pub(crate) fn definitions(text: &str, offset: usize) -> Vec<Span> {
+    let variable = match token_at(text, offset) {
+        Some((_, Token::Alphanumeric(name)))
+        | Some((_, Token::JsonPath(name, _)))
+        | Some((_, Token::Accumulator(name))) => name,
+        _ => return vec![],
+    };
+    let instruction = match air_parser::parse(text) {
+        Ok(instruction) => instruction,
+        Err(_) => return vec![],
+    };
+
+    let mut finder = DefinitionFinder {
+        variable,
+        offset,
+        fold_iterator: None,
+        outputs: vec![],
+    };
+    finder.visit_instruction(&instruction);
+
+    if let Some(fold_span) = finder.fold_iterator {
+        // (fold iterable iterator instruction)
+        let iterator_span = tokens(text, fold_span).nth(3).map(|(span, _)| span);
+        return vec![iterator_span.unwrap_or(fold_span)];
+    }
+
+    finder
+        .outputs
+        .into_iter()
+        .map(|instruction_span| {
+            // (call peer_part function_part [args] output), (ap value output)
+            // or (invoke name [args] output)
+            let mut instruction_tokens = tokens(text, instruction_span).collect::<Vec<_>>();
+            instruction_tokens.pop();
+            instruction_tokens
+                .pop()
+                .map_or(instruction_span, |(span, _)| span)
+        })
+        .collect()
+}
+
+/// Returns a description of the innermost instruction under the offset and its span.
+pub(crate) fn hover(text: &str, offset: usize) -> Option<(Span, String)> {
+    let instruction = air_parser::parse(text).ok()?;
+
+    let mut finder = InnermostInstructionFinder {
+        offset,
+        found: None,
+    };
+    finder.visit_instruction(&instruction);
+
+    let (span, (kind, description)) = finder.found?;
+
+    Some((span, format!("**{}**: {}", kind, description)))
+}
+
+/// Returns a formatted script, if it could be parsed.
+pub(crate) fn format(text: &str) -> Option<String> {
+    air_parser::format(text).ok()
+}
+
+fn token_at(text: &str, offset: usize) -> Option<(Span, Token<'_>)> {
+    AIRLexer::new(text)
+        .take_while(Result::is_ok)
+        .filter_map(Result::ok)
+        .map(|(left, token, right)| (Span::new(left, right), token))
+        // the cursor could be right after a token as well
+        .find(|(span, _)| span.left <= offset && offset <= span.right && span.left != span.right)
+}
+
+fn tokens(text: &str, span: Span) -> impl Iterator<Item = (Span, Token<'_>)> {
+    let fragment = text.get(span.left..span.right).unwrap_or_default();
+
+    AIRLexer::new(fragment)
+        .take_while(Result::is_ok)
+        .filter_map(Result::ok)
+        .map(move |(left, token, right)| (Span::new(span.left + left, span.left + right), token))
+}
+
+fn contains(span: Span, offset: usize) -> bool {
+    span.left <= offset && offset < span.right
+}
+
+struct DefinitionFinder<'v> {
+    variable: &'v str,
+    offset: usize,
+    fold_iterator: Option<Span>,
+    outputs: Vec<Span>,
+}
+
+impl DefinitionFinder<'_> {
+    fn visit_output(&mut self, output: &CallOutputValue<'_>, span: Span) {
+        match output {
+            CallOutputValue::Scalar(name) | CallOutputValue::Accumulator(name)
+                if *name == self.variable =>
+            {
+                self.outputs.push(span)
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'i> Visitor<'i> for DefinitionFinder<'_> {
+    fn visit_call(&mut self, call: &ast::Call<'i>) {
+        self.visit_output(&call.output, call.span);
+    }
+
+    fn visit_ap(&mut self, ap: &ast::Ap<'i>) {
+        self.visit_output(&ap.output, ap.span);
+    }
+
+    fn visit_invoke(&mut self, invoke: &ast::Invoke<'i>) {
+        self.visit_output(&invoke.output, invoke.span);
+    }
+
+    fn visit_fold(&mut self, fold: &ast::Fold<'i>) {
+        // folds are visited from outer to inner ones, so the innermost one is saved last
+        if fold.iterator == self.variable && contains(fold.span, self.offset) {
+            self.fold_iterator = Some(fold.span);
+        }
+        visitor::walk_fold(self, fold);
+    }
+}
+
+/// Returns a keyword of the instruction and a short description of what it does.
+fn describe(instruction: &Instruction<'_>) -> (&'static str, &'static str) {
+    use Instruction::*;
+
+    match instruction {
+        Call(_) => (
+            "call",
+            "calls a service function on a peer, its result is saved to the output variable",
+        ),
+        Ap(_) => (
+            "ap",
+            "sets the output variable to the value without calling any service",
+        ),
+        Seq(_) => ("seq", "executes instructions sequentially"),
+        Par(_) => ("par", "executes instructions in parallel"),
+        Xor(_) => (
+            "xor",
+            "executes the second instruction iff the first one fails",
+        ),
+        Match(_) => ("match", "executes the instruction iff values are equal"),
+        MisMatch(_) => (
+            "mismatch",
+            "executes the instruction iff values aren't equal",
+        ),
+        Compare(compare) => (
+            compare.operator.keyword(),
+            "executes the instruction iff the comparison of values holds",
+        ),
+        Fold(_) => (
+            "fold",
+            "iterates through an array, assigning each element to the iterator",
+        ),
+        New(_) => (
+            "new",
+            "opens a scope where the variable is undefined, it gets back its value after the scope",
+        ),
+        Next(_) => ("next", "triggers the next iteration of the enclosing fold"),
+        Null(_) => ("null", "does nothing"),
+        Fail(_) => (
+            "fail",
+            "raises an error with the code and message, it could be caught by xor",
+        ),
+        Define(_) => (
+            "define",
+            "defines a procedure with parameters, which could be invoked from the script",
+        ),
+        Invoke(_) => (
+            "invoke",
+            "executes a procedure with parameters bound to the arguments",
+        ),
+        Error => ("error", "couldn't be parsed"),
+    }
+}
+
+struct InnermostInstructionFinder {
+    offset: usize,
+    found: Option<(Span, (&'static str, &'static str))>,
+}
+
+impl<'i> Visitor<'i> for InnermostInstructionFinder {
+    fn visit_instruction(&mut self, instruction: &Instruction<'i>) {
+        let span = instruction.span();
+        if !contains(span, self.offset) {
+            // the main instruction follows definitions, so it isn't a part of their spans
+            if let Instruction::Define(define) = instruction {
+                self.visit_instruction(&define.instruction);
+            }
+            return;
+        }
+
+        // children are visited after their parent, so the innermost one is saved last
+        self.found = Some((span, describe(instruction)));
+        visitor::walk_instruction(self, instruction);
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,172 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::*;
+
+use air_parser::Span;
+
+fn span_text(text: &str, span: Span) -> &str {
+    &text[span.left..span.right]
+}
+
+#[test]
+fn parser_errors_are_reported() {
+    // the parser recovers after the first malformed instruction and finds the second one
+    let text = r#"(seq (seq (call) (null)) (par (null) (next)))"#;
+
+    let diagnostics = diagnostics(text);
+
+    assert!(diagnostics.len() >= 2, "{:?}", diagnostics);
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+    assert_eq!(diagnostics[0].span, Span::new(15, 16));
+    assert_eq!(diagnostics[1].span, Span::new(42, 43));
+}
+
+#[test]
+fn validation_diagnostics_are_reported() {
+    let text = r#"
+        (seq
+            (call "peer" ("service" "function") [undefined] result)
+            (next i)
+        )
+        "#;
+
+    let diagnostics = diagnostics(text);
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .collect::<Vec<_>>();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(span_text(text, errors[0].span), "(next i)");
+
+    let warnings = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Warning)
+        .count();
+    assert_eq!(warnings, 1);
+}
+
+#[test]
+fn definition_of_call_output() {
+    let text = r#"
+        (seq
+            (call "peer" ("service" "function") [] result)
+            (call result.$.peer ("service" "function") [result])
+        )
+        "#;
+    let use_offset = text.rfind("[result]").unwrap() + 3;
+
+    let definitions = definitions(text, use_offset);
+
+    assert_eq!(definitions.len(), 1);
+    assert_eq!(span_text(text, definitions[0]), "result");
+    assert_eq!(definitions[0].left, text.find("result").unwrap());
+
+    let json_path_offset = text.find("result.$.peer").unwrap();
+    assert_eq!(super::definitions(text, json_path_offset), definitions);
+}
+
+#[test]
+fn definition_of_ap_output() {
+    let text = r#"
+        (seq
+            (call "peer" ("service" "function") [] result)
+            (seq
+                (ap result.$.peer peers[])
+                (ap "peer" peers[])
+            )
+        )
+        "#;
+    let definitions = definitions(text, text.rfind("peers[]").unwrap());
+
+    assert_eq!(definitions.len(), 2);
+    assert_eq!(definitions[0].left, text.find("peers[]").unwrap());
+    assert_eq!(span_text(text, definitions[1]), "peers[]");
+}
+
+#[test]
+fn definition_of_fold_iterator() {
+    let text = r#"
+        (seq
+            (call "peer" ("service" "function") [] i)
+            (fold i i
+                (seq
+                    (call i ("service" "function") [])
+                    (next i)
+                )
+            )
+        )
+        "#;
+    let use_offset = text.find("(next i)").unwrap() + 6;
+
+    let definitions = definitions(text, use_offset);
+
+    assert_eq!(
+        definitions,
+        vec![Span::new(
+            text.find("(fold i i").unwrap() + 8,
+            text.find("(fold i i").unwrap() + 9
+        )]
+    );
+}
+
+#[test]
+fn definition_of_not_a_variable() {
+    let text = r#"(call "peer" ("service" "function") [] result)"#;
+
+    assert!(definitions(text, 2).is_empty());
+    assert!(definitions(text, 8).is_empty());
+}
+
+#[test]
+fn hover_shows_innermost_instruction() {
+    let text = r#"(seq (null) (fold iterable i (next i)))"#;
+
+    let (span, description) = hover(text, text.find("next").unwrap()).unwrap();
+    assert_eq!(span_text(text, span), "(next i)");
+    assert!(description.starts_with("**next**"));
+
+    let (span, description) = hover(text, text.find("iterable").unwrap()).unwrap();
+    assert_eq!(span_text(text, span), "(fold iterable i (next i))");
+    assert!(description.starts_with("**fold**"));
+
+    let (span, description) = hover(text, 0).unwrap();
+    assert_eq!(span, Span::new(0, text.len()));
+    assert!(description.starts_with("**seq**"));
+}
+
+#[test]
+fn hover_after_procedure_definition() {
+    let text = r#"(define greet [peer] (call peer ("hello" "greet") [])) (invoke greet ["peer"])"#;
+
+    let (span, description) = hover(text, text.find("call").unwrap()).unwrap();
+    assert_eq!(span_text(text, span), r#"(call peer ("hello" "greet") [])"#);
+    assert!(description.starts_with("**call**"));
+
+    let (span, description) = hover(text, text.find("invoke").unwrap()).unwrap();
+    assert_eq!(span_text(text, span), r#"(invoke greet ["peer"])"#);
+    assert!(description.starts_with("**invoke**"));
+}
+
+#[test]
+fn format_valid_script_only() {
+    assert_eq!(
+        format("(seq (null) (null))").unwrap(),
+        "(seq\n    (null)\n    (null)\n)\n"
+    );
+    assert!(format("(seq (null)").is_none());
+}
@@ -0,0 +1,86 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::protocol::Position;
+use crate::protocol::Range;
+
+use air_parser::Span;
+
+/// Converts byte offsets used by the parser to LSP positions and back.
+pub(crate) struct LineIndex<'t> {
+    text: &'t str,
+    // byte offsets of line starts
+    line_starts: Vec<usize>,
+}
+
+impl<'t> LineIndex<'t> {
+    pub(crate) fn new(text: &'t str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(pos, _)| pos + 1))
+            .collect();
+
+        Self { text, line_starts }
+    }
+
+    pub(crate) fn position(&self, offset: usize) -> Position {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+        let character = self.text[self.line_starts[line]..offset]
+            .encode_utf16()
+            .count();
+
+        Position::new(line as u32, character as u32)
+    }
+
+    pub(crate) fn offset(&self, position: Position) -> usize {
+        let line = position.line as usize;
+        let line_start = match self.line_starts.get(line) {
+            Some(&line_start) => line_start,
+            None => return self.text.len(),
+        };
+        let line_end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(self.text.len(), |next_line_start| next_line_start - 1);
+
+        let mut character = 0;
+        for (pos, ch) in self.text[line_start..line_end].char_indices() {
+            if character >= position.character as usize {
+                return line_start + pos;
+            }
+            character += ch.len_utf16();
+        }
+
+        line_end
+    }
+
+    pub(crate) fn range(&self, span: Span) -> Range {
+        Range {
+            start: self.position(span.left),
+            end: self.position(span.right),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,43 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::*;
+
+#[test]
+fn positions_are_in_utf16() {
+    let text = "(seq\n  (call \"ключ\" (\"𝔸\" \"f\") [] r)\n)";
+    let line_index = LineIndex::new(text);
+
+    let f_offset = text.find("\"f\"").unwrap();
+    // "𝔸" is encoded with a surrogate pair in UTF-16
+    let f_position = Position::new(1, 21);
+    assert_eq!(line_index.position(f_offset), f_position);
+    assert_eq!(line_index.offset(f_position), f_offset);
+
+    assert_eq!(line_index.position(0), Position::new(0, 0));
+    assert_eq!(line_index.position(text.len()), Position::new(2, 1));
+}
+
+#[test]
+fn out_of_range_positions() {
+    let text = "(seq\n(null) (null))";
+    let line_index = LineIndex::new(text);
+
+    // characters past a line end point to the line end
+    assert_eq!(line_index.offset(Position::new(0, 100)), 4);
+    assert_eq!(line_index.offset(Position::new(100, 0)), text.len());
+    assert_eq!(line_index.position(text.len() + 100), Position::new(1, 14));
+}
@@ -0,0 +1,48 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Language server for AIR scripts, it communicates with an editor over stdio
+//! using the Language Server Protocol.
+
+#![warn(rust_2018_idioms)]
+#![deny(
+    dead_code,
+    nonstandard_style,
+    unused_imports,
+    unused_mut,
+    unused_variables,
+    unused_unsafe,
+    unreachable_patterns
+)]
+
+mod analysis;
+mod line_index;
+mod protocol;
+mod server;
+mod transport;
+
+use server::Server;
+
+pub fn main() {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+
+    let mut server = Server::new(stdin.lock(), stdout.lock());
+    if let Err(err) = server.run() {
+        eprintln!("air language server stopped with an error: {}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,194 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A subset of the Language Server Protocol types used by the server.
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value as JValue;
+
+pub(crate) const PARSE_ERROR: i64 = -32700;
+pub(crate) const INVALID_PARAMS: i64 = -32602;
+pub(crate) const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Documents are synchronized by sending their full content.
+pub(crate) const FULL_TEXT_DOCUMENT_SYNC: u8 = 1;
+
+/// An incoming message, responses are not expected since the server doesn't send requests.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Message {
+    Request {
+        id: JValue,
+        method: String,
+        params: JValue,
+    },
+    Notification {
+        method: String,
+        params: JValue,
+    },
+    Response,
+}
+
+impl Message {
+    pub(crate) fn from_json(mut message: JValue) -> Option<Self> {
+        let params = message.get_mut("params").map_or(JValue::Null, JValue::take);
+        let method = message
+            .get("method")
+            .and_then(JValue::as_str)
+            .map(String::from);
+
+        match (message.get_mut("id").map(JValue::take), method) {
+            (Some(id), Some(method)) => Some(Message::Request { id, method, params }),
+            (None, Some(method)) => Some(Message::Notification { method, params }),
+            (Some(_), None) => Some(Message::Response),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ResponseError {
+    pub(crate) code: i64,
+    pub(crate) message: String,
+}
+
+impl ResponseError {
+    pub(crate) fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Position in a document, character is an offset in UTF-16 code units.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Position {
+    pub(crate) line: u32,
+    pub(crate) character: u32,
+}
+
+impl Position {
+    pub(crate) fn new(line: u32, character: u32) -> Self {
+        Self { line, character }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Range {
+    pub(crate) start: Position,
+    pub(crate) end: Position,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Location {
+    pub(crate) uri: String,
+    pub(crate) range: Range,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DiagnosticSeverity {
+    Error = 1,
+    Warning = 2,
+}
+
+impl Serialize for DiagnosticSeverity {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Diagnostic {
+    pub(crate) range: Range,
+    pub(crate) severity: DiagnosticSeverity,
+    pub(crate) source: &'static str,
+    pub(crate) message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PublishDiagnosticsParams {
+    pub(crate) uri: String,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct MarkupContent {
+    pub(crate) kind: &'static str,
+    pub(crate) value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Hover {
+    pub(crate) contents: MarkupContent,
+    pub(crate) range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TextEdit {
+    pub(crate) range: Range,
+    pub(crate) new_text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub(crate) struct TextDocumentIdentifier {
+    pub(crate) uri: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub(crate) struct TextDocumentItem {
+    pub(crate) uri: String,
+    pub(crate) text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TextDocumentPositionParams {
+    pub(crate) text_document: TextDocumentIdentifier,
+    pub(crate) position: Position,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DidOpenTextDocumentParams {
+    pub(crate) text_document: TextDocumentItem,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub(crate) struct TextDocumentContentChangeEvent {
+    pub(crate) text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DidChangeTextDocumentParams {
+    pub(crate) text_document: TextDocumentIdentifier,
+    pub(crate) content_changes: Vec<TextDocumentContentChangeEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DidCloseTextDocumentParams {
+    pub(crate) text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DocumentFormattingParams {
+    pub(crate) text_document: TextDocumentIdentifier,
+}
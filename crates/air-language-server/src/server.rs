@@ -0,0 +1,254 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::analysis;
+use crate::analysis::Severity;
+use crate::line_index::LineIndex;
+use crate::protocol::*;
+use crate::transport;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+use serde_json::Value as JValue;
+
+use std::collections::HashMap;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+const SOURCE: &str = "air";
+
+type RequestResult = Result<JValue, ResponseError>;
+
+/// Serves requests of one client until it sends the exit notification or closes the stream.
+pub(crate) struct Server<R, W> {
+    reader: R,
+    writer: W,
+    // texts of opened documents by their uri
+    documents: HashMap<String, String>,
+}
+
+impl<R: BufRead, W: Write> Server<R, W> {
+    pub(crate) fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader,
+            writer,
+            documents: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn run(&mut self) -> io::Result<()> {
+        while let Some(message) = transport::read_message(&mut self.reader)? {
+            let message = match message {
+                Ok(message) => message,
+                Err(e) => {
+                    // the id of a request that can't be parsed is unknown
+                    let error = ResponseError::new(PARSE_ERROR, e.to_string());
+                    self.send_response(JValue::Null, Err(error))?;
+                    continue;
+                }
+            };
+
+            match Message::from_json(message) {
+                Some(Message::Request { id, method, params }) => {
+                    let result = self.handle_request(&method, params);
+                    self.send_response(id, result)?;
+                }
+                Some(Message::Notification { method, .. }) if method == "exit" => return Ok(()),
+                Some(Message::Notification { method, params }) => {
+                    self.handle_notification(&method, params)?
+                }
+                Some(Message::Response) | None => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&mut self, method: &str, params: JValue) -> RequestResult {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": FULL_TEXT_DOCUMENT_SYNC,
+                    "definitionProvider": true,
+                    "hoverProvider": true,
+                    "documentFormattingProvider": true,
+                },
+                "serverInfo": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                }
+            })),
+            "shutdown" => Ok(JValue::Null),
+            "textDocument/definition" => {
+                let params: TextDocumentPositionParams = from_params(params)?;
+                let text = self.document(&params.text_document.uri)?;
+                let line_index = LineIndex::new(text);
+
+                let offset = line_index.offset(params.position);
+                let locations = analysis::definitions(text, offset)
+                    .into_iter()
+                    .map(|span| Location {
+                        uri: params.text_document.uri.clone(),
+                        range: line_index.range(span),
+                    })
+                    .collect::<Vec<_>>();
+
+                to_result(locations)
+            }
+            "textDocument/hover" => {
+                let params: TextDocumentPositionParams = from_params(params)?;
+                let text = self.document(&params.text_document.uri)?;
+                let line_index = LineIndex::new(text);
+
+                let offset = line_index.offset(params.position);
+                let hover = analysis::hover(text, offset).map(|(span, description)| Hover {
+                    contents: MarkupContent {
+                        kind: "markdown",
+                        value: description,
+                    },
+                    range: line_index.range(span),
+                });
+
+                to_result(hover)
+            }
+            "textDocument/formatting" => {
+                let params: DocumentFormattingParams = from_params(params)?;
+                let text = self.document(&params.text_document.uri)?;
+                let line_index = LineIndex::new(text);
+
+                // the whole document is replaced, scripts that can't be parsed aren't formatted
+                let edits = analysis::format(text).map(|formatted| {
+                    vec![TextEdit {
+                        range: Range {
+                            start: Position::default(),
+                            end: line_index.position(text.len()),
+                        },
+                        new_text: formatted,
+                    }]
+                });
+
+                to_result(edits)
+            }
+            method => Err(ResponseError::new(
+                METHOD_NOT_FOUND,
+                format!("method '{}' isn't supported", method),
+            )),
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: JValue) -> io::Result<()> {
+        // there is no way to report errors of notifications, so malformed ones are ignored
+        match method {
+            "textDocument/didOpen" => {
+                if let Ok(params) = from_params::<DidOpenTextDocumentParams>(params) {
+                    let document = params.text_document;
+                    self.documents.insert(document.uri.clone(), document.text);
+                    self.publish_diagnostics(document.uri)?;
+                }
+            }
+            "textDocument/didChange" => {
+                if let Ok(mut params) = from_params::<DidChangeTextDocumentParams>(params) {
+                    // with the full sync each change contains the whole text
+                    if let Some(change) = params.content_changes.pop() {
+                        let uri = params.text_document.uri;
+                        self.documents.insert(uri.clone(), change.text);
+                        self.publish_diagnostics(uri)?;
+                    }
+                }
+            }
+            "textDocument/didClose" => {
+                if let Ok(params) = from_params::<DidCloseTextDocumentParams>(params) {
+                    let uri = params.text_document.uri;
+                    self.documents.remove(&uri);
+                    // clear diagnostics of the closed document
+                    self.send_notification(
+                        "textDocument/publishDiagnostics",
+                        PublishDiagnosticsParams {
+                            uri,
+                            diagnostics: vec![],
+                        },
+                    )?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn publish_diagnostics(&mut self, uri: String) -> io::Result<()> {
+        let text = match self.documents.get(&uri) {
+            Some(text) => text,
+            None => return Ok(()),
+        };
+        let line_index = LineIndex::new(text);
+
+        let diagnostics = analysis::diagnostics(text)
+            .into_iter()
+            .map(|diagnostic| Diagnostic {
+                range: line_index.range(diagnostic.span),
+                severity: match diagnostic.severity {
+                    Severity::Error => DiagnosticSeverity::Error,
+                    Severity::Warning => DiagnosticSeverity::Warning,
+                },
+                source: SOURCE,
+                message: diagnostic.message,
+            })
+            .collect();
+
+        let params = PublishDiagnosticsParams { uri, diagnostics };
+        self.send_notification("textDocument/publishDiagnostics", params)
+    }
+
+    fn document(&self, uri: &str) -> Result<&str, ResponseError> {
+        self.documents.get(uri).map(String::as_str).ok_or_else(|| {
+            ResponseError::new(INVALID_PARAMS, format!("document '{}' isn't opened", uri))
+        })
+    }
+
+    fn send_response(&mut self, id: JValue, result: RequestResult) -> io::Result<()> {
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": error.code, "message": error.message },
+            }),
+        };
+
+        transport::write_message(&mut self.writer, &response)
+    }
+
+    fn send_notification(&mut self, method: &str, params: impl Serialize) -> io::Result<()> {
+        let params = serde_json::to_value(params)?;
+        let notification = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+
+        transport::write_message(&mut self.writer, &notification)
+    }
+}
+
+fn from_params<T: DeserializeOwned>(params: JValue) -> Result<T, ResponseError> {
+    serde_json::from_value(params).map_err(|e| ResponseError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn to_result(result: impl Serialize) -> RequestResult {
+    Ok(serde_json::to_value(result).expect("protocol types are always serializable"))
+}
+
+#[cfg(test)]
+mod tests;
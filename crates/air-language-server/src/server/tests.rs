@@ -0,0 +1,201 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::*;
+
+use serde_json::json;
+
+fn frame(message: JValue) -> String {
+    let content = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{}", content.len(), content)
+}
+
+/// Runs the server over supplied messages and returns all messages it sent.
+fn run_session(messages: Vec<JValue>) -> Vec<JValue> {
+    let input = messages.into_iter().map(frame).collect::<String>();
+    run_raw_session(&input)
+}
+
+/// Runs the server over already framed input and returns all messages it sent.
+fn run_raw_session(input: &str) -> Vec<JValue> {
+    let mut output = vec![];
+
+    Server::new(input.as_bytes(), &mut output)
+        .run()
+        .expect("session failed");
+
+    let mut reader = output.as_slice();
+    let mut sent = vec![];
+    while let Some(message) = transport::read_message(&mut reader).expect("invalid output") {
+        sent.push(message.expect("invalid json"));
+    }
+
+    sent
+}
+
+fn request(id: u32, method: &str, params: JValue) -> JValue {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+fn notification(method: &str, params: JValue) -> JValue {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+const URI: &str = "file:///script.clj";
+
+fn did_open(text: &str) -> JValue {
+    notification(
+        "textDocument/didOpen",
+        json!({ "textDocument": { "uri": URI, "languageId": "air", "version": 1, "text": text } }),
+    )
+}
+
+#[test]
+fn initialize_and_shutdown() {
+    let sent = run_session(vec![
+        request(1, "initialize", json!({ "capabilities": {} })),
+        notification("initialized", json!({})),
+        request(2, "shutdown", JValue::Null),
+        notification("exit", JValue::Null),
+        // messages after exit aren't handled
+        request(3, "shutdown", JValue::Null),
+    ]);
+
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[0]["id"], 1);
+    assert_eq!(
+        sent[0]["result"]["capabilities"]["definitionProvider"],
+        true
+    );
+    assert_eq!(sent[0]["result"]["capabilities"]["hoverProvider"], true);
+    assert_eq!(
+        sent[0]["result"]["capabilities"]["documentFormattingProvider"],
+        true
+    );
+    assert_eq!(
+        sent[1],
+        json!({ "jsonrpc": "2.0", "id": 2, "result": null })
+    );
+}
+
+#[test]
+fn diagnostics_are_published() {
+    let sent = run_session(vec![
+        did_open("(seq (null) (next i))"),
+        notification(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": URI, "version": 2 },
+                "contentChanges": [{ "text": "(seq (null) (null))" }],
+            }),
+        ),
+        notification(
+            "textDocument/didClose",
+            json!({ "textDocument": { "uri": URI } }),
+        ),
+    ]);
+
+    assert_eq!(sent.len(), 3);
+    let diagnostics = &sent[0]["params"]["diagnostics"];
+    assert_eq!(sent[0]["method"], "textDocument/publishDiagnostics");
+    assert_eq!(sent[0]["params"]["uri"], URI);
+    assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+    assert_eq!(diagnostics[0]["severity"], 1);
+    assert_eq!(
+        diagnostics[0]["range"],
+        json!({ "start": { "line": 0, "character": 12 }, "end": { "line": 0, "character": 20 } })
+    );
+
+    assert_eq!(sent[1]["params"]["diagnostics"], json!([]));
+    assert_eq!(sent[2]["params"]["diagnostics"], json!([]));
+}
+
+#[test]
+fn document_requests() {
+    let text = "(seq\n    (call \"peer\" (\"service\" \"function\") [] result)\n    (call result (\"service\" \"function\") []))";
+    let position =
+        json!({ "textDocument": { "uri": URI }, "position": { "line": 2, "character": 12 } });
+
+    let sent = run_session(vec![
+        did_open(text),
+        request(1, "textDocument/definition", position.clone()),
+        request(2, "textDocument/hover", position),
+        request(
+            3,
+            "textDocument/formatting",
+            json!({ "textDocument": { "uri": URI }, "options": {} }),
+        ),
+    ]);
+
+    assert_eq!(sent.len(), 4);
+    assert_eq!(
+        sent[1]["result"],
+        json!([{
+            "uri": URI,
+            "range": { "start": { "line": 1, "character": 43 }, "end": { "line": 1, "character": 49 } },
+        }])
+    );
+    assert!(sent[2]["result"]["contents"]["value"]
+        .as_str()
+        .unwrap()
+        .starts_with("**call**"));
+    assert_eq!(
+        sent[3]["result"][0]["range"]["end"],
+        json!({ "line": 2, "character": 44 })
+    );
+    assert_eq!(
+        sent[3]["result"][0]["newText"],
+        "(seq\n    (call \"peer\" (\"service\" \"function\") [] result)\n    (call result (\"service\" \"function\") [])\n)\n"
+    );
+}
+
+#[test]
+fn unsupported_requests() {
+    let sent = run_session(vec![
+        request(1, "textDocument/completion", json!({})),
+        request(
+            2,
+            "textDocument/hover",
+            json!({ "textDocument": { "uri": URI }, "position": { "line": 0, "character": 0 } }),
+        ),
+        request(3, "textDocument/hover", json!({ "position": 1 })),
+    ]);
+
+    assert_eq!(sent.len(), 3);
+    assert_eq!(sent[0]["error"]["code"], METHOD_NOT_FOUND);
+    assert_eq!(sent[1]["error"]["code"], INVALID_PARAMS);
+    assert_eq!(sent[2]["error"]["code"], INVALID_PARAMS);
+}
+
+#[test]
+fn malformed_json_is_reported() {
+    let bad_content = r#"{"jsonrpc": "2.0", "id": 1, "method": "#;
+    let bad_frame = format!(
+        "Content-Length: {}\r\n\r\n{}",
+        bad_content.len(),
+        bad_content
+    );
+    let input = bad_frame + &frame(request(2, "shutdown", JValue::Null));
+
+    let sent = run_raw_session(&input);
+
+    // the server keeps serving requests after a malformed one
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[0]["id"], JValue::Null);
+    assert_eq!(sent[0]["error"]["code"], PARSE_ERROR);
+    assert_eq!(sent[1]["id"], 2);
+    assert_eq!(sent[1]["result"], JValue::Null);
+}
@@ -0,0 +1,86 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use serde_json::Value as JValue;
+
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+const CONTENT_LENGTH: &str = "Content-Length";
+
+/// Read one message framed with the base protocol headers, returns None if the stream is closed.
+/// A frame with malformed JSON content is read completely, so the next message could be read
+/// after its error is reported.
+pub(crate) fn read_message(
+    reader: &mut impl BufRead,
+) -> io::Result<Option<serde_json::Result<JValue>>> {
+    let mut content_length = None;
+
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        // other headers, like Content-Type, don't affect the content
+        if let Some((name, value)) = split_header(header) {
+            if name.eq_ignore_ascii_case(CONTENT_LENGTH) {
+                let length = value
+                    .parse::<usize>()
+                    .map_err(|e| invalid_data(e.to_string()))?;
+                content_length = Some(length);
+            }
+        }
+    }
+
+    let content_length = content_length
+        .ok_or_else(|| invalid_data(format!("message without the {} header", CONTENT_LENGTH)))?;
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+
+    Ok(Some(serde_json::from_slice(&content)))
+}
+
+/// Write a message with the base protocol headers.
+pub(crate) fn write_message(writer: &mut impl Write, message: &JValue) -> io::Result<()> {
+    let content = serde_json::to_string(message)?;
+
+    write!(
+        writer,
+        "{}: {}\r\n\r\n{}",
+        CONTENT_LENGTH,
+        content.len(),
+        content
+    )?;
+    writer.flush()
+}
+
+fn split_header(header: &str) -> Option<(&str, &str)> {
+    let colon_pos = header.find(':')?;
+    let (name, value) = header.split_at(colon_pos);
+
+    Some((name.trim(), value[1..].trim()))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -27,9 +27,23 @@
 mod parser;
 
 pub use parser::ast;
+pub use parser::format;
+pub use parser::line_column_to_offset;
+pub use parser::offset_to_line_column;
 pub use parser::parse;
+pub use parser::validate;
 pub use parser::AIRLexer;
 pub use parser::AIRParser;
+pub use parser::LexerError;
+pub use parser::ParsedScript;
+pub use parser::ParserError;
+pub use parser::ParserErrorKind;
+pub use parser::SemanticDiagnostic;
+pub use parser::SemanticDiagnosticKind;
+pub use parser::Span;
+pub use parser::SpannedError;
+pub use parser::Token;
+pub use parser::ValidationReport;
 
 #[cfg(test)]
 #[macro_use]
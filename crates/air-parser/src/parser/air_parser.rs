@@ -16,15 +16,10 @@
 
 use super::air;
 use super::ast::Instruction;
+use super::ast::Span;
 use super::lexer::AIRLexer;
-use super::lexer::LexerError;
-use super::lexer::Token;
-
-use codespan_reporting::diagnostic::{Diagnostic, Label};
-use codespan_reporting::files::SimpleFiles;
-use codespan_reporting::term;
-use codespan_reporting::term::termcolor::{Buffer, ColorChoice, StandardStream};
-use lalrpop_util::{ErrorRecovery, ParseError};
+use super::ParserError;
+use super::SpannedError;
 
 use air::AIRParser;
 
@@ -33,101 +28,38 @@ use air::AIRParser;
 thread_local!(static PARSER: AIRParser = AIRParser::new());
 
 /// Parse AIR `source_code` to `Box<Instruction>`
-pub fn parse(air_script: &str) -> Result<Box<Instruction<'_>>, String> {
-    let mut files = SimpleFiles::new();
-    let file_id = files.add("script.aqua", air_script);
-
+pub fn parse(air_script: &str) -> Result<Box<Instruction<'_>>, ParserError> {
     PARSER.with(|parser| {
         let mut errors = Vec::new();
         let lexer = AIRLexer::new(air_script);
         match parser.parse(air_script, &mut errors, lexer) {
             Ok(r) if errors.is_empty() => Ok(r),
-            Ok(_) => Err(report_errors(file_id, files, errors)),
-            Err(err) => Err(report_errors(
-                file_id,
-                files,
-                vec![ErrorRecovery {
-                    error: err,
-                    dropped_tokens: vec![],
-                }],
-            )),
+            Ok(_) => Err(ParserError::from(errors)),
+            Err(err) => Err(ParserError {
+                errors: vec![SpannedError::from(err)],
+            }),
         }
     })
 }
 
-fn report_errors(
-    file_id: usize,
-    files: SimpleFiles<&str, &str>,
-    errors: Vec<ErrorRecovery<usize, Token<'_>, LexerError>>,
-) -> String {
-    let labels = errors_to_labels(file_id, errors);
-    let diagnostic = Diagnostic::error().with_labels(labels);
-
-    // Write to stderr
-    let writer = StandardStream::stderr(ColorChoice::Auto);
-    let config = codespan_reporting::term::Config::default();
-    term::emit(&mut writer.lock(), &config, &files, &diagnostic).expect("term emit to stderr");
-
-    // Return as a string
-    let mut buffer = Buffer::no_color();
-    term::emit(&mut buffer, &config, &files, &diagnostic).expect("term emit to buffer");
-    String::from_utf8_lossy(buffer.as_slice())
-        .as_ref()
-        .to_string()
-}
-
-fn errors_to_labels(
-    file_id: usize,
-    errors: Vec<ErrorRecovery<usize, Token<'_>, LexerError>>,
-) -> Vec<Label<usize>> {
-    errors
-        .into_iter()
-        .map(|err| match err.error {
-            ParseError::UnrecognizedToken {
-                token: (start, _, end),
-                expected,
-            } => Label::primary(file_id, start..end)
-                .with_message(format!("expected {}", pretty_expected(expected))),
-            ParseError::InvalidToken { location } => {
-                Label::primary(file_id, location..(location + 1)).with_message("unexpected token")
-            }
-            ParseError::ExtraToken {
-                token: (start, _, end),
-            } => Label::primary(file_id, start..end).with_message("extra token"),
-            ParseError::UnrecognizedEOF { location, expected } => {
-                Label::primary(file_id, location..(location + 1))
-                    .with_message(format!("expected {}", pretty_expected(expected)))
-            }
-            ParseError::User { error } => lexical_error_to_label(file_id, error),
-        })
-        .collect()
-}
-
-fn pretty_expected(expected: Vec<String>) -> String {
-    if expected.is_empty() {
-        "<nothing>".to_string()
-    } else {
-        expected.join(" or ")
+/// Desugar an n-ary composite instruction like `(seq i1 i2 i3)` into right-nested binary ones
+/// `(seq i1 (seq i2 i3))`, so that execution and the trace format stay the same.
+/// Nested instructions span from their first child to the last one.
+pub(super) fn desugar_nary<'i>(
+    head: Box<Instruction<'i>>,
+    mut tail: Vec<Instruction<'i>>,
+    span: Span,
+    make_binary: impl Fn(Box<Instruction<'i>>, Box<Instruction<'i>>, Span) -> Instruction<'i>,
+) -> Box<Instruction<'i>> {
+    // grammar guarantees that there is at least one instruction in the tail
+    let mut nested = Box::new(
+        tail.pop()
+            .expect("tail of n-ary instruction can't be empty"),
+    );
+    while let Some(previous) = tail.pop() {
+        let nested_span = Span::new(previous.span().left, nested.span().right);
+        nested = Box::new(make_binary(Box::new(previous), nested, nested_span));
     }
-}
 
-fn lexical_error_to_label(file_id: usize, error: LexerError) -> Label<usize> {
-    use LexerError::*;
-    match error {
-        UnclosedQuote(start, end) => {
-            Label::primary(file_id, start..end).with_message(error.to_string())
-        }
-        EmptyString(start, end) => {
-            Label::primary(file_id, start..end).with_message(error.to_string())
-        }
-        IsNotAlphanumeric(start, end) => {
-            Label::primary(file_id, start..end).with_message(error.to_string())
-        }
-        EmptyAccName(start, end) => {
-            Label::primary(file_id, start..end).with_message(error.to_string())
-        }
-        InvalidJsonPath(start, end) => {
-            Label::primary(file_id, start..end).with_message(error.to_string())
-        }
-    }
+    Box::new(make_binary(head, nested, span))
 }
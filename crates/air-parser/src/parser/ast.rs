@@ -14,106 +14,331 @@
  * limitations under the License.
  */
 
+mod json_path;
+mod owned;
+mod traits;
+pub mod visitor;
+
+pub use super::span::Span;
+pub use json_path::CompiledJsonPath;
+pub use visitor::Visitor;
+pub use visitor::VisitorMut;
+
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::Number;
+use serde_json::Value as JValue;
 
+use std::borrow::Cow;
 use std::rc::Rc;
 
 #[allow(clippy::large_enum_variant)] // for Null and Error variants
-#[derive(Serialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum Instruction<'i> {
     Null(Null),
     Call(Call<'i>),
+    Ap(Ap<'i>),
     Seq(Seq<'i>),
     Par(Par<'i>),
     Xor(Xor<'i>),
     Match(Match<'i>),
     MisMatch(MisMatch<'i>),
+    Compare(Compare<'i>),
     Fold(Fold<'i>),
+    New(New<'i>),
     Next(Next<'i>),
+    Fail(Fail<'i>),
+    Define(Define<'i>),
+    Invoke(Invoke<'i>),
     Error,
 }
 
-#[derive(Serialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum PeerPart<'i> {
     PeerPk(CallArgValue<'i>),
     PeerPkWithServiceId(CallArgValue<'i>, CallArgValue<'i>),
+    /// An array of peer ids, the call is executed on each of them.
+    PeerPks(CallArgValue<'i>),
+    PeerPksWithServiceId(CallArgValue<'i>, CallArgValue<'i>),
 }
 
-#[derive(Serialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum FunctionPart<'i> {
     FuncName(CallArgValue<'i>),
     ServiceIdWithFuncName(CallArgValue<'i>, CallArgValue<'i>),
 }
 
-#[derive(Serialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Call<'i> {
     pub peer_part: PeerPart<'i>,
     pub function_part: FunctionPart<'i>,
     pub args: Rc<Vec<CallArgValue<'i>>>,
     pub output: CallOutputValue<'i>,
+    pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+/// Sets `output` to `value` locally, without calling any service.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Ap<'i> {
+    pub value: CallArgValue<'i>,
+    pub output: CallOutputValue<'i>,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum CallArgValue<'i> {
     InitPeerId,
-    Literal(&'i str),
-    Variable(&'i str),
-    JsonPath { variable: &'i str, path: &'i str },
+    /// Id of a peer executing the script.
+    CurrentPeerId,
+    /// Id of a particle carrying the script.
+    ParticleId,
+    /// Particle creation time in milliseconds since the Unix epoch.
+    Timestamp,
+    /// Particle time to live in milliseconds.
+    Ttl,
+    Literal(Cow<'i, str>),
+    Number(Number),
+    Boolean(bool),
+    Null,
+    /// An array or an object literal.
+    Json(JValue),
+    /// A variable with a part of the script it's referred in.
+    Variable(Cow<'i, str>, Span),
+    JsonPath {
+        variable: Cow<'i, str>,
+        path: CompiledJsonPath<'i>,
+        span: Span,
+    },
+    /// An error caught by the innermost xor, optionally with a json path applied to it.
+    LastError(Option<CompiledJsonPath<'i>>),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
 pub enum IterableValue<'i> {
-    Variable(&'i str),
-    JsonPath { variable: &'i str, path: &'i str },
+    /// A variable with a part of the script it's referred in.
+    Variable(Cow<'i, str>, Span),
+    JsonPath {
+        variable: Cow<'i, str>,
+        path: CompiledJsonPath<'i>,
+        span: Span,
+    },
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum MatchableValue<'i> {
-    Literal(&'i str),
-    Variable(&'i str),
-    JsonPath { variable: &'i str, path: &'i str },
+    Literal(Cow<'i, str>),
+    Number(Number),
+    Boolean(bool),
+    Null,
+    /// An array or an object literal.
+    Json(JValue),
+    /// A variable with a part of the script it's referred in.
+    Variable(Cow<'i, str>, Span),
+    JsonPath {
+        variable: Cow<'i, str>,
+        path: CompiledJsonPath<'i>,
+        span: Span,
+    },
 }
 
-#[derive(Serialize, Debug, Hash, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum CallOutputValue<'i> {
-    Scalar(&'i str),
-    Accumulator(&'i str),
+    Scalar(Cow<'i, str>),
+    Accumulator(Cow<'i, str>),
     None,
 }
 
-#[derive(Serialize, Debug, PartialEq, Eq)]
-pub struct Seq<'i>(pub Box<Instruction<'i>>, pub Box<Instruction<'i>>);
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Seq<'i>(pub Box<Instruction<'i>>, pub Box<Instruction<'i>>, pub Span);
 
-#[derive(Serialize, Debug, PartialEq, Eq)]
-pub struct Par<'i>(pub Box<Instruction<'i>>, pub Box<Instruction<'i>>);
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Par<'i>(pub Box<Instruction<'i>>, pub Box<Instruction<'i>>, pub Span);
 
-#[derive(Serialize, Debug, PartialEq, Eq)]
-pub struct Xor<'i>(pub Box<Instruction<'i>>, pub Box<Instruction<'i>>);
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Xor<'i>(pub Box<Instruction<'i>>, pub Box<Instruction<'i>>, pub Span);
 
-#[derive(Serialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Match<'i> {
     pub left_value: MatchableValue<'i>,
     pub right_value: MatchableValue<'i>,
     pub instruction: Box<Instruction<'i>>,
+    pub span: Span,
 }
 
-#[derive(Serialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct MisMatch<'i> {
     pub left_value: MatchableValue<'i>,
     pub right_value: MatchableValue<'i>,
     pub instruction: Box<Instruction<'i>>,
+    pub span: Span,
+}
+
+/// Executes the instruction iff the comparison of values holds.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Compare<'i> {
+    pub operator: CompareOperator,
+    pub left_value: MatchableValue<'i>,
+    /// Absent for unary operators such as `is_empty`.
+    pub right_value: Option<MatchableValue<'i>>,
+    pub instruction: Box<Instruction<'i>>,
+    pub span: Span,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompareOperator {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    /// Checks that an array contains an element, an object contains a key
+    /// or a string contains a substring.
+    Contains,
+    /// Checks that the type of the left value is named by the right one.
+    TypeOf,
+    /// Checks that an array, object or string is empty.
+    IsEmpty,
+}
+
+impl CompareOperator {
+    /// Returns a keyword this operator is written with in a script.
+    pub fn keyword(&self) -> &'static str {
+        use CompareOperator::*;
+
+        match self {
+            Less => "lt",
+            LessOrEqual => "lte",
+            Greater => "gt",
+            GreaterOrEqual => "gte",
+            Contains => "contains",
+            TypeOf => "type_of",
+            IsEmpty => "is_empty",
+        }
+    }
+
+    /// Returns true for operators that take only one value.
+    pub fn is_unary(&self) -> bool {
+        matches!(self, CompareOperator::IsEmpty)
+    }
 }
 
-#[derive(Serialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Fold<'i> {
     pub iterable: IterableValue<'i>,
-    pub iterator: &'i str,
+    pub iterator: Cow<'i, str>,
     pub instruction: Rc<Instruction<'i>>,
+    pub span: Span,
+}
+
+/// Opens a lexical scope for the variable, it's undefined at the beginning of the scope
+/// and gets back its previous value at the end.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct New<'i> {
+    pub variable: CallOutputValue<'i>,
+    pub instruction: Box<Instruction<'i>>,
+    pub span: Span,
 }
 
-#[derive(Serialize, Debug, PartialEq, Eq)]
-pub struct Next<'i>(pub &'i str);
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Next<'i>(pub Cow<'i, str>, pub Span);
 
-#[derive(Serialize, Debug, PartialEq, Eq)]
-pub struct Null;
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Null(pub Span);
+
+/// Raises an error with the supplied code and message, it could be caught by an enclosing xor.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Fail<'i> {
+    pub ret_code: CallArgValue<'i>,
+    pub message: CallArgValue<'i>,
+    pub span: Span,
+}
+
+/// Defines a named procedure with parameters, it could be invoked from the instruction
+/// and from the following definitions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Define<'i> {
+    pub name: Cow<'i, str>,
+    pub parameters: Vec<Cow<'i, str>>,
+    /// A variable of the body returned to the invocation output.
+    pub output: Option<Cow<'i, str>>,
+    pub body: Box<Instruction<'i>>,
+    pub instruction: Box<Instruction<'i>>,
+    pub span: Span,
+}
+
+/// Executes the body of a defined procedure with parameters bound to the arguments.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Invoke<'i> {
+    pub name: Cow<'i, str>,
+    pub args: Rc<Vec<CallArgValue<'i>>>,
+    pub output: CallOutputValue<'i>,
+    pub span: Span,
+}
+
+impl PeerPart<'_> {
+    /// Returns true if the call is executed on every peer of an array.
+    pub fn is_fan_out(&self) -> bool {
+        matches!(
+            self,
+            PeerPart::PeerPks(_) | PeerPart::PeerPksWithServiceId(..)
+        )
+    }
+}
+
+impl CallArgValue<'_> {
+    /// Returns a part of the script a variable is referred in, other values are defined
+    /// by the script itself and always resolve, so they don't keep it.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            CallArgValue::Variable(_, span) | CallArgValue::JsonPath { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
+}
+
+impl IterableValue<'_> {
+    /// Returns a part of the script the iterable is referred in.
+    pub fn span(&self) -> Span {
+        match self {
+            IterableValue::Variable(_, span) | IterableValue::JsonPath { span, .. } => *span,
+        }
+    }
+}
+
+impl MatchableValue<'_> {
+    /// Returns a part of the script a variable is referred in, other values are defined
+    /// by the script itself and always resolve, so they don't keep it.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            MatchableValue::Variable(_, span) | MatchableValue::JsonPath { span, .. } => {
+                Some(*span)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'i> Instruction<'i> {
+    /// Returns a part of the script this instruction was parsed from.
+    pub fn span(&self) -> Span {
+        use Instruction::*;
+
+        match self {
+            Null(null) => null.0,
+            Call(call) => call.span,
+            Ap(ap) => ap.span,
+            Seq(seq) => seq.2,
+            Par(par) => par.2,
+            Xor(xor) => xor.2,
+            Match(match_) => match_.span,
+            MisMatch(mismatch) => mismatch.span,
+            Compare(compare) => compare.span,
+            Fold(fold) => fold.span,
+            New(new) => new.span,
+            Next(next) => next.1,
+            Fail(fail) => fail.span,
+            Define(define) => define.span,
+            Invoke(invoke) => invoke.span,
+            Error => Span::default(),
+        }
+    }
+}
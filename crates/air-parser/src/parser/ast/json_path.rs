@@ -0,0 +1,183 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use jsonpath_lib::Compiled;
+use jsonpath_lib::JsonPathError;
+use serde::de::Error as DeError;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use serde_json::Value as JValue;
+
+use std::borrow::Cow;
+use std::fmt;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::rc::Rc;
+
+/// A json path compiled while parsing, so it isn't re-parsed on each application.
+/// Compiled paths are compared, hashed and serialized by their source text.
+#[derive(Clone)]
+pub struct CompiledJsonPath<'i> {
+    path: Cow<'i, str>,
+    compiled: Rc<Compiled>,
+}
+
+impl<'i> CompiledJsonPath<'i> {
+    /// Compiles the supplied json path, returns a description of a syntax error on failure.
+    pub fn compile(path: impl Into<Cow<'i, str>>) -> Result<Self, String> {
+        let path = path.into();
+        let compiled = Compiled::compile(&path)?;
+
+        Ok(Self {
+            path,
+            compiled: Rc::new(compiled),
+        })
+    }
+
+    /// Converts the path to one that owns its source text.
+    pub fn into_owned(self) -> CompiledJsonPath<'static> {
+        CompiledJsonPath {
+            path: Cow::Owned(self.path.into_owned()),
+            compiled: self.compiled,
+        }
+    }
+
+    /// Returns the source text of this json path.
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns true if this path addresses a single place in a json,
+    /// i.e. it doesn't contain wildcards, filters, slices, unions or recursive descents.
+    pub fn is_definite(&self) -> bool {
+        let mut opened_quote = None;
+        let mut is_escaped = false;
+        let mut previous = None;
+
+        for ch in self.path.chars() {
+            match opened_quote {
+                Some(_) if is_escaped => is_escaped = false,
+                Some(_) if ch == '\\' => is_escaped = true,
+                Some(quote) if ch == quote => opened_quote = None,
+                Some(_) => {}
+                None if ch == '"' || ch == '\'' => opened_quote = Some(ch),
+                None if matches!(ch, '*' | '?' | ',' | ':') => return false,
+                None if ch == '.' && previous == Some('.') => return false,
+                None => {}
+            }
+            previous = Some(ch);
+        }
+
+        true
+    }
+
+    /// Selects all values of the supplied json matched by this path.
+    pub fn select<'v>(&self, value: &'v JValue) -> Result<Vec<&'v JValue>, JsonPathError> {
+        self.compiled.select(value)
+    }
+
+    /// Selects values matched by this path from an array formed by the supplied values,
+    /// also returns the index of a value each selected one was taken from.
+    pub fn select_with_iter<'v>(
+        &self,
+        values: impl Iterator<Item = &'v JValue>,
+    ) -> Result<(Vec<&'v JValue>, Vec<usize>), JsonPathError> {
+        let values = values.collect::<Vec<_>>();
+        let elements = values.iter().map(|&value| value.clone()).collect();
+        let array = JValue::Array(elements);
+
+        let mut selected_values = Vec::new();
+        let mut indices = Vec::new();
+        // selected values borrow the array copy, so they are mapped back to the original ones
+        for selected in self.compiled.select(&array)? {
+            for (id, &value) in values.iter().enumerate() {
+                let element = &array[id];
+                if let Some(value) = find_counterpart(element, value, selected) {
+                    selected_values.push(value);
+                    indices.push(id);
+                    break;
+                }
+            }
+        }
+
+        Ok((selected_values, indices))
+    }
+}
+
+/// Returns a value of the original json at the same place as the target one in its copy.
+fn find_counterpart<'v>(
+    copy: &JValue,
+    original: &'v JValue,
+    target: &JValue,
+) -> Option<&'v JValue> {
+    if std::ptr::eq(copy, target) {
+        return Some(original);
+    }
+
+    match (copy, original) {
+        (JValue::Array(copies), JValue::Array(originals)) => copies
+            .iter()
+            .zip(originals.iter())
+            .find_map(|(copy, original)| find_counterpart(copy, original, target)),
+        (JValue::Object(copies), JValue::Object(originals)) => copies
+            .values()
+            .zip(originals.values())
+            .find_map(|(copy, original)| find_counterpart(copy, original, target)),
+        _ => None,
+    }
+}
+
+impl PartialEq for CompiledJsonPath<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl Eq for CompiledJsonPath<'_> {}
+
+impl Hash for CompiledJsonPath<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.path.hash(state)
+    }
+}
+
+impl fmt::Debug for CompiledJsonPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.path)
+    }
+}
+
+impl fmt::Display for CompiledJsonPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path)
+    }
+}
+
+impl Serialize for CompiledJsonPath<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.path)
+    }
+}
+
+impl<'de, 'i> Deserialize<'de> for CompiledJsonPath<'i> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // paths with escaped characters can't be borrowed from a serialized ast
+        let path = String::deserialize(deserializer)?;
+        Self::compile(path).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,240 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::*;
+
+impl Instruction<'_> {
+    /// Converts the instruction to one that owns all strings borrowed from a script,
+    /// so it could outlive the script.
+    pub fn into_owned(self) -> Instruction<'static> {
+        use Instruction::*;
+
+        match self {
+            Null(null) => Null(null),
+            Call(call) => Call(call.into_owned()),
+            Ap(ap) => Ap(ap.into_owned()),
+            Seq(super::Seq(left, right, span)) => Seq(super::Seq(boxed(left), boxed(right), span)),
+            Par(super::Par(left, right, span)) => Par(super::Par(boxed(left), boxed(right), span)),
+            Xor(super::Xor(left, right, span)) => Xor(super::Xor(boxed(left), boxed(right), span)),
+            Match(match_) => Match(super::Match {
+                left_value: match_.left_value.into_owned(),
+                right_value: match_.right_value.into_owned(),
+                instruction: boxed(match_.instruction),
+                span: match_.span,
+            }),
+            MisMatch(mismatch) => MisMatch(super::MisMatch {
+                left_value: mismatch.left_value.into_owned(),
+                right_value: mismatch.right_value.into_owned(),
+                instruction: boxed(mismatch.instruction),
+                span: mismatch.span,
+            }),
+            Compare(compare) => Compare(super::Compare {
+                operator: compare.operator,
+                left_value: compare.left_value.into_owned(),
+                right_value: compare.right_value.map(MatchableValue::into_owned),
+                instruction: boxed(compare.instruction),
+                span: compare.span,
+            }),
+            Fold(fold) => Fold(super::Fold {
+                iterable: fold.iterable.into_owned(),
+                iterator: owned(fold.iterator),
+                instruction: Rc::new(unwrap_or_clone(fold.instruction).into_owned()),
+                span: fold.span,
+            }),
+            New(new) => New(super::New {
+                variable: new.variable.into_owned(),
+                instruction: boxed(new.instruction),
+                span: new.span,
+            }),
+            Next(super::Next(iterator, span)) => Next(super::Next(owned(iterator), span)),
+            Fail(fail) => Fail(super::Fail {
+                ret_code: fail.ret_code.into_owned(),
+                message: fail.message.into_owned(),
+                span: fail.span,
+            }),
+            Define(define) => Define(super::Define {
+                name: owned(define.name),
+                parameters: define.parameters.into_iter().map(owned).collect(),
+                output: define.output.map(owned),
+                body: boxed(define.body),
+                instruction: boxed(define.instruction),
+                span: define.span,
+            }),
+            Invoke(invoke) => Invoke(super::Invoke {
+                name: owned(invoke.name),
+                args: owned_args(invoke.args),
+                output: invoke.output.into_owned(),
+                span: invoke.span,
+            }),
+            Error => Error,
+        }
+    }
+}
+
+impl Call<'_> {
+    pub fn into_owned(self) -> Call<'static> {
+        Call {
+            peer_part: self.peer_part.into_owned(),
+            function_part: self.function_part.into_owned(),
+            args: owned_args(self.args),
+            output: self.output.into_owned(),
+            span: self.span,
+        }
+    }
+}
+
+impl Ap<'_> {
+    pub fn into_owned(self) -> Ap<'static> {
+        Ap {
+            value: self.value.into_owned(),
+            output: self.output.into_owned(),
+            span: self.span,
+        }
+    }
+}
+
+impl PeerPart<'_> {
+    pub fn into_owned(self) -> PeerPart<'static> {
+        use PeerPart::*;
+
+        match self {
+            PeerPk(peer_pk) => PeerPk(peer_pk.into_owned()),
+            PeerPkWithServiceId(peer_pk, service_id) => {
+                PeerPkWithServiceId(peer_pk.into_owned(), service_id.into_owned())
+            }
+            PeerPks(peer_pks) => PeerPks(peer_pks.into_owned()),
+            PeerPksWithServiceId(peer_pks, service_id) => {
+                PeerPksWithServiceId(peer_pks.into_owned(), service_id.into_owned())
+            }
+        }
+    }
+}
+
+impl FunctionPart<'_> {
+    pub fn into_owned(self) -> FunctionPart<'static> {
+        use FunctionPart::*;
+
+        match self {
+            FuncName(func_name) => FuncName(func_name.into_owned()),
+            ServiceIdWithFuncName(service_id, func_name) => {
+                ServiceIdWithFuncName(service_id.into_owned(), func_name.into_owned())
+            }
+        }
+    }
+}
+
+impl CallArgValue<'_> {
+    pub fn into_owned(self) -> CallArgValue<'static> {
+        use CallArgValue::*;
+
+        match self {
+            InitPeerId => InitPeerId,
+            CurrentPeerId => CurrentPeerId,
+            ParticleId => ParticleId,
+            Timestamp => Timestamp,
+            Ttl => Ttl,
+            Literal(value) => Literal(owned(value)),
+            Number(value) => Number(value),
+            Boolean(value) => Boolean(value),
+            Null => Null,
+            Json(value) => Json(value),
+            Variable(name, span) => Variable(owned(name), span),
+            JsonPath {
+                variable,
+                path,
+                span,
+            } => JsonPath {
+                variable: owned(variable),
+                path: path.into_owned(),
+                span,
+            },
+            LastError(path) => LastError(path.map(CompiledJsonPath::into_owned)),
+        }
+    }
+}
+
+impl IterableValue<'_> {
+    pub fn into_owned(self) -> IterableValue<'static> {
+        use IterableValue::*;
+
+        match self {
+            Variable(name, span) => Variable(owned(name), span),
+            JsonPath {
+                variable,
+                path,
+                span,
+            } => JsonPath {
+                variable: owned(variable),
+                path: path.into_owned(),
+                span,
+            },
+        }
+    }
+}
+
+impl MatchableValue<'_> {
+    pub fn into_owned(self) -> MatchableValue<'static> {
+        use MatchableValue::*;
+
+        match self {
+            Literal(value) => Literal(owned(value)),
+            Number(value) => Number(value),
+            Boolean(value) => Boolean(value),
+            Null => Null,
+            Json(value) => Json(value),
+            Variable(name, span) => Variable(owned(name), span),
+            JsonPath {
+                variable,
+                path,
+                span,
+            } => JsonPath {
+                variable: owned(variable),
+                path: path.into_owned(),
+                span,
+            },
+        }
+    }
+}
+
+impl CallOutputValue<'_> {
+    pub fn into_owned(self) -> CallOutputValue<'static> {
+        use CallOutputValue::*;
+
+        match self {
+            Scalar(name) => Scalar(owned(name)),
+            Accumulator(name) => Accumulator(owned(name)),
+            None => None,
+        }
+    }
+}
+
+fn owned(value: Cow<'_, str>) -> Cow<'static, str> {
+    Cow::Owned(value.into_owned())
+}
+
+fn boxed(instruction: Box<Instruction<'_>>) -> Box<Instruction<'static>> {
+    Box::new(instruction.into_owned())
+}
+
+fn owned_args(args: Rc<Vec<CallArgValue<'_>>>) -> Rc<Vec<CallArgValue<'static>>> {
+    let args = unwrap_or_clone(args);
+    Rc::new(args.into_iter().map(CallArgValue::into_owned).collect())
+}
+
+/// Takes a value out of an Rc, a shared value is cloned.
+fn unwrap_or_clone<T: Clone>(value: Rc<T>) -> T {
+    Rc::try_unwrap(value).unwrap_or_else(|value| (*value).clone())
+}
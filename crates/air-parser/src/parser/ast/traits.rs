@@ -0,0 +1,306 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::*;
+use crate::parser::formatter::Printer;
+use crate::parser::lexer::escape_string_literal;
+
+use std::fmt;
+
+impl fmt::Display for Instruction<'_> {
+    /// Prints an instruction in one line, the alternate form (`{:#}`) prints it with indentation.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Instruction::*;
+
+        if f.alternate() {
+            let mut printer = Printer::new(vec![]);
+            printer.print_instruction(self);
+            return write!(f, "{}", printer.finish());
+        }
+
+        match self {
+            Null(null) => write!(f, "{}", null),
+            Call(call) => write!(f, "{}", call),
+            Ap(ap) => write!(f, "{}", ap),
+            Seq(seq) => write!(f, "{}", seq),
+            Par(par) => write!(f, "{}", par),
+            Xor(xor) => write!(f, "{}", xor),
+            Match(match_) => write!(f, "{}", match_),
+            MisMatch(mismatch) => write!(f, "{}", mismatch),
+            Compare(compare) => write!(f, "{}", compare),
+            Fold(fold) => write!(f, "{}", fold),
+            New(new) => write!(f, "{}", new),
+            Next(next) => write!(f, "{}", next),
+            Fail(fail) => write!(f, "{}", fail),
+            Define(define) => write!(f, "{}", define),
+            Invoke(invoke) => write!(f, "{}", invoke),
+            Error => write!(f, "<error>"),
+        }
+    }
+}
+
+impl fmt::Display for CallArgValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use CallArgValue::*;
+
+        match self {
+            InitPeerId => write!(f, "%init_peer_id%"),
+            CurrentPeerId => write!(f, "%current_peer_id%"),
+            ParticleId => write!(f, "%particle_id%"),
+            Timestamp => write!(f, "%timestamp%"),
+            Ttl => write!(f, "%ttl%"),
+            Literal(str) => write!(f, r#""{}""#, escape_string_literal(str)),
+            Number(number) => write!(f, "{}", number),
+            Boolean(value) => write!(f, "{}", value),
+            Null => write!(f, "null"),
+            Json(value) => write!(f, "{}", AIRJson(value)),
+            Variable(str, _) => write!(f, "{}", str),
+            JsonPath { variable, path, .. } => write!(f, "{}.{}", variable, path),
+            LastError(None) => write!(f, "%last_error%"),
+            LastError(Some(path)) => write!(f, "%last_error%.{}", path),
+        }
+    }
+}
+
+impl fmt::Display for IterableValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use IterableValue::*;
+
+        match self {
+            Variable(str, _) => write!(f, "{}", str),
+            JsonPath { variable, path, .. } => write!(f, "{}.{}", variable, path),
+        }
+    }
+}
+
+impl fmt::Display for MatchableValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use MatchableValue::*;
+
+        match self {
+            Literal(str) => write!(f, r#""{}""#, escape_string_literal(str)),
+            Number(number) => write!(f, "{}", number),
+            Boolean(value) => write!(f, "{}", value),
+            Null => write!(f, "null"),
+            Json(value) => write!(f, "{}", AIRJson(value)),
+            Variable(str, _) => write!(f, "{}", str),
+            JsonPath { variable, path, .. } => write!(f, "{}.{}", variable, path),
+        }
+    }
+}
+
+/// Prints a json value the way it's written in AIR: arrays with space separated elements
+/// and string literals with AIR escapes, objects are printed as plain json.
+struct AIRJson<'v>(&'v JValue);
+
+impl fmt::Display for AIRJson<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            JValue::String(str) => write!(f, r#""{}""#, escape_string_literal(str)),
+            JValue::Array(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|element| AIRJson(element).to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                write!(f, "[{}]", elements)
+            }
+            value => write!(f, "{}", value),
+        }
+    }
+}
+
+impl fmt::Display for CallOutputValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use CallOutputValue::*;
+
+        match self {
+            Scalar(str) => write!(f, "{}", str),
+            Accumulator(str) => write!(f, "{}[]", str),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for PeerPart<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use PeerPart::*;
+
+        match self {
+            PeerPk(peer_pk) => write!(f, "{}", peer_pk),
+            PeerPkWithServiceId(peer_pk, service_id) => write!(f, "({} {})", peer_pk, service_id),
+            PeerPks(peer_pks) => write!(f, "[{}]", peer_pks),
+            PeerPksWithServiceId(peer_pks, service_id) => {
+                write!(f, "([{}] {})", peer_pks, service_id)
+            }
+        }
+    }
+}
+
+impl fmt::Display for FunctionPart<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use FunctionPart::*;
+
+        match self {
+            FuncName(func_name) => write!(f, "{}", func_name),
+            ServiceIdWithFuncName(service_id, func_name) => {
+                write!(f, "({} {})", service_id, func_name)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Call<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args = self
+            .args
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        write!(
+            f,
+            "(call {} {} [{}]",
+            self.peer_part, self.function_part, args
+        )?;
+        match self.output {
+            CallOutputValue::None => write!(f, ")"),
+            _ => write!(f, " {})", self.output),
+        }
+    }
+}
+
+impl fmt::Display for Ap<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(ap {} {})", self.value, self.output)
+    }
+}
+
+impl fmt::Display for Seq<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(seq {} {})", self.0, self.1)
+    }
+}
+
+impl fmt::Display for Par<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(par {} {})", self.0, self.1)
+    }
+}
+
+impl fmt::Display for Xor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(xor {} {})", self.0, self.1)
+    }
+}
+
+impl fmt::Display for Match<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "(match {} {} {})",
+            self.left_value, self.right_value, self.instruction
+        )
+    }
+}
+
+impl fmt::Display for MisMatch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "(mismatch {} {} {})",
+            self.left_value, self.right_value, self.instruction
+        )
+    }
+}
+
+impl fmt::Display for Compare<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({} {}", self.operator, self.left_value)?;
+        if let Some(right_value) = &self.right_value {
+            write!(f, " {}", right_value)?;
+        }
+        write!(f, " {})", self.instruction)
+    }
+}
+
+impl fmt::Display for CompareOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.keyword())
+    }
+}
+
+impl fmt::Display for Fold<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "(fold {} {} {})",
+            self.iterable, self.iterator, self.instruction
+        )
+    }
+}
+
+impl fmt::Display for New<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(new {} {})", self.variable, self.instruction)
+    }
+}
+
+impl fmt::Display for Next<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(next {})", self.0)
+    }
+}
+
+impl fmt::Display for Null {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(null)")
+    }
+}
+
+impl fmt::Display for Fail<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(fail {} {})", self.ret_code, self.message)
+    }
+}
+
+impl fmt::Display for Define<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(define {} [{}]", self.name, self.parameters.join(" "))?;
+        if let Some(output) = &self.output {
+            write!(f, " {}", output)?;
+        }
+        write!(f, " {}) {}", self.body, self.instruction)
+    }
+}
+
+impl fmt::Display for Invoke<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args = self
+            .args
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        write!(f, "(invoke {} [{}]", self.name, args)?;
+        match self.output {
+            CallOutputValue::None => write!(f, ")"),
+            _ => write!(f, " {})", self.output),
+        }
+    }
+}
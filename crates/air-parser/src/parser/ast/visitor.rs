@@ -0,0 +1,452 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Traversal of AIR AST: every `visit_*` method of [`Visitor`] and [`VisitorMut`] by default
+//! walks into children of the node with a corresponding `walk_*` function, so an implementation
+//! overrides only methods for nodes it's interested in and calls `walk_*` to continue traversal.
+
+use super::*;
+
+/// Visits AST nodes by shared references.
+pub trait Visitor<'i> {
+    fn visit_instruction(&mut self, instruction: &Instruction<'i>) {
+        walk_instruction(self, instruction)
+    }
+
+    fn visit_call(&mut self, call: &Call<'i>) {
+        walk_call(self, call)
+    }
+
+    fn visit_ap(&mut self, ap: &Ap<'i>) {
+        walk_ap(self, ap)
+    }
+
+    fn visit_seq(&mut self, seq: &Seq<'i>) {
+        walk_seq(self, seq)
+    }
+
+    fn visit_par(&mut self, par: &Par<'i>) {
+        walk_par(self, par)
+    }
+
+    fn visit_xor(&mut self, xor: &Xor<'i>) {
+        walk_xor(self, xor)
+    }
+
+    fn visit_match(&mut self, match_: &Match<'i>) {
+        walk_match(self, match_)
+    }
+
+    fn visit_mismatch(&mut self, mismatch: &MisMatch<'i>) {
+        walk_mismatch(self, mismatch)
+    }
+
+    fn visit_compare(&mut self, compare: &Compare<'i>) {
+        walk_compare(self, compare)
+    }
+
+    fn visit_fold(&mut self, fold: &Fold<'i>) {
+        walk_fold(self, fold)
+    }
+
+    fn visit_new(&mut self, new: &New<'i>) {
+        walk_new(self, new)
+    }
+
+    fn visit_next(&mut self, _next: &Next<'i>) {}
+
+    fn visit_null(&mut self, _null: &Null) {}
+
+    fn visit_fail(&mut self, fail: &Fail<'i>) {
+        walk_fail(self, fail)
+    }
+
+    fn visit_define(&mut self, define: &Define<'i>) {
+        walk_define(self, define)
+    }
+
+    fn visit_invoke(&mut self, invoke: &Invoke<'i>) {
+        walk_invoke(self, invoke)
+    }
+
+    fn visit_peer_part(&mut self, peer_part: &PeerPart<'i>) {
+        walk_peer_part(self, peer_part)
+    }
+
+    fn visit_function_part(&mut self, function_part: &FunctionPart<'i>) {
+        walk_function_part(self, function_part)
+    }
+
+    fn visit_call_arg_value(&mut self, _value: &CallArgValue<'i>) {}
+
+    fn visit_call_output_value(&mut self, _value: &CallOutputValue<'i>) {}
+
+    fn visit_iterable_value(&mut self, _value: &IterableValue<'i>) {}
+
+    fn visit_matchable_value(&mut self, _value: &MatchableValue<'i>) {}
+}
+
+pub fn walk_instruction<'i, V: Visitor<'i> + ?Sized>(
+    visitor: &mut V,
+    instruction: &Instruction<'i>,
+) {
+    use Instruction::*;
+
+    match instruction {
+        Null(null) => visitor.visit_null(null),
+        Call(call) => visitor.visit_call(call),
+        Ap(ap) => visitor.visit_ap(ap),
+        Seq(seq) => visitor.visit_seq(seq),
+        Par(par) => visitor.visit_par(par),
+        Xor(xor) => visitor.visit_xor(xor),
+        Match(match_) => visitor.visit_match(match_),
+        MisMatch(mismatch) => visitor.visit_mismatch(mismatch),
+        Compare(compare) => visitor.visit_compare(compare),
+        Fold(fold) => visitor.visit_fold(fold),
+        New(new) => visitor.visit_new(new),
+        Next(next) => visitor.visit_next(next),
+        Fail(fail) => visitor.visit_fail(fail),
+        Define(define) => visitor.visit_define(define),
+        Invoke(invoke) => visitor.visit_invoke(invoke),
+        Error => {}
+    }
+}
+
+pub fn walk_call<'i, V: Visitor<'i> + ?Sized>(visitor: &mut V, call: &Call<'i>) {
+    visitor.visit_peer_part(&call.peer_part);
+    visitor.visit_function_part(&call.function_part);
+    for arg in call.args.iter() {
+        visitor.visit_call_arg_value(arg);
+    }
+    visitor.visit_call_output_value(&call.output);
+}
+
+pub fn walk_ap<'i, V: Visitor<'i> + ?Sized>(visitor: &mut V, ap: &Ap<'i>) {
+    visitor.visit_call_arg_value(&ap.value);
+    visitor.visit_call_output_value(&ap.output);
+}
+
+pub fn walk_seq<'i, V: Visitor<'i> + ?Sized>(visitor: &mut V, seq: &Seq<'i>) {
+    visitor.visit_instruction(&seq.0);
+    visitor.visit_instruction(&seq.1);
+}
+
+pub fn walk_par<'i, V: Visitor<'i> + ?Sized>(visitor: &mut V, par: &Par<'i>) {
+    visitor.visit_instruction(&par.0);
+    visitor.visit_instruction(&par.1);
+}
+
+pub fn walk_xor<'i, V: Visitor<'i> + ?Sized>(visitor: &mut V, xor: &Xor<'i>) {
+    visitor.visit_instruction(&xor.0);
+    visitor.visit_instruction(&xor.1);
+}
+
+pub fn walk_match<'i, V: Visitor<'i> + ?Sized>(visitor: &mut V, match_: &Match<'i>) {
+    visitor.visit_matchable_value(&match_.left_value);
+    visitor.visit_matchable_value(&match_.right_value);
+    visitor.visit_instruction(&match_.instruction);
+}
+
+pub fn walk_mismatch<'i, V: Visitor<'i> + ?Sized>(visitor: &mut V, mismatch: &MisMatch<'i>) {
+    visitor.visit_matchable_value(&mismatch.left_value);
+    visitor.visit_matchable_value(&mismatch.right_value);
+    visitor.visit_instruction(&mismatch.instruction);
+}
+
+pub fn walk_compare<'i, V: Visitor<'i> + ?Sized>(visitor: &mut V, compare: &Compare<'i>) {
+    visitor.visit_matchable_value(&compare.left_value);
+    if let Some(right_value) = &compare.right_value {
+        visitor.visit_matchable_value(right_value);
+    }
+    visitor.visit_instruction(&compare.instruction);
+}
+
+pub fn walk_fold<'i, V: Visitor<'i> + ?Sized>(visitor: &mut V, fold: &Fold<'i>) {
+    visitor.visit_iterable_value(&fold.iterable);
+    visitor.visit_instruction(&fold.instruction);
+}
+
+pub fn walk_new<'i, V: Visitor<'i> + ?Sized>(visitor: &mut V, new: &New<'i>) {
+    visitor.visit_call_output_value(&new.variable);
+    visitor.visit_instruction(&new.instruction);
+}
+
+pub fn walk_fail<'i, V: Visitor<'i> + ?Sized>(visitor: &mut V, fail: &Fail<'i>) {
+    visitor.visit_call_arg_value(&fail.ret_code);
+    visitor.visit_call_arg_value(&fail.message);
+}
+
+pub fn walk_define<'i, V: Visitor<'i> + ?Sized>(visitor: &mut V, define: &Define<'i>) {
+    visitor.visit_instruction(&define.body);
+    visitor.visit_instruction(&define.instruction);
+}
+
+pub fn walk_invoke<'i, V: Visitor<'i> + ?Sized>(visitor: &mut V, invoke: &Invoke<'i>) {
+    for arg in invoke.args.iter() {
+        visitor.visit_call_arg_value(arg);
+    }
+    visitor.visit_call_output_value(&invoke.output);
+}
+
+pub fn walk_peer_part<'i, V: Visitor<'i> + ?Sized>(visitor: &mut V, peer_part: &PeerPart<'i>) {
+    match peer_part {
+        PeerPart::PeerPk(peer_pk) | PeerPart::PeerPks(peer_pk) => {
+            visitor.visit_call_arg_value(peer_pk)
+        }
+        PeerPart::PeerPkWithServiceId(peer_pk, service_id)
+        | PeerPart::PeerPksWithServiceId(peer_pk, service_id) => {
+            visitor.visit_call_arg_value(peer_pk);
+            visitor.visit_call_arg_value(service_id);
+        }
+    }
+}
+
+pub fn walk_function_part<'i, V: Visitor<'i> + ?Sized>(
+    visitor: &mut V,
+    function_part: &FunctionPart<'i>,
+) {
+    match function_part {
+        FunctionPart::FuncName(func_name) => visitor.visit_call_arg_value(func_name),
+        FunctionPart::ServiceIdWithFuncName(service_id, func_name) => {
+            visitor.visit_call_arg_value(service_id);
+            visitor.visit_call_arg_value(func_name);
+        }
+    }
+}
+
+/// Visits AST nodes by mutable references, so they could be rewritten in place.
+///
+/// Fold bodies are stored in `Rc`, walking into a body shared with some other owner panics,
+/// it never happens for an AST returned by the parser.
+pub trait VisitorMut<'i> {
+    fn visit_instruction_mut(&mut self, instruction: &mut Instruction<'i>) {
+        walk_instruction_mut(self, instruction)
+    }
+
+    fn visit_call_mut(&mut self, call: &mut Call<'i>) {
+        walk_call_mut(self, call)
+    }
+
+    fn visit_ap_mut(&mut self, ap: &mut Ap<'i>) {
+        walk_ap_mut(self, ap)
+    }
+
+    fn visit_seq_mut(&mut self, seq: &mut Seq<'i>) {
+        walk_seq_mut(self, seq)
+    }
+
+    fn visit_par_mut(&mut self, par: &mut Par<'i>) {
+        walk_par_mut(self, par)
+    }
+
+    fn visit_xor_mut(&mut self, xor: &mut Xor<'i>) {
+        walk_xor_mut(self, xor)
+    }
+
+    fn visit_match_mut(&mut self, match_: &mut Match<'i>) {
+        walk_match_mut(self, match_)
+    }
+
+    fn visit_mismatch_mut(&mut self, mismatch: &mut MisMatch<'i>) {
+        walk_mismatch_mut(self, mismatch)
+    }
+
+    fn visit_compare_mut(&mut self, compare: &mut Compare<'i>) {
+        walk_compare_mut(self, compare)
+    }
+
+    fn visit_fold_mut(&mut self, fold: &mut Fold<'i>) {
+        walk_fold_mut(self, fold)
+    }
+
+    fn visit_new_mut(&mut self, new: &mut New<'i>) {
+        walk_new_mut(self, new)
+    }
+
+    fn visit_next_mut(&mut self, _next: &mut Next<'i>) {}
+
+    fn visit_null_mut(&mut self, _null: &mut Null) {}
+
+    fn visit_fail_mut(&mut self, fail: &mut Fail<'i>) {
+        walk_fail_mut(self, fail)
+    }
+
+    fn visit_define_mut(&mut self, define: &mut Define<'i>) {
+        walk_define_mut(self, define)
+    }
+
+    fn visit_invoke_mut(&mut self, invoke: &mut Invoke<'i>) {
+        walk_invoke_mut(self, invoke)
+    }
+
+    fn visit_peer_part_mut(&mut self, peer_part: &mut PeerPart<'i>) {
+        walk_peer_part_mut(self, peer_part)
+    }
+
+    fn visit_function_part_mut(&mut self, function_part: &mut FunctionPart<'i>) {
+        walk_function_part_mut(self, function_part)
+    }
+
+    fn visit_call_arg_value_mut(&mut self, _value: &mut CallArgValue<'i>) {}
+
+    fn visit_call_output_value_mut(&mut self, _value: &mut CallOutputValue<'i>) {}
+
+    fn visit_iterable_value_mut(&mut self, _value: &mut IterableValue<'i>) {}
+
+    fn visit_matchable_value_mut(&mut self, _value: &mut MatchableValue<'i>) {}
+}
+
+pub fn walk_instruction_mut<'i, V: VisitorMut<'i> + ?Sized>(
+    visitor: &mut V,
+    instruction: &mut Instruction<'i>,
+) {
+    use Instruction::*;
+
+    match instruction {
+        Null(null) => visitor.visit_null_mut(null),
+        Call(call) => visitor.visit_call_mut(call),
+        Ap(ap) => visitor.visit_ap_mut(ap),
+        Seq(seq) => visitor.visit_seq_mut(seq),
+        Par(par) => visitor.visit_par_mut(par),
+        Xor(xor) => visitor.visit_xor_mut(xor),
+        Match(match_) => visitor.visit_match_mut(match_),
+        MisMatch(mismatch) => visitor.visit_mismatch_mut(mismatch),
+        Compare(compare) => visitor.visit_compare_mut(compare),
+        Fold(fold) => visitor.visit_fold_mut(fold),
+        New(new) => visitor.visit_new_mut(new),
+        Next(next) => visitor.visit_next_mut(next),
+        Fail(fail) => visitor.visit_fail_mut(fail),
+        Define(define) => visitor.visit_define_mut(define),
+        Invoke(invoke) => visitor.visit_invoke_mut(invoke),
+        Error => {}
+    }
+}
+
+pub fn walk_call_mut<'i, V: VisitorMut<'i> + ?Sized>(visitor: &mut V, call: &mut Call<'i>) {
+    visitor.visit_peer_part_mut(&mut call.peer_part);
+    visitor.visit_function_part_mut(&mut call.function_part);
+    for arg in Rc::make_mut(&mut call.args).iter_mut() {
+        visitor.visit_call_arg_value_mut(arg);
+    }
+    visitor.visit_call_output_value_mut(&mut call.output);
+}
+
+pub fn walk_ap_mut<'i, V: VisitorMut<'i> + ?Sized>(visitor: &mut V, ap: &mut Ap<'i>) {
+    visitor.visit_call_arg_value_mut(&mut ap.value);
+    visitor.visit_call_output_value_mut(&mut ap.output);
+}
+
+pub fn walk_seq_mut<'i, V: VisitorMut<'i> + ?Sized>(visitor: &mut V, seq: &mut Seq<'i>) {
+    visitor.visit_instruction_mut(&mut seq.0);
+    visitor.visit_instruction_mut(&mut seq.1);
+}
+
+pub fn walk_par_mut<'i, V: VisitorMut<'i> + ?Sized>(visitor: &mut V, par: &mut Par<'i>) {
+    visitor.visit_instruction_mut(&mut par.0);
+    visitor.visit_instruction_mut(&mut par.1);
+}
+
+pub fn walk_xor_mut<'i, V: VisitorMut<'i> + ?Sized>(visitor: &mut V, xor: &mut Xor<'i>) {
+    visitor.visit_instruction_mut(&mut xor.0);
+    visitor.visit_instruction_mut(&mut xor.1);
+}
+
+pub fn walk_match_mut<'i, V: VisitorMut<'i> + ?Sized>(visitor: &mut V, match_: &mut Match<'i>) {
+    visitor.visit_matchable_value_mut(&mut match_.left_value);
+    visitor.visit_matchable_value_mut(&mut match_.right_value);
+    visitor.visit_instruction_mut(&mut match_.instruction);
+}
+
+pub fn walk_mismatch_mut<'i, V: VisitorMut<'i> + ?Sized>(
+    visitor: &mut V,
+    mismatch: &mut MisMatch<'i>,
+) {
+    visitor.visit_matchable_value_mut(&mut mismatch.left_value);
+    visitor.visit_matchable_value_mut(&mut mismatch.right_value);
+    visitor.visit_instruction_mut(&mut mismatch.instruction);
+}
+
+pub fn walk_compare_mut<'i, V: VisitorMut<'i> + ?Sized>(
+    visitor: &mut V,
+    compare: &mut Compare<'i>,
+) {
+    visitor.visit_matchable_value_mut(&mut compare.left_value);
+    if let Some(right_value) = &mut compare.right_value {
+        visitor.visit_matchable_value_mut(right_value);
+    }
+    visitor.visit_instruction_mut(&mut compare.instruction);
+}
+
+pub fn walk_fold_mut<'i, V: VisitorMut<'i> + ?Sized>(visitor: &mut V, fold: &mut Fold<'i>) {
+    visitor.visit_iterable_value_mut(&mut fold.iterable);
+
+    // a shared fold body is cloned, so other owners of the AST aren't changed
+    visitor.visit_instruction_mut(Rc::make_mut(&mut fold.instruction));
+}
+
+pub fn walk_new_mut<'i, V: VisitorMut<'i> + ?Sized>(visitor: &mut V, new: &mut New<'i>) {
+    visitor.visit_call_output_value_mut(&mut new.variable);
+    visitor.visit_instruction_mut(&mut new.instruction);
+}
+
+pub fn walk_fail_mut<'i, V: VisitorMut<'i> + ?Sized>(visitor: &mut V, fail: &mut Fail<'i>) {
+    visitor.visit_call_arg_value_mut(&mut fail.ret_code);
+    visitor.visit_call_arg_value_mut(&mut fail.message);
+}
+
+pub fn walk_define_mut<'i, V: VisitorMut<'i> + ?Sized>(visitor: &mut V, define: &mut Define<'i>) {
+    visitor.visit_instruction_mut(&mut define.body);
+    visitor.visit_instruction_mut(&mut define.instruction);
+}
+
+pub fn walk_invoke_mut<'i, V: VisitorMut<'i> + ?Sized>(visitor: &mut V, invoke: &mut Invoke<'i>) {
+    for arg in Rc::make_mut(&mut invoke.args).iter_mut() {
+        visitor.visit_call_arg_value_mut(arg);
+    }
+    visitor.visit_call_output_value_mut(&mut invoke.output);
+}
+
+pub fn walk_peer_part_mut<'i, V: VisitorMut<'i> + ?Sized>(
+    visitor: &mut V,
+    peer_part: &mut PeerPart<'i>,
+) {
+    match peer_part {
+        PeerPart::PeerPk(peer_pk) | PeerPart::PeerPks(peer_pk) => {
+            visitor.visit_call_arg_value_mut(peer_pk)
+        }
+        PeerPart::PeerPkWithServiceId(peer_pk, service_id)
+        | PeerPart::PeerPksWithServiceId(peer_pk, service_id) => {
+            visitor.visit_call_arg_value_mut(peer_pk);
+            visitor.visit_call_arg_value_mut(service_id);
+        }
+    }
+}
+
+pub fn walk_function_part_mut<'i, V: VisitorMut<'i> + ?Sized>(
+    visitor: &mut V,
+    function_part: &mut FunctionPart<'i>,
+) {
+    match function_part {
+        FunctionPart::FuncName(func_name) => visitor.visit_call_arg_value_mut(func_name),
+        FunctionPart::ServiceIdWithFuncName(service_id, func_name) => {
+            visitor.visit_call_arg_value_mut(service_id);
+            visitor.visit_call_arg_value_mut(func_name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
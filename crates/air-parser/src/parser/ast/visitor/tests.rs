@@ -0,0 +1,208 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::*;
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+/// Collects literal peer ids of all calls in a script.
+#[derive(Default)]
+struct PeerSetCollector {
+    peers: BTreeSet<String>,
+    variables: BTreeSet<String>,
+}
+
+impl<'i> Visitor<'i> for PeerSetCollector {
+    fn visit_peer_part(&mut self, peer_part: &PeerPart<'i>) {
+        let peer_pk = match peer_part {
+            PeerPart::PeerPk(peer_pk)
+            | PeerPart::PeerPkWithServiceId(peer_pk, _)
+            | PeerPart::PeerPks(peer_pk)
+            | PeerPart::PeerPksWithServiceId(peer_pk, _) => peer_pk,
+        };
+
+        match peer_pk {
+            CallArgValue::Literal(peer) => {
+                self.peers.insert(peer.to_string());
+            }
+            CallArgValue::Variable(variable, _) | CallArgValue::JsonPath { variable, .. } => {
+                self.variables.insert(variable.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_iterable_value(&mut self, value: &IterableValue<'i>) {
+        let variable = match value {
+            IterableValue::Variable(variable, _) | IterableValue::JsonPath { variable, .. } => {
+                variable
+            }
+        };
+        self.variables.insert(variable.to_string());
+    }
+}
+
+#[test]
+fn visitor_walks_all_nodes() {
+    let source_code = r#"
+        (seq
+            (call "peer_1" ("service" "function") [] peers)
+            (xor
+                (fold peers.$.all! peer
+                    (par
+                        (match peer "peer_2"
+                            (call ("peer_2" "service") ("service" "function") [])
+                        )
+                        (next peer)
+                    )
+                )
+                (call peer ("service" "function") [])
+            )
+        )
+        "#;
+    let instruction = crate::parse(source_code).expect("parsing failed");
+
+    let mut collector = PeerSetCollector::default();
+    collector.visit_instruction(&instruction);
+
+    let expected_peers = vec![String::from("peer_1"), String::from("peer_2")];
+    assert_eq!(
+        collector.peers.into_iter().collect::<Vec<_>>(),
+        expected_peers
+    );
+    assert_eq!(
+        collector.variables.into_iter().collect::<Vec<_>>(),
+        vec!["peer", "peers"]
+    );
+}
+
+/// Renames all usages of a variable.
+struct VariableRenamer<'i> {
+    from: &'i str,
+    to: &'i str,
+}
+
+impl<'i> VariableRenamer<'i> {
+    fn rename(&self, variable: &mut Cow<'i, str>) {
+        if variable.as_ref() == self.from {
+            *variable = Cow::Borrowed(self.to);
+        }
+    }
+}
+
+impl<'i> VisitorMut<'i> for VariableRenamer<'i> {
+    fn visit_call_arg_value_mut(&mut self, value: &mut CallArgValue<'i>) {
+        if let CallArgValue::Variable(variable, _) | CallArgValue::JsonPath { variable, .. } = value
+        {
+            self.rename(variable);
+        }
+    }
+
+    fn visit_call_output_value_mut(&mut self, value: &mut CallOutputValue<'i>) {
+        if let CallOutputValue::Scalar(variable) | CallOutputValue::Accumulator(variable) = value {
+            self.rename(variable);
+        }
+    }
+
+    fn visit_iterable_value_mut(&mut self, value: &mut IterableValue<'i>) {
+        match value {
+            IterableValue::Variable(variable, _) | IterableValue::JsonPath { variable, .. } => {
+                self.rename(variable)
+            }
+        }
+    }
+
+    fn visit_matchable_value_mut(&mut self, value: &mut MatchableValue<'i>) {
+        if let MatchableValue::Variable(variable, _) | MatchableValue::JsonPath { variable, .. } =
+            value
+        {
+            self.rename(variable);
+        }
+    }
+}
+
+#[test]
+fn visitor_mut_rewrites_nodes() {
+    let source_code = r#"
+        (seq
+            (call "peer" ("service" "function") [] result)
+            (ap result.$.b result_2)
+            (fold result.$.all! i
+                (mismatch result "value"
+                    (call i ("service" "function") [result.$.a result_2])
+                )
+            )
+        )
+        "#;
+    let expected = r#"
+        (seq
+            (call "peer" ("service" "function") [] renamed)
+            (ap renamed.$.b result_2)
+            (fold renamed.$.all! i
+                (mismatch renamed "value"
+                    (call i ("service" "function") [renamed.$.a result_2])
+                )
+            )
+        )
+        "#;
+    let mut instruction = crate::parse(source_code).expect("parsing failed");
+
+    let mut renamer = VariableRenamer {
+        from: "result",
+        to: "renamed",
+    };
+    renamer.visit_instruction_mut(&mut instruction);
+
+    assert_eq!(
+        instruction.to_string(),
+        crate::parse(expected).unwrap().to_string()
+    );
+}
+
+#[test]
+fn visitor_mut_clones_shared_fold_body() {
+    let source_code = r#"
+        (fold result i
+            (call i ("service" "function") [result])
+        )
+        "#;
+    let mut instruction = crate::parse(source_code).expect("parsing failed");
+    let shared_body = match &*instruction {
+        Instruction::Fold(fold) => fold.instruction.clone(),
+        _ => unreachable!("fold was parsed"),
+    };
+
+    let mut renamer = VariableRenamer {
+        from: "result",
+        to: "renamed",
+    };
+    renamer.visit_instruction_mut(&mut instruction);
+
+    let expected = r#"
+        (fold renamed i
+            (call i ("service" "function") [renamed])
+        )
+        "#;
+    assert_eq!(
+        instruction.to_string(),
+        crate::parse(expected).unwrap().to_string()
+    );
+    assert_eq!(
+        shared_body.to_string(),
+        r#"(call i ("service" "function") [result])"#
+    );
+}
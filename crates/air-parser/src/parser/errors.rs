@@ -0,0 +1,174 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::lexer::LexerError;
+use super::lexer::Token;
+use super::span::Span;
+
+use codespan_reporting::diagnostic::{Diagnostic, Label};
+use codespan_reporting::files::SimpleFiles;
+use codespan_reporting::term;
+use codespan_reporting::term::termcolor::Buffer;
+use lalrpop_util::{ErrorRecovery, ParseError};
+use thiserror::Error as ThisError;
+
+/// Errors occurred while parsing an AIR script, the parser recovers after most of them,
+/// so there could be several errors per script.
+#[derive(ThisError, Debug, Clone, PartialEq, Eq)]
+pub struct ParserError {
+    pub errors: Vec<SpannedError>,
+}
+
+/// A parser error together with the part of the script where it occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpannedError {
+    pub span: Span,
+    pub kind: ParserErrorKind,
+}
+
+#[derive(ThisError, Debug, Clone, PartialEq, Eq)]
+pub enum ParserErrorKind {
+    #[error("expected {}", pretty_expected(.expected))]
+    UnrecognizedToken {
+        token: String,
+        expected: Vec<String>,
+    },
+
+    #[error("unexpected token")]
+    InvalidToken,
+
+    #[error("extra token")]
+    ExtraToken { token: String },
+
+    #[error("expected {}", pretty_expected(.expected))]
+    UnrecognizedEOF { expected: Vec<String> },
+
+    #[error("{0}")]
+    LexerError(LexerError),
+}
+
+impl ParserError {
+    /// Renders errors as a plain text report with the corresponding fragments of `air_script`.
+    pub fn render(&self, air_script: &str) -> String {
+        let mut buffer = Buffer::no_color();
+        self.emit(air_script, &mut buffer);
+
+        String::from_utf8_lossy(buffer.as_slice()).to_string()
+    }
+
+    /// Renders errors as a report with terminal colors suitable for printing to a console.
+    pub fn render_colored(&self, air_script: &str) -> String {
+        let mut buffer = Buffer::ansi();
+        self.emit(air_script, &mut buffer);
+
+        String::from_utf8_lossy(buffer.as_slice()).to_string()
+    }
+
+    fn emit(&self, air_script: &str, buffer: &mut Buffer) {
+        let mut files = SimpleFiles::new();
+        let file_id = files.add("script.aqua", air_script);
+
+        let labels = self
+            .errors
+            .iter()
+            .map(|error| {
+                Label::primary(file_id, error.span.left..error.span.right)
+                    .with_message(error.kind.to_string())
+            })
+            .collect();
+        let diagnostic = Diagnostic::error().with_labels(labels);
+
+        let config = term::Config::default();
+        term::emit(buffer, &config, &files, &diagnostic).expect("term emit to buffer");
+    }
+}
+
+impl std::fmt::Display for ParserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (id, error) in self.errors.iter().enumerate() {
+            if id != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", error)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for SpannedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}: {}", self.span.left, self.span.right, self.kind)
+    }
+}
+
+impl<'input> From<Vec<ErrorRecovery<usize, Token<'input>, LexerError>>> for ParserError {
+    fn from(errors: Vec<ErrorRecovery<usize, Token<'input>, LexerError>>) -> Self {
+        let errors = errors
+            .into_iter()
+            .map(|recovery| SpannedError::from(recovery.error))
+            .collect();
+
+        Self { errors }
+    }
+}
+
+impl<'input> From<ParseError<usize, Token<'input>, LexerError>> for SpannedError {
+    fn from(error: ParseError<usize, Token<'input>, LexerError>) -> Self {
+        use ParserErrorKind::*;
+
+        match error {
+            ParseError::UnrecognizedToken {
+                token: (left, token, right),
+                expected,
+            } => Self {
+                span: Span::new(left, right),
+                kind: UnrecognizedToken {
+                    token: token.to_string(),
+                    expected,
+                },
+            },
+            ParseError::InvalidToken { location } => Self {
+                span: Span::new(location, location + 1),
+                kind: InvalidToken,
+            },
+            ParseError::ExtraToken {
+                token: (left, token, right),
+            } => Self {
+                span: Span::new(left, right),
+                kind: ExtraToken {
+                    token: token.to_string(),
+                },
+            },
+            ParseError::UnrecognizedEOF { location, expected } => Self {
+                span: Span::new(location, location + 1),
+                kind: UnrecognizedEOF { expected },
+            },
+            ParseError::User { error } => Self {
+                span: error.span(),
+                kind: LexerError(error),
+            },
+        }
+    }
+}
+
+fn pretty_expected(expected: &[String]) -> String {
+    if expected.is_empty() {
+        "<nothing>".to_string()
+    } else {
+        expected.join(" or ")
+    }
+}
@@ -0,0 +1,241 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::ast::Instruction;
+use super::lexer::AIRLexer;
+use super::ParserError;
+
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+const INDENT: &str = "    ";
+
+/// Reformat AIR script with consistent indentation, comments are preserved.
+pub fn format(air_script: &str) -> Result<String, ParserError> {
+    let instruction = super::parse(air_script)?;
+    let comments = collect_comments(air_script);
+
+    let mut printer = Printer::new(comments);
+    printer.print_instruction(&instruction);
+    printer.flush_comments(air_script.len());
+
+    let mut formatted = printer.finish();
+    formatted.push('\n');
+    Ok(formatted)
+}
+
+/// A comment from an AIR script with a position of its leading semicolon.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Comment<'i> {
+    pub(crate) position: usize,
+    pub(crate) text: &'i str,
+    /// True, if the comment placed on the same line right after some token.
+    pub(crate) is_trailing: bool,
+}
+
+/// Prints instructions line by line with indentation, interleaving them with comments.
+pub(crate) struct Printer<'c> {
+    lines: Vec<String>,
+    indent: usize,
+    comments: Peekable<IntoIter<Comment<'c>>>,
+}
+
+impl<'c> Printer<'c> {
+    pub(crate) fn new(comments: Vec<Comment<'c>>) -> Self {
+        Self {
+            lines: vec![],
+            indent: 0,
+            comments: comments.into_iter().peekable(),
+        }
+    }
+
+    pub(crate) fn print_instruction(&mut self, instruction: &Instruction<'_>) {
+        use Instruction::*;
+
+        let span = instruction.span();
+        self.flush_comments(span.left);
+
+        match instruction {
+            Seq(_) => self.print_composite("seq", &nary_children(instruction), span.right),
+            Par(_) => self.print_composite("par", &nary_children(instruction), span.right),
+            Xor(_) => self.print_composite("xor", &nary_children(instruction), span.right),
+            Match(match_) => {
+                let header = format!("match {} {}", match_.left_value, match_.right_value);
+                self.print_composite(&header, &[&match_.instruction], span.right)
+            }
+            MisMatch(mismatch) => {
+                let header = format!("mismatch {} {}", mismatch.left_value, mismatch.right_value);
+                self.print_composite(&header, &[&mismatch.instruction], span.right)
+            }
+            Compare(compare) => {
+                let mut header = format!("{} {}", compare.operator, compare.left_value);
+                if let Some(right_value) = &compare.right_value {
+                    header = format!("{} {}", header, right_value);
+                }
+                self.print_composite(&header, &[&compare.instruction], span.right)
+            }
+            Fold(fold) => {
+                let header = format!("fold {} {}", fold.iterable, fold.iterator);
+                self.print_composite(&header, &[&fold.instruction], span.right)
+            }
+            New(new) => {
+                let header = format!("new {}", new.variable);
+                self.print_composite(&header, &[&new.instruction], span.right)
+            }
+            Define(define) => {
+                let mut header =
+                    format!("define {} [{}]", define.name, define.parameters.join(" "));
+                if let Some(output) = &define.output {
+                    header = format!("{} {}", header, output);
+                }
+                self.print_composite(&header, &[&define.body], span.right);
+                // the following definitions and the main instruction aren't nested in a definition
+                self.print_instruction(&define.instruction)
+            }
+            Call(_) | Ap(_) | Invoke(_) | Next(_) | Null(_) | Fail(_) | Error => {
+                self.push_line(instruction.to_string());
+                // comments inside a one-line instruction are moved right after it
+                self.flush_comments(span.right);
+            }
+        }
+    }
+
+    fn print_composite(&mut self, header: &str, children: &[&Instruction<'_>], right: usize) {
+        self.push_line(format!("({}", header));
+
+        self.indent += 1;
+        for child in children {
+            self.print_instruction(child);
+        }
+        // comments placed before the closing bracket
+        self.flush_comments(right.saturating_sub(1));
+        self.indent -= 1;
+
+        self.push_line(String::from(")"));
+    }
+
+    /// Print all comments placed before the supplied position.
+    pub(crate) fn flush_comments(&mut self, position: usize) {
+        while let Some(comment) = self.comments.peek() {
+            if comment.position >= position {
+                break;
+            }
+
+            let comment = self.comments.next().unwrap();
+            match self.lines.last_mut() {
+                Some(last_line) if comment.is_trailing => {
+                    last_line.push(' ');
+                    last_line.push_str(comment.text);
+                }
+                _ => self.push_line(comment.text.to_string()),
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> String {
+        self.lines.join("\n")
+    }
+
+    fn push_line(&mut self, line: String) {
+        self.lines.push(INDENT.repeat(self.indent) + &line);
+    }
+}
+
+/// Returns children of a seq, par or xor instruction, unfolding right-nested instructions
+/// desugared by the parser from an n-ary form back to it.
+fn nary_children<'a, 'i>(instruction: &'a Instruction<'i>) -> Vec<&'a Instruction<'i>> {
+    let mut children = vec![];
+    let mut current = instruction;
+
+    while let Some((left, right)) = binary_children(current) {
+        children.push(left);
+
+        // a desugared instruction starts with its first child, while a written one with a bracket
+        let right_span = right.span();
+        let is_desugared = std::mem::discriminant(current) == std::mem::discriminant(right)
+            && right_span.left < right_span.right
+            && binary_children(right).map(|(first, _)| first.span().left) == Some(right_span.left);
+
+        if !is_desugared {
+            children.push(right);
+            break;
+        }
+        current = right;
+    }
+
+    children
+}
+
+fn binary_children<'a, 'i>(
+    instruction: &'a Instruction<'i>,
+) -> Option<(&'a Instruction<'i>, &'a Instruction<'i>)> {
+    use Instruction::*;
+
+    match instruction {
+        Seq(seq) => Some((&seq.0, &seq.1)),
+        Par(par) => Some((&par.0, &par.1)),
+        Xor(xor) => Some((&xor.0, &xor.1)),
+        _ => None,
+    }
+}
+
+/// Collect comments from places between tokens, the lexer skips only whitespaces and comments,
+/// so everything that starts with a semicolon there is a comment.
+pub(crate) fn collect_comments(air_script: &str) -> Vec<Comment<'_>> {
+    let mut comments = vec![];
+    let mut gap_start = 0;
+
+    for token in AIRLexer::new(air_script) {
+        let (left, right) = match token {
+            Ok((left, _, right)) => (left, right),
+            Err(_) => break,
+        };
+
+        collect_gap_comments(air_script, gap_start..left, &mut comments);
+        gap_start = right;
+    }
+    collect_gap_comments(air_script, gap_start..air_script.len(), &mut comments);
+
+    comments
+}
+
+fn collect_gap_comments<'i>(
+    air_script: &'i str,
+    gap: std::ops::Range<usize>,
+    comments: &mut Vec<Comment<'i>>,
+) {
+    let has_token_before = gap.start != 0;
+    let mut position = gap.start;
+
+    while let Some(comment_start) = air_script[position..gap.end].find(';') {
+        let comment_start = position + comment_start;
+        let comment_end = air_script[comment_start..gap.end]
+            .find('\n')
+            .map_or(gap.end, |end| comment_start + end);
+
+        let is_trailing = has_token_before && !air_script[gap.start..comment_start].contains('\n');
+        comments.push(Comment {
+            position: comment_start,
+            text: air_script[comment_start..comment_end].trim_end(),
+            is_trailing,
+        });
+
+        position = comment_end;
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,210 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::format;
+use crate::ast::Instruction;
+use crate::parser::tests::clear_spans;
+
+const SCRIPT: &str = r#"
+(seq
+    (seq
+        (call %init_peer_id% ("" "") ["peers"] peers)
+        (call "relay" (service_id "fname") [peers.$.[0] "literal" name] result[])
+    )
+    (xor
+        (fold peers.$.all! i
+            (par
+                (match i.$.name "name"
+                    (seq
+                        (call i ("op" "identity") [])
+                        (ap i.$.name names[])
+                    )
+                )
+                (next i)
+            )
+        )
+        (mismatch result "result"
+            (fail 1 "unexpected result")
+        )
+    )
+)"#;
+
+fn parse(air_script: &str) -> Instruction<'_> {
+    let mut instruction = *crate::parse(air_script).expect("parsing failed");
+    clear_spans(&mut instruction);
+    instruction
+}
+
+#[test]
+fn display_round_trip() {
+    let instruction = parse(SCRIPT);
+
+    let printed = instruction.to_string();
+    assert_eq!(parse(&printed), instruction);
+    assert!(!printed.contains('\n'));
+
+    let pretty_printed = format!("{:#}", instruction);
+    assert_eq!(parse(&pretty_printed), instruction);
+    assert_eq!(pretty_printed, SCRIPT.trim());
+}
+
+#[test]
+fn display_call() {
+    let source_code = r#"(call  (peer_id "service_id")  function
+        [ arg1.$.a   "arg2"] )"#;
+    let instruction = parse(source_code);
+
+    assert_eq!(
+        instruction.to_string(),
+        r#"(call (peer_id "service_id") function [arg1.$.a "arg2"])"#
+    );
+}
+
+#[test]
+fn display_escaped_literal() {
+    let source_code = r#"(call "peer" ("service" "function") ["\"\\\n\t\u{7}"])"#;
+    let instruction = parse(source_code);
+
+    assert_eq!(instruction.to_string(), source_code);
+    assert_eq!(parse(&instruction.to_string()), instruction);
+}
+
+#[test]
+fn display_typed_literals() {
+    let source_code =
+        r#"(call "peer" ("service" "function") [1 -2.5 true null ["a\n" [] {"b":[1,"\n"]}]])"#;
+    let instruction = parse(source_code);
+
+    assert_eq!(instruction.to_string(), source_code);
+    assert_eq!(parse(&instruction.to_string()), instruction);
+}
+
+#[test]
+fn display_compare() {
+    let source_code = r#"(contains peers.$.all "peer" (is_empty peers (null)))"#;
+    let instruction = parse(source_code);
+
+    assert_eq!(instruction.to_string(), source_code);
+    assert_eq!(
+        format(source_code).expect("formatting failed"),
+        "(contains peers.$.all \"peer\"\n    (is_empty peers\n        (null)\n    )\n)\n"
+    );
+}
+
+#[test]
+fn format_indentation() {
+    let source_code = r#"(seq (call "peer" ("service" "function") [] result)
+ (par
+(null)   (fold result i (next i))))"#;
+
+    let expected = r#"(seq
+    (call "peer" ("service" "function") [] result)
+    (par
+        (null)
+        (fold result i
+            (next i)
+        )
+    )
+)
+"#;
+
+    assert_eq!(format(source_code).expect("formatting failed"), expected);
+}
+
+#[test]
+fn format_keeps_comments() {
+    let source_code = r#"
+; leading comment
+(seq ; header comment
+  ; before the first call
+  (call "peer" ("service" "function") [] result) ; trailing comment
+       (call "peer" ("service" "function") [result]) ;; another trailing one
+  ; before the closing bracket
+)
+; final comment"#;
+
+    let expected = r#"; leading comment
+(seq ; header comment
+    ; before the first call
+    (call "peer" ("service" "function") [] result) ; trailing comment
+    (call "peer" ("service" "function") [result]) ;; another trailing one
+    ; before the closing bracket
+)
+; final comment
+"#;
+
+    let formatted = format(source_code).expect("formatting failed");
+    assert_eq!(formatted, expected);
+    assert_eq!(format(&formatted).expect("formatting failed"), formatted);
+    assert_eq!(parse(&formatted), parse(source_code));
+}
+
+#[test]
+fn format_keeps_nary_instructions() {
+    let source_code = r#"(seq (null) (xor (null) (null) (null)) ; trailing comment
+    (seq (null) (null)) (null))"#;
+
+    let expected = r#"(seq
+    (null)
+    (xor
+        (null)
+        (null)
+        (null)
+    ) ; trailing comment
+    (seq
+        (null)
+        (null)
+    )
+    (null)
+)
+"#;
+
+    let formatted = format(source_code).expect("formatting failed");
+    assert_eq!(formatted, expected);
+    assert_eq!(parse(&formatted), parse(source_code));
+}
+
+#[test]
+fn format_invalid_script() {
+    let source_code = "(seq (null) null)";
+
+    let error = format(source_code).expect_err("formatting should fail");
+    assert_eq!(error.errors.len(), 1);
+}
+
+#[test]
+fn format_procedures() {
+    let source_code = r#"(define relay [peer relay_id] result (seq (call relay_id ("op" "identity") [])
+        (call peer ("service" "function") [] result)))
+(define empty [] (null)) (invoke relay ["peer" "relay"] output)"#;
+
+    let expected = r#"(define relay [peer relay_id] result
+    (seq
+        (call relay_id ("op" "identity") [])
+        (call peer ("service" "function") [] result)
+    )
+)
+(define empty []
+    (null)
+)
+(invoke relay ["peer" "relay"] output)
+"#;
+
+    let formatted = format(source_code).expect("formatting failed");
+    assert_eq!(formatted, expected);
+    assert_eq!(parse(&formatted), parse(source_code));
+    assert_eq!(parse(&parse(source_code).to_string()), parse(source_code));
+}
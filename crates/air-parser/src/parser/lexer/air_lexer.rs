@@ -16,7 +16,10 @@
 
 use super::errors::LexerError;
 use super::token::Token;
+use crate::parser::ast::CompareOperator;
+use crate::parser::ast::CompiledJsonPath;
 
+use std::borrow::Cow;
 use std::iter::Peekable;
 use std::str::CharIndices;
 
@@ -57,6 +60,7 @@ impl<'input> AIRLexer<'input> {
                 ch if ch.is_whitespace() => {}
 
                 '"' => return self.tokenize_string_literal(start_pos),
+                '{' => return self.tokenize_json_object(start_pos),
 
                 _ => return self.tokenize_string(start_pos),
             }
@@ -75,27 +79,161 @@ impl<'input> AIRLexer<'input> {
         }
     }
 
-    #[allow(clippy::unnecessary_wraps)]
     fn tokenize_string_literal(
         &mut self,
         start_pos: usize,
     ) -> Option<Spanned<Token<'input>, usize, LexerError>> {
+        // a literal is borrowed from the input until the first escape sequence is met
+        let mut unescaped: Option<String> = None;
+        let mut chunk_start = start_pos + 1;
+        let mut escape_error = None;
+
         while let Some((pos, ch)) = self.chars.next() {
-            if ch == '"' {
-                // + 1 to count an open double quote
-                let string_size = pos - start_pos + 1;
-
-                return Some(Ok((
-                    start_pos,
-                    Token::StringLiteral(&self.input[start_pos + 1..pos]),
-                    start_pos + string_size,
-                )));
+            match ch {
+                '"' => {
+                    if let Some(error) = escape_error {
+                        return Some(Err(error));
+                    }
+
+                    let literal = match unescaped {
+                        Some(mut literal) => {
+                            literal.push_str(&self.input[chunk_start..pos]);
+                            Cow::Owned(literal)
+                        }
+                        None => Cow::Borrowed(&self.input[chunk_start..pos]),
+                    };
+
+                    return Some(Ok((start_pos, Token::StringLiteral(literal), pos + 1)));
+                }
+                '\\' => {
+                    let literal = unescaped.get_or_insert_with(String::new);
+                    literal.push_str(&self.input[chunk_start..pos]);
+
+                    match self.tokenize_escape_sequence(pos) {
+                        Ok((ch, end_pos)) => {
+                            literal.push(ch);
+                            chunk_start = end_pos;
+                        }
+                        Err(error) => {
+                            // keep scanning up to the closing quote to continue lexing after it
+                            escape_error.get_or_insert(error);
+                        }
+                    }
+                }
+                _ => {}
             }
         }
 
         Some(Err(LexerError::UnclosedQuote(start_pos, self.input.len())))
     }
 
+    /// Tokenize an escape sequence started with a backslash at the supplied position,
+    /// returns an escaped character and a position right after the sequence.
+    fn tokenize_escape_sequence(&mut self, start_pos: usize) -> Result<(char, usize), LexerError> {
+        let invalid_sequence = |end_pos| LexerError::InvalidEscapeSequence(start_pos, end_pos);
+
+        let (pos, ch) = match self.chars.next() {
+            Some(pos_and_ch) => pos_and_ch,
+            None => return Err(invalid_sequence(self.input.len())),
+        };
+
+        match ch {
+            '"' => Ok(('"', pos + 1)),
+            '\\' => Ok(('\\', pos + 1)),
+            'n' => Ok(('\n', pos + 1)),
+            't' => Ok(('\t', pos + 1)),
+            'u' => self.tokenize_unicode_escape(start_pos, pos + 1),
+            ch => Err(invalid_sequence(pos + ch.len_utf8())),
+        }
+    }
+
+    /// Tokenize the `{..}` part of an unicode escape sequence.
+    fn tokenize_unicode_escape(
+        &mut self,
+        start_pos: usize,
+        brace_pos: usize,
+    ) -> Result<(char, usize), LexerError> {
+        const MAX_HEX_DIGITS: usize = 6;
+
+        if !matches!(self.chars.peek(), Some((_, '{'))) {
+            return Err(LexerError::InvalidEscapeSequence(start_pos, brace_pos));
+        }
+        self.chars.next();
+
+        let digits_start = brace_pos + 1;
+        while let Some(&(pos, ch)) = self.chars.peek() {
+            if ch == '}' {
+                self.chars.next();
+
+                let digits = &self.input[digits_start..pos];
+                let end_pos = pos + 1;
+                if digits.is_empty() || digits.len() > MAX_HEX_DIGITS {
+                    return Err(LexerError::InvalidEscapeSequence(start_pos, end_pos));
+                }
+
+                return u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(std::char::from_u32)
+                    .map(|ch| (ch, end_pos))
+                    .ok_or(LexerError::InvalidEscapeSequence(start_pos, end_pos));
+            }
+
+            if !ch.is_ascii_hexdigit() {
+                return Err(LexerError::InvalidEscapeSequence(start_pos, pos));
+            }
+            self.chars.next();
+        }
+
+        Err(LexerError::InvalidEscapeSequence(
+            start_pos,
+            self.input.len(),
+        ))
+    }
+
+    /// Tokenize a json object literal, the braces are balanced skipping json strings.
+    fn tokenize_json_object(
+        &mut self,
+        start_pos: usize,
+    ) -> Option<Spanned<Token<'input>, usize, LexerError>> {
+        let mut braces_balance = 1;
+        let mut is_in_string = false;
+        let mut is_escaped = false;
+
+        while let Some((pos, ch)) = self.chars.next() {
+            if is_in_string {
+                match ch {
+                    _ if is_escaped => is_escaped = false,
+                    '\\' => is_escaped = true,
+                    '"' => is_in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match ch {
+                '"' => is_in_string = true,
+                '{' => braces_balance += 1,
+                '}' => braces_balance -= 1,
+                _ => {}
+            }
+
+            if braces_balance == 0 {
+                let end_pos = pos + 1;
+                let object = &self.input[start_pos..end_pos];
+
+                return match serde_json::from_str(object) {
+                    Ok(object) => Some(Ok((start_pos, Token::JsonObject(object), end_pos))),
+                    Err(_) => Some(Err(LexerError::InvalidJsonObject(start_pos, end_pos))),
+                };
+            }
+        }
+
+        Some(Err(LexerError::InvalidJsonObject(
+            start_pos,
+            self.input.len(),
+        )))
+    }
+
     #[allow(clippy::unnecessary_wraps)]
     fn tokenize_string(
         &mut self,
@@ -174,17 +312,39 @@ fn string_to_token(input: &str, start_pos: usize) -> Result<Token, LexerError> {
         "" => Err(LexerError::EmptyString(start_pos, start_pos)),
 
         CALL_INSTR => Ok(Token::Call),
+        AP_INSTR => Ok(Token::Ap),
         SEQ_INSTR => Ok(Token::Seq),
         PAR_INSTR => Ok(Token::Par),
         NULL_INSTR => Ok(Token::Null),
         FOLD_INSTR => Ok(Token::Fold),
+        NEW_INSTR => Ok(Token::New),
         XOR_INSTR => Ok(Token::Xor),
         NEXT_INSTR => Ok(Token::Next),
         MATCH_INSTR => Ok(Token::Match),
         MISMATCH_INSTR => Ok(Token::MisMatch),
+        LT_INSTR => Ok(Token::Compare(CompareOperator::Less)),
+        LTE_INSTR => Ok(Token::Compare(CompareOperator::LessOrEqual)),
+        GT_INSTR => Ok(Token::Compare(CompareOperator::Greater)),
+        GTE_INSTR => Ok(Token::Compare(CompareOperator::GreaterOrEqual)),
+        CONTAINS_INSTR => Ok(Token::Compare(CompareOperator::Contains)),
+        TYPE_OF_INSTR => Ok(Token::Compare(CompareOperator::TypeOf)),
+        IS_EMPTY_INSTR => Ok(Token::IsEmpty),
+        FAIL_INSTR => Ok(Token::Fail),
+        DEFINE_INSTR => Ok(Token::Define),
+        INVOKE_INSTR => Ok(Token::Invoke),
 
         INIT_PEER_ID => Ok(Token::InitPeerId),
+        CURRENT_PEER_ID => Ok(Token::CurrentPeerId),
+        PARTICLE_ID => Ok(Token::ParticleId),
+        TIMESTAMP => Ok(Token::Timestamp),
+        TTL => Ok(Token::Ttl),
+        LAST_ERROR => Ok(Token::LastError),
+
+        TRUE_VALUE => Ok(Token::Boolean(true)),
+        FALSE_VALUE => Ok(Token::Boolean(false)),
 
+        str if is_number_start(str) => try_parse_number(str, start_pos),
+        str if str.starts_with(LAST_ERROR_PATH_START) => try_parse_last_error_path(str, start_pos),
         str if str.ends_with(ACC_END_TAG) => try_parse_accumulator(str, start_pos),
         str => try_parse_call_variable(str, start_pos),
     }
@@ -201,7 +361,8 @@ fn try_parse_accumulator(maybe_acc: &str, start: usize) -> Result<Token, LexerEr
     // this slice is safe here because str's been checked for ending with "[]"
     let maybe_acc = &maybe_acc[0..str_len - ACC_END_TAG_SIZE];
 
-    for (pos, ch) in maybe_acc.chars().enumerate() {
+    // positions are byte offsets, so that they could be used for slicing the input
+    for (pos, ch) in maybe_acc.char_indices() {
         if !is_aqua_alphanumeric(ch) {
             return Err(LexerError::IsNotAlphanumeric(start + pos, start + pos));
         }
@@ -210,10 +371,30 @@ fn try_parse_accumulator(maybe_acc: &str, start: usize) -> Result<Token, LexerEr
     Ok(Token::Accumulator(maybe_acc))
 }
 
+fn try_parse_number(maybe_number: &str, start: usize) -> Result<Token, LexerError> {
+    use serde_json::Number;
+
+    if let Ok(number) = maybe_number.parse::<i64>() {
+        return Ok(Token::Number(number.into()));
+    }
+    if let Ok(number) = maybe_number.parse::<u64>() {
+        return Ok(Token::Number(number.into()));
+    }
+
+    match maybe_number.parse::<f64>() {
+        Ok(number) => Number::from_f64(number)
+            .map(Token::Number)
+            .ok_or_else(|| LexerError::InvalidNumber(start, start + maybe_number.len())),
+        // it still could be a variable name started with a digit
+        Err(_) => try_parse_call_variable(maybe_number, start),
+    }
+}
+
 fn try_parse_call_variable(maybe_var: &str, start: usize) -> Result<Token, LexerError> {
     let mut json_path_start_pos = None;
 
-    for (pos, ch) in maybe_var.chars().enumerate() {
+    // positions are byte offsets, so that they could be used for slicing the variable
+    for (pos, ch) in maybe_var.char_indices() {
         if !json_path_started(json_path_start_pos) && is_json_path_start_point(ch) {
             json_path_start_pos = Some(pos);
         } else if !json_path_started(json_path_start_pos) && !is_aqua_alphanumeric(ch) {
@@ -224,25 +405,81 @@ fn try_parse_call_variable(maybe_var: &str, start: usize) -> Result<Token, Lexer
     }
 
     match json_path_start_pos {
-        Some(pos) => Ok(Token::JsonPath(maybe_var, pos)),
+        Some(pos) => {
+            let variable = &maybe_var[0..pos];
+            let path = &maybe_var[pos + 1..];
+            let path = CompiledJsonPath::compile(path).map_err(|_| {
+                LexerError::InvalidJsonPathSyntax(start + pos + 1, start + maybe_var.len())
+            })?;
+
+            Ok(Token::JsonPath(variable, path))
+        }
         None => Ok(Token::Alphanumeric(maybe_var)),
     }
 }
 
+fn try_parse_last_error_path(last_error_path: &str, start: usize) -> Result<Token, LexerError> {
+    let path_start = LAST_ERROR_PATH_START.len();
+    let path = &last_error_path[path_start..];
+
+    for (pos, ch) in path.char_indices() {
+        if !json_path_allowed_char(ch) {
+            let pos = start + path_start + pos;
+            return Err(LexerError::InvalidJsonPath(pos, pos));
+        }
+    }
+
+    let path = CompiledJsonPath::compile(path).map_err(|_| {
+        LexerError::InvalidJsonPathSyntax(start + path_start, start + last_error_path.len())
+    })?;
+
+    Ok(Token::LastErrorPath(path))
+}
+
 const CALL_INSTR: &str = "call";
+const AP_INSTR: &str = "ap";
 const SEQ_INSTR: &str = "seq";
 const PAR_INSTR: &str = "par";
 const NULL_INSTR: &str = "null";
 const FOLD_INSTR: &str = "fold";
+const NEW_INSTR: &str = "new";
 const XOR_INSTR: &str = "xor";
 const NEXT_INSTR: &str = "next";
 const MATCH_INSTR: &str = "match";
 const MISMATCH_INSTR: &str = "mismatch";
+const LT_INSTR: &str = "lt";
+const LTE_INSTR: &str = "lte";
+const GT_INSTR: &str = "gt";
+const GTE_INSTR: &str = "gte";
+const CONTAINS_INSTR: &str = "contains";
+const TYPE_OF_INSTR: &str = "type_of";
+const IS_EMPTY_INSTR: &str = "is_empty";
+const FAIL_INSTR: &str = "fail";
+const DEFINE_INSTR: &str = "define";
+const INVOKE_INSTR: &str = "invoke";
 
 const INIT_PEER_ID: &str = "%init_peer_id%";
+const CURRENT_PEER_ID: &str = "%current_peer_id%";
+const PARTICLE_ID: &str = "%particle_id%";
+const TIMESTAMP: &str = "%timestamp%";
+const TTL: &str = "%ttl%";
+const LAST_ERROR: &str = "%last_error%";
+const LAST_ERROR_PATH_START: &str = "%last_error%.";
+
+const TRUE_VALUE: &str = "true";
+const FALSE_VALUE: &str = "false";
 
 const ACC_END_TAG: &str = "[]";
 
+fn is_number_start(str: &str) -> bool {
+    let mut chars = str.chars();
+    match chars.next() {
+        Some('-') => matches!(chars.next(), Some(ch) if ch.is_ascii_digit()),
+        Some(ch) => ch.is_ascii_digit(),
+        None => false,
+    }
+}
+
 fn is_json_path_start_point(ch: char) -> bool {
     ch == '.'
 }
@@ -14,6 +14,8 @@
  * limitations under the License.
  */
 
+use crate::parser::Span;
+
 use thiserror::Error as ThisError;
 
 #[derive(ThisError, Debug, Clone, PartialEq, Eq, Hash)]
@@ -21,6 +23,9 @@ pub enum LexerError {
     #[error("this string literal has unclosed quote")]
     UnclosedQuote(usize, usize),
 
+    #[error(r#"invalid escape sequence, only \", \\, \n, \t and \u{{..}} are allowed"#)]
+    InvalidEscapeSequence(usize, usize),
+
     #[error("empty string aren't allowed in this position")]
     EmptyString(usize, usize),
 
@@ -32,6 +37,35 @@ pub enum LexerError {
 
     #[error("invalid character in json path")]
     InvalidJsonPath(usize, usize),
+
+    #[error("this json path has invalid syntax")]
+    InvalidJsonPathSyntax(usize, usize),
+
+    #[error("this number literal can't be represented in json")]
+    InvalidNumber(usize, usize),
+
+    #[error("this json object literal is invalid or has unclosed brace")]
+    InvalidJsonObject(usize, usize),
+}
+
+impl LexerError {
+    pub fn span(&self) -> Span {
+        use LexerError::*;
+
+        let (left, right) = match self {
+            UnclosedQuote(left, right) => (left, right),
+            InvalidEscapeSequence(left, right) => (left, right),
+            EmptyString(left, right) => (left, right),
+            IsNotAlphanumeric(left, right) => (left, right),
+            EmptyAccName(left, right) => (left, right),
+            InvalidJsonPath(left, right) => (left, right),
+            InvalidJsonPathSyntax(left, right) => (left, right),
+            InvalidNumber(left, right) => (left, right),
+            InvalidJsonObject(left, right) => (left, right),
+        };
+
+        Span::new(*left, *right)
+    }
 }
 
 impl From<std::convert::Infallible> for LexerError {
@@ -0,0 +1,42 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::borrow::Cow;
+
+/// Escape a string literal value so that the lexer turns it back into the same value.
+pub(crate) fn escape_string_literal(literal: &str) -> Cow<'_, str> {
+    if !literal.chars().any(should_be_escaped) {
+        return Cow::Borrowed(literal);
+    }
+
+    let mut escaped = String::with_capacity(literal.len() + 2);
+    for ch in literal.chars() {
+        match ch {
+            '"' => escaped.push_str(r#"\""#),
+            '\\' => escaped.push_str(r"\\"),
+            '\n' => escaped.push_str(r"\n"),
+            '\t' => escaped.push_str(r"\t"),
+            ch if ch.is_control() => escaped.push_str(&format!(r"\u{{{:x}}}", ch as u32)),
+            ch => escaped.push(ch),
+        }
+    }
+
+    Cow::Owned(escaped)
+}
+
+fn should_be_escaped(ch: char) -> bool {
+    ch == '"' || ch == '\\' || ch.is_control()
+}
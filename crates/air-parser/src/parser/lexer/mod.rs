@@ -16,6 +16,7 @@
 
 mod air_lexer;
 mod errors;
+mod escape;
 mod token;
 
 #[cfg(test)]
@@ -23,4 +24,5 @@ pub mod tests;
 
 pub use air_lexer::AIRLexer;
 pub use errors::LexerError;
+pub(crate) use escape::escape_string_literal;
 pub use token::Token;
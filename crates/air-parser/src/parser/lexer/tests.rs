@@ -18,6 +18,7 @@ use super::air_lexer::Spanned;
 use super::AIRLexer;
 use super::LexerError;
 use super::Token;
+use crate::parser::ast::CompareOperator;
 
 fn run_lexer(input: &str) -> Vec<Spanned<Token<'_>, usize, LexerError>> {
     let lexer = AIRLexer::new(input);
@@ -39,6 +40,36 @@ fn air_instructions() {
         ]
     );
 
+    let ap_tokens = run_lexer("ap");
+    assert_eq!(ap_tokens, vec![Ok((0, Token::Ap, 2))]);
+
+    let fail_tokens = run_lexer("fail");
+    assert_eq!(fail_tokens, vec![Ok((0, Token::Fail, 4))]);
+
+    let new_tokens = run_lexer("new");
+    assert_eq!(new_tokens, vec![Ok((0, Token::New, 3))]);
+
+    let define_tokens = run_lexer("define");
+    assert_eq!(define_tokens, vec![Ok((0, Token::Define, 6))]);
+
+    let invoke_tokens = run_lexer("invoke");
+    assert_eq!(invoke_tokens, vec![Ok((0, Token::Invoke, 6))]);
+
+    let gte_tokens = run_lexer("gte");
+    assert_eq!(
+        gte_tokens,
+        vec![Ok((0, Token::Compare(CompareOperator::GreaterOrEqual), 3))]
+    );
+
+    let type_of_tokens = run_lexer("type_of");
+    assert_eq!(
+        type_of_tokens,
+        vec![Ok((0, Token::Compare(CompareOperator::TypeOf), 7))]
+    );
+
+    let is_empty_tokens = run_lexer("is_empty");
+    assert_eq!(is_empty_tokens, vec![Ok((0, Token::IsEmpty, 8))]);
+
     let par_tokens = run_lexer("par");
     assert_eq!(par_tokens, vec![Ok((0, Token::Par, 3))]);
 
@@ -142,6 +173,48 @@ fn init_peer_id() {
     );
 }
 
+#[test]
+fn run_parameters() {
+    let tokens = run_lexer("%current_peer_id% %particle_id% %timestamp% %ttl%");
+    assert_eq!(
+        tokens,
+        vec![
+            Ok((0, Token::CurrentPeerId, 17)),
+            Ok((18, Token::ParticleId, 31)),
+            Ok((32, Token::Timestamp, 43)),
+            Ok((44, Token::Ttl, 49)),
+        ]
+    );
+}
+
+#[test]
+fn last_error() {
+    use crate::ast::CompiledJsonPath;
+
+    const LAST_ERROR: &str = "%last_error%";
+
+    let last_error_tokens = run_lexer(LAST_ERROR);
+    assert_eq!(
+        last_error_tokens,
+        vec![Ok((0, Token::LastError, LAST_ERROR.len()))]
+    );
+
+    const LAST_ERROR_PATH: &str = "%last_error%.$.message";
+
+    let last_error_tokens = run_lexer(LAST_ERROR_PATH);
+    let path = CompiledJsonPath::compile("$.message").unwrap();
+    assert_eq!(
+        last_error_tokens,
+        vec![Ok((0, Token::LastErrorPath(path), LAST_ERROR_PATH.len()))]
+    );
+
+    let last_error_tokens = run_lexer("%last_error%.$.[");
+    assert_eq!(
+        last_error_tokens,
+        vec![Err(LexerError::InvalidJsonPathSyntax(13, 16))]
+    );
+}
+
 #[test]
 fn accumulator() {
     const ACC: &str = "accumulator____asdasd[]";
@@ -166,7 +239,7 @@ fn string_literal() {
         string_literal_tokens,
         vec![Ok((
             0,
-            Token::StringLiteral(&STRING_LITERAL[1..STRING_LITERAL.len() - 1]),
+            Token::StringLiteral(STRING_LITERAL[1..STRING_LITERAL.len() - 1].into()),
             STRING_LITERAL.len()
         ))]
     );
@@ -174,13 +247,35 @@ fn string_literal() {
 
 #[test]
 fn json_path() {
-    // this json path contains all allowed in json path charactes
+    use crate::ast::CompiledJsonPath;
+
+    const JSON_PATH: &str = r#"value.$.peers[0]["name"].all!"#;
+
+    let json_path_tokens = run_lexer(JSON_PATH);
+    let path = CompiledJsonPath::compile(&JSON_PATH[6..]).unwrap();
+    assert_eq!(
+        json_path_tokens,
+        vec![Ok((0, Token::JsonPath("value", path), JSON_PATH.len()))]
+    );
+}
+
+#[test]
+fn json_path_syntax_error() {
+    // this json path contains all allowed in json path characters, but it can't be compiled
     const JSON_PATH: &str = r#"value.$[$@[]():?.*,"!]"#;
 
     let json_path_tokens = run_lexer(JSON_PATH);
     assert_eq!(
         json_path_tokens,
-        vec![Ok((0, Token::JsonPath(JSON_PATH, 5), JSON_PATH.len()))]
+        vec![Err(LexerError::InvalidJsonPathSyntax(6, JSON_PATH.len()))]
+    );
+
+    const UNCLOSED_JSON_PATH: &str = "(call value.$.[ \"f\" [])";
+
+    let json_path_tokens = run_lexer(UNCLOSED_JSON_PATH);
+    assert_eq!(
+        json_path_tokens[2],
+        Err(LexerError::InvalidJsonPathSyntax(12, 15))
     );
 }
 
@@ -217,3 +312,186 @@ fn invalid_json_path() {
         vec![Err(LexerError::InvalidJsonPath(7, 7))]
     );
 }
+
+#[test]
+fn multibyte_identifiers() {
+    use crate::ast::CompiledJsonPath;
+
+    const SCRIPT: &str = r#"(call "пир" ("сервис" 函数) [данные.$.名前] 结果[])"#;
+
+    let tokens = run_lexer(SCRIPT);
+    let offset = |fragment: &str| SCRIPT.find(fragment).unwrap();
+
+    let json_path_start = offset("данные");
+    let json_path_end = json_path_start + "данные.$.名前".len();
+    let path = CompiledJsonPath::compile("$.名前").unwrap();
+    let acc_start = offset("结果");
+
+    assert_eq!(
+        tokens[2],
+        Ok((
+            offset("\"пир\""),
+            Token::StringLiteral("пир".into()),
+            offset(" (\"")
+        ))
+    );
+    assert_eq!(
+        tokens[5],
+        Ok((offset("函数"), Token::Alphanumeric("函数"), offset(") [")))
+    );
+    assert_eq!(
+        tokens[8],
+        Ok((
+            json_path_start,
+            Token::JsonPath("данные", path),
+            json_path_end
+        ))
+    );
+    assert_eq!(
+        tokens[10],
+        Ok((
+            acc_start,
+            Token::Accumulator("结果"),
+            acc_start + "结果[]".len()
+        ))
+    );
+}
+
+#[test]
+fn multibyte_identifier_errors() {
+    // offsets are in bytes, so they point right to the wrong char
+    const INVALID_VARIABLE: &str = "пер§еменная";
+    let position = INVALID_VARIABLE.find('§').unwrap();
+    assert_eq!(position, 6);
+    assert_eq!(
+        run_lexer(INVALID_VARIABLE),
+        vec![Err(LexerError::IsNotAlphanumeric(position, position))]
+    );
+
+    const INVALID_ACCUMULATOR: &str = "累加§器[]";
+    let position = INVALID_ACCUMULATOR.find('§').unwrap();
+    assert_eq!(
+        run_lexer(INVALID_ACCUMULATOR),
+        vec![Err(LexerError::IsNotAlphanumeric(position, position))]
+    );
+
+    const INVALID_JSON_PATH: &str = "данные.$.名前%";
+    let position = INVALID_JSON_PATH.find('%').unwrap();
+    assert_eq!(
+        run_lexer(INVALID_JSON_PATH),
+        vec![Err(LexerError::InvalidJsonPath(position, position))]
+    );
+
+    const JSON_PATH_SYNTAX_ERROR: &str = "данные.$.[";
+    assert_eq!(
+        run_lexer(JSON_PATH_SYNTAX_ERROR),
+        vec![Err(LexerError::InvalidJsonPathSyntax(
+            "данные.".len(),
+            JSON_PATH_SYNTAX_ERROR.len()
+        ))]
+    );
+}
+
+#[test]
+fn escaped_string_literal() {
+    const STRING_LITERAL: &str = r#""\"quoted\" \\ \n\t\u{44}\u{1f600}""#;
+
+    let string_literal_tokens = run_lexer(STRING_LITERAL);
+    assert_eq!(
+        string_literal_tokens,
+        vec![Ok((
+            0,
+            Token::StringLiteral("\"quoted\" \\ \n\tD😀".into()),
+            STRING_LITERAL.len()
+        ))]
+    );
+}
+
+#[test]
+fn invalid_escape_sequence() {
+    const STRING_LITERAL: &str = r#"("ab\c" "\u{d800}" "\u{12" "\u{}")"#;
+
+    let string_literal_tokens = run_lexer(STRING_LITERAL);
+    assert_eq!(
+        string_literal_tokens,
+        vec![
+            Ok((0, Token::OpenRoundBracket, 1)),
+            Err(LexerError::InvalidEscapeSequence(4, 6)),
+            Err(LexerError::InvalidEscapeSequence(9, 17)),
+            Err(LexerError::InvalidEscapeSequence(20, 25)),
+            Err(LexerError::InvalidEscapeSequence(28, 32)),
+            Ok((33, Token::CloseRoundBracket, 34)),
+        ]
+    );
+}
+
+#[test]
+fn unclosed_escaped_quote() {
+    const STRING_LITERAL: &str = r#""abc\""#;
+
+    let string_literal_tokens = run_lexer(STRING_LITERAL);
+    assert_eq!(
+        string_literal_tokens,
+        vec![Err(LexerError::UnclosedQuote(0, STRING_LITERAL.len()))]
+    );
+}
+
+#[test]
+fn number_and_boolean_literals() {
+    use serde_json::Number;
+
+    const LITERALS: &str = "42 -7 1.5 -2e3 18446744073709551615 true false 1st";
+
+    let literal_tokens = run_lexer(LITERALS);
+    assert_eq!(
+        literal_tokens,
+        vec![
+            Ok((0, Token::Number(42.into()), 2)),
+            Ok((3, Token::Number((-7).into()), 5)),
+            Ok((6, Token::Number(Number::from_f64(1.5).unwrap()), 9)),
+            Ok((10, Token::Number(Number::from_f64(-2e3).unwrap()), 14)),
+            Ok((15, Token::Number(u64::MAX.into()), 35)),
+            Ok((36, Token::Boolean(true), 40)),
+            Ok((41, Token::Boolean(false), 46)),
+            Ok((47, Token::Alphanumeric("1st"), 50)),
+        ]
+    );
+}
+
+#[test]
+fn too_big_number() {
+    const NUMBER: &str = "1e400";
+
+    let number_tokens = run_lexer(NUMBER);
+    assert_eq!(
+        number_tokens,
+        vec![Err(LexerError::InvalidNumber(0, NUMBER.len()))]
+    );
+}
+
+#[test]
+fn json_object_literal() {
+    const OBJECT: &str = r#"{"a": {"b": [1, "}"]}, "c\"}": null}"#;
+
+    let object_tokens = run_lexer(OBJECT);
+    let expected_object = serde_json::json!({"a": {"b": [1, "}"]}, "c\"}": null});
+    assert_eq!(
+        object_tokens,
+        vec![Ok((0, Token::JsonObject(expected_object), OBJECT.len()))]
+    );
+}
+
+#[test]
+fn invalid_json_object_literal() {
+    const OBJECTS: &str = r#"({"a": } {"a": 1)"#;
+
+    let object_tokens = run_lexer(OBJECTS);
+    assert_eq!(
+        object_tokens,
+        vec![
+            Ok((0, Token::OpenRoundBracket, 1)),
+            Err(LexerError::InvalidJsonObject(1, 8)),
+            Err(LexerError::InvalidJsonObject(9, OBJECTS.len())),
+        ]
+    );
+}
@@ -14,27 +14,98 @@
  * limitations under the License.
  */
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+use super::escape_string_literal;
+use crate::parser::ast::CompareOperator;
+use crate::parser::ast::CompiledJsonPath;
+
+use serde_json::Number;
+use serde_json::Value as JValue;
+
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Token<'input> {
     OpenRoundBracket,
     CloseRoundBracket,
     OpenSquareBracket,
     CloseSquareBracket,
 
-    StringLiteral(&'input str),
+    StringLiteral(Cow<'input, str>),
+    Number(Number),
+    Boolean(bool),
+    JsonObject(JValue),
     Alphanumeric(&'input str),
-    JsonPath(&'input str, usize),
+    JsonPath(&'input str, CompiledJsonPath<'input>),
     Accumulator(&'input str),
 
     InitPeerId,
+    CurrentPeerId,
+    ParticleId,
+    Timestamp,
+    Ttl,
+    LastError,
+    LastErrorPath(CompiledJsonPath<'input>),
 
     Call,
+    Ap,
     Seq,
     Par,
     Null,
     Fold,
+    New,
     Xor,
     Next,
     Match,
     MisMatch,
+    Compare(CompareOperator),
+    IsEmpty,
+    Fail,
+    Define,
+    Invoke,
+}
+
+impl std::fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Token::*;
+
+        match self {
+            OpenRoundBracket => write!(f, "("),
+            CloseRoundBracket => write!(f, ")"),
+            OpenSquareBracket => write!(f, "["),
+            CloseSquareBracket => write!(f, "]"),
+
+            StringLiteral(literal) => write!(f, r#""{}""#, escape_string_literal(literal)),
+            Number(number) => write!(f, "{}", number),
+            Boolean(value) => write!(f, "{}", value),
+            JsonObject(object) => write!(f, "{}", object),
+            Alphanumeric(str) => write!(f, "{}", str),
+            JsonPath(variable, path) => write!(f, "{}.{}", variable, path),
+            Accumulator(acc) => write!(f, "{}[]", acc),
+
+            InitPeerId => write!(f, "%init_peer_id%"),
+            CurrentPeerId => write!(f, "%current_peer_id%"),
+            ParticleId => write!(f, "%particle_id%"),
+            Timestamp => write!(f, "%timestamp%"),
+            Ttl => write!(f, "%ttl%"),
+            LastError => write!(f, "%last_error%"),
+            LastErrorPath(path) => write!(f, "%last_error%.{}", path),
+
+            Call => write!(f, "call"),
+            Ap => write!(f, "ap"),
+            Seq => write!(f, "seq"),
+            Par => write!(f, "par"),
+            Null => write!(f, "null"),
+            Fold => write!(f, "fold"),
+            New => write!(f, "new"),
+            Xor => write!(f, "xor"),
+            Next => write!(f, "next"),
+            Match => write!(f, "match"),
+            MisMatch => write!(f, "mismatch"),
+            Compare(operator) => write!(f, "{}", operator),
+            IsEmpty => write!(f, "is_empty"),
+            Fail => write!(f, "fail"),
+            Define => write!(f, "define"),
+            Invoke => write!(f, "invoke"),
+        }
+    }
 }
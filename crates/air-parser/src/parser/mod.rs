@@ -15,7 +15,12 @@
  */
 
 pub mod air_parser;
+mod errors;
+mod formatter;
 mod lexer;
+mod parsed_script;
+mod span;
+mod validator;
 
 // air is auto-generated, so exclude it from `cargo fmt -- --check` and `cargo clippy`
 #[rustfmt::skip]
@@ -29,8 +34,18 @@ pub mod tests;
 
 pub use self::air_parser::parse;
 pub use air::AIRParser;
+pub use errors::ParserError;
+pub use errors::ParserErrorKind;
+pub use errors::SpannedError;
+pub use formatter::format;
 pub use lexer::AIRLexer;
-
-fn into_variable_and_path(str: &str, pos: usize) -> (&str, &str) {
-    (&str[0..pos], &str[pos + 1..])
-}
+pub use lexer::LexerError;
+pub use lexer::Token;
+pub use parsed_script::ParsedScript;
+pub use span::line_column_to_offset;
+pub use span::offset_to_line_column;
+pub use span::Span;
+pub use validator::validate;
+pub use validator::SemanticDiagnostic;
+pub use validator::SemanticDiagnosticKind;
+pub use validator::ValidationReport;
@@ -0,0 +1,69 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::ast::Instruction;
+use super::ParserError;
+
+use std::rc::Rc;
+
+/// An AST that owns all its strings together with the script it was parsed from, so it
+/// doesn't borrow anything and could be stored and reused across several executions of the same script.
+pub struct ParsedScript {
+    instruction: Box<Instruction<'static>>,
+    script: Rc<str>,
+}
+
+impl ParsedScript {
+    /// Parse AIR `air_script` to an owned AST.
+    pub fn parse(air_script: impl Into<Rc<str>>) -> Result<Self, ParserError> {
+        let script: Rc<str> = air_script.into();
+        let instruction = super::parse(&script)?;
+
+        Ok(Self {
+            instruction: Box::new(instruction.into_owned()),
+            script,
+        })
+    }
+
+    /// Deserialize an AST previously serialized to JSON, so that the AIR parsing is skipped.
+    pub fn from_json(json_ast: impl Into<Rc<str>>) -> Result<Self, serde_json::Error> {
+        let script: Rc<str> = json_ast.into();
+        let instruction = serde_json::from_str(&script)?;
+
+        Ok(Self {
+            instruction,
+            script,
+        })
+    }
+
+    /// Returns the root instruction of the AST.
+    pub fn instruction(&self) -> &Instruction<'_> {
+        &self.instruction
+    }
+
+    /// Returns the script or the JSON this AST was built from.
+    pub fn script(&self) -> &str {
+        &self.script
+    }
+}
+
+impl std::fmt::Debug for ParsedScript {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParsedScript")
+            .field("instruction", self.instruction())
+            .finish()
+    }
+}
@@ -0,0 +1,95 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Location of an AIR script fragment, represented as a half-open range of byte offsets.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Span {
+    pub left: usize,
+    pub right: usize,
+}
+
+impl Span {
+    pub fn new(left: usize, right: usize) -> Self {
+        Self { left, right }
+    }
+
+    /// Returns 1-based line and column of the span start in the supplied script.
+    pub fn line_column(&self, air_script: &str) -> (usize, usize) {
+        offset_to_line_column(air_script, self.left)
+    }
+
+    /// Returns 1-based line and column of the span end in the supplied script.
+    pub fn end_line_column(&self, air_script: &str) -> (usize, usize) {
+        offset_to_line_column(air_script, self.right)
+    }
+}
+
+/// Converts a byte offset in the script to 1-based line and column, columns are counted in chars.
+/// An offset inside a multibyte char points to this char, an offset past the end to the end.
+pub fn offset_to_line_column(air_script: &str, offset: usize) -> (usize, usize) {
+    let mut offset = offset.min(air_script.len());
+    while !air_script.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before_offset = &air_script[..offset];
+
+    let line = before_offset.matches('\n').count() + 1;
+    let line_start = before_offset.rfind('\n').map_or(0, |pos| pos + 1);
+    let column = before_offset[line_start..].chars().count() + 1;
+
+    (line, column)
+}
+
+/// Converts 1-based line and column to a byte offset in the script, returns None
+/// if there is no such position in the script. The position right after a line end is allowed.
+pub fn line_column_to_offset(air_script: &str, line: usize, column: usize) -> Option<usize> {
+    let line_start = match line {
+        0 => return None,
+        1 => 0,
+        _ => air_script
+            .match_indices('\n')
+            .nth(line - 2)
+            .map(|(pos, _)| pos + 1)?,
+    };
+    let line_end = air_script[line_start..]
+        .find('\n')
+        .map_or(air_script.len(), |pos| line_start + pos);
+
+    let line_str = &air_script[line_start..line_end];
+    let mut char_offsets = line_str
+        .char_indices()
+        .map(|(pos, _)| pos)
+        .chain(std::iter::once(line_str.len()));
+
+    char_offsets
+        .nth(column.checked_sub(1)?)
+        .map(|pos| line_start + pos)
+}
+
+impl From<std::ops::Range<usize>> for Span {
+    fn from(range: std::ops::Range<usize>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
+
+impl From<Span> for std::ops::Range<usize> {
+    fn from(span: Span) -> Self {
+        span.left..span.right
+    }
+}
@@ -15,13 +15,123 @@
  */
 
 use crate::ast;
+use ast::visitor::*;
 use ast::Instruction;
+use ast::Span;
 
 use fstrings::f;
 use std::rc::Rc;
 
 fn parse(source_code: &str) -> Instruction {
-    *crate::parse(source_code).expect("parsing failed")
+    let mut instruction = *crate::parse(source_code).expect("parsing failed");
+    clear_spans(&mut instruction);
+    instruction
+}
+
+// spans are checked by separate tests, so reset them to compare instructions only by structure
+pub(crate) fn clear_spans(instruction: &mut Instruction<'_>) {
+    SpansCleaner.visit_instruction_mut(instruction)
+}
+
+struct SpansCleaner;
+
+impl<'i> VisitorMut<'i> for SpansCleaner {
+    fn visit_call_mut(&mut self, call: &mut ast::Call<'i>) {
+        call.span = Span::default();
+        walk_call_mut(self, call);
+    }
+
+    fn visit_ap_mut(&mut self, ap: &mut ast::Ap<'i>) {
+        ap.span = Span::default();
+        walk_ap_mut(self, ap);
+    }
+
+    fn visit_fail_mut(&mut self, fail: &mut ast::Fail<'i>) {
+        fail.span = Span::default();
+        walk_fail_mut(self, fail);
+    }
+
+    fn visit_seq_mut(&mut self, seq: &mut ast::Seq<'i>) {
+        seq.2 = Span::default();
+        walk_seq_mut(self, seq);
+    }
+
+    fn visit_par_mut(&mut self, par: &mut ast::Par<'i>) {
+        par.2 = Span::default();
+        walk_par_mut(self, par);
+    }
+
+    fn visit_xor_mut(&mut self, xor: &mut ast::Xor<'i>) {
+        xor.2 = Span::default();
+        walk_xor_mut(self, xor);
+    }
+
+    fn visit_match_mut(&mut self, match_: &mut ast::Match<'i>) {
+        match_.span = Span::default();
+        walk_match_mut(self, match_);
+    }
+
+    fn visit_mismatch_mut(&mut self, mismatch: &mut ast::MisMatch<'i>) {
+        mismatch.span = Span::default();
+        walk_mismatch_mut(self, mismatch);
+    }
+
+    fn visit_compare_mut(&mut self, compare: &mut ast::Compare<'i>) {
+        compare.span = Span::default();
+        walk_compare_mut(self, compare);
+    }
+
+    fn visit_fold_mut(&mut self, fold: &mut ast::Fold<'i>) {
+        fold.span = Span::default();
+        walk_fold_mut(self, fold);
+    }
+
+    fn visit_new_mut(&mut self, new: &mut ast::New<'i>) {
+        new.span = Span::default();
+        walk_new_mut(self, new);
+    }
+
+    fn visit_next_mut(&mut self, next: &mut ast::Next<'i>) {
+        next.1 = Span::default();
+    }
+
+    fn visit_null_mut(&mut self, null: &mut ast::Null) {
+        null.0 = Span::default();
+    }
+
+    fn visit_define_mut(&mut self, define: &mut ast::Define<'i>) {
+        define.span = Span::default();
+        walk_define_mut(self, define);
+    }
+
+    fn visit_invoke_mut(&mut self, invoke: &mut ast::Invoke<'i>) {
+        invoke.span = Span::default();
+        walk_invoke_mut(self, invoke);
+    }
+
+    fn visit_call_arg_value_mut(&mut self, value: &mut ast::CallArgValue<'i>) {
+        if let ast::CallArgValue::Variable(_, span) | ast::CallArgValue::JsonPath { span, .. } =
+            value
+        {
+            *span = Span::default();
+        }
+    }
+
+    fn visit_iterable_value_mut(&mut self, value: &mut ast::IterableValue<'i>) {
+        match value {
+            ast::IterableValue::Variable(_, span) | ast::IterableValue::JsonPath { span, .. } => {
+                *span = Span::default()
+            }
+        }
+    }
+
+    fn visit_matchable_value_mut(&mut self, value: &mut ast::MatchableValue<'i>) {
+        if let ast::MatchableValue::Variable(_, span) | ast::MatchableValue::JsonPath { span, .. } =
+            value
+        {
+            *span = Span::default();
+        }
+    }
 }
 
 #[test]
@@ -41,16 +151,21 @@ fn parse_seq() {
     let instruction = parse(source_code);
     let expected = seq(
         Instruction::Call(Call {
-            peer_part: PeerPk(Variable("peerid")),
-            function_part: FuncName(Variable("function")),
+            peer_part: PeerPk(Variable("peerid".into(), Span::default())),
+            function_part: FuncName(Variable("function".into(), Span::default())),
             args: Rc::new(vec![]),
-            output: Scalar("output"),
+            output: Scalar("output".into()),
+            span: Span::default(),
         }),
         Instruction::Call(Call {
-            peer_part: PeerPk(Literal("id")),
-            function_part: FuncName(Literal("f")),
-            args: Rc::new(vec![Literal("hello"), Variable("name")]),
+            peer_part: PeerPk(Literal("id".into())),
+            function_part: FuncName(Literal("f".into())),
+            args: Rc::new(vec![
+                Literal("hello".into()),
+                Variable("name".into(), Span::default()),
+            ]),
             output: None,
+            span: Span::default(),
         }),
     );
     assert_eq!(instruction, expected);
@@ -77,28 +192,92 @@ fn parse_seq_seq() {
     let expected = seq(
         seq(
             Instruction::Call(Call {
-                peer_part: PeerPk(Variable("peerid")),
-                function_part: FuncName(Variable("function")),
+                peer_part: PeerPk(Variable("peerid".into(), Span::default())),
+                function_part: FuncName(Variable("function".into(), Span::default())),
                 args: Rc::new(vec![]),
                 output: None,
+                span: Span::default(),
             }),
             Instruction::Call(Call {
-                peer_part: PeerPkWithServiceId(Variable("peerid"), Variable("serviceA")),
-                function_part: ServiceIdWithFuncName(Literal("serviceB"), Variable("function")),
+                peer_part: PeerPkWithServiceId(
+                    Variable("peerid".into(), Span::default()),
+                    Variable("serviceA".into(), Span::default()),
+                ),
+                function_part: ServiceIdWithFuncName(
+                    Literal("serviceB".into()),
+                    Variable("function".into(), Span::default()),
+                ),
                 args: Rc::new(vec![]),
                 output: None,
+                span: Span::default(),
             }),
         ),
         Instruction::Call(Call {
-            peer_part: PeerPk(Literal("id")),
-            function_part: FuncName(Literal("f")),
-            args: Rc::new(vec![Literal("hello"), Variable("name")]),
-            output: Accumulator("output"),
+            peer_part: PeerPk(Literal("id".into())),
+            function_part: FuncName(Literal("f".into())),
+            args: Rc::new(vec![
+                Literal("hello".into()),
+                Variable("name".into(), Span::default()),
+            ]),
+            output: Accumulator("output".into()),
+            span: Span::default(),
         }),
     );
     assert_eq!(instruction, expected);
 }
 
+#[test]
+fn parse_call_on_array_of_peers() {
+    use ast::Call;
+    use ast::CallArgValue::*;
+    use ast::CallOutputValue::*;
+    use ast::FunctionPart::*;
+    use ast::PeerPart::*;
+
+    let source_code = r#"
+        (seq
+            (call [peers] ("service" "function") [] results[])
+            (call ([relays.$.peers] "service") "function" [])
+        )
+        "#;
+    let instruction = parse(source_code);
+    let expected = seq(
+        Instruction::Call(Call {
+            peer_part: PeerPks(Variable("peers".into(), Span::default())),
+            function_part: ServiceIdWithFuncName(
+                Literal("service".into()),
+                Literal("function".into()),
+            ),
+            args: Rc::new(vec![]),
+            output: Accumulator("results".into()),
+            span: Span::default(),
+        }),
+        Instruction::Call(Call {
+            peer_part: PeerPksWithServiceId(
+                JsonPath {
+                    variable: "relays".into(),
+                    path: json_path("$.peers"),
+                    span: Span::default(),
+                },
+                Literal("service".into()),
+            ),
+            function_part: FuncName(Literal("function".into())),
+            args: Rc::new(vec![]),
+            output: None,
+            span: Span::default(),
+        }),
+    );
+    assert_eq!(instruction, expected);
+
+    let call = r#"(call ([peers] "service") "function" [] results[])"#;
+    let instruction = crate::parse(call).expect("parsing failed");
+    assert_eq!(instruction.to_string(), call);
+
+    // an array of peers is a variable or a json path only
+    assert!(crate::parse(r#"(call ["peer"] ("service" "function") [])"#).is_err());
+    assert!(crate::parse(r#"(call [peers other] ("service" "function") [])"#).is_err());
+}
+
 #[test]
 fn parse_json_path() {
     use ast::Call;
@@ -113,12 +292,17 @@ fn parse_json_path() {
     let instruction = parse(source_code);
     let expected = Instruction::Call(Call {
         peer_part: PeerPk(JsonPath {
-            variable: "id",
-            path: "$.a",
+            variable: "id".into(),
+            path: json_path("$.a"),
+            span: Span::default(),
         }),
-        function_part: FuncName(Literal("f")),
-        args: Rc::new(vec![Literal("hello"), Variable("name")]),
-        output: Accumulator("void"),
+        function_part: FuncName(Literal("f".into())),
+        args: Rc::new(vec![
+            Literal("hello".into()),
+            Variable("name".into(), Span::default()),
+        ]),
+        output: Accumulator("void".into()),
+        span: Span::default(),
     });
     assert_eq!(instruction, expected);
 }
@@ -134,28 +318,32 @@ fn parse_json_path_complex() {
     let source_code = r#"
         (seq
             (call m.$.[1] "f" [] void)
-            (call m.$.abc["c"].cde[a][0].cde["bcd"] "f" [] void)
+            (call m.$.abc["c"].cde["a"][0].cde["bcd"] "f" [] void)
         )
         "#;
     let instruction = parse(source_code);
     let expected = seq(
         Instruction::Call(Call {
             peer_part: PeerPk(JsonPath {
-                variable: "m",
-                path: "$.[1]",
+                variable: "m".into(),
+                path: json_path("$.[1]"),
+                span: Span::default(),
             }),
-            function_part: FuncName(Literal("f")),
+            function_part: FuncName(Literal("f".into())),
             args: Rc::new(vec![]),
-            output: Scalar("void"),
+            output: Scalar("void".into()),
+            span: Span::default(),
         }),
         Instruction::Call(Call {
             peer_part: PeerPk(JsonPath {
-                variable: "m",
-                path: r#"$.abc["c"].cde[a][0].cde["bcd"]"#,
+                variable: "m".into(),
+                path: json_path(r#"$.abc["c"].cde["a"][0].cde["bcd"]"#),
+                span: Span::default(),
             }),
-            function_part: FuncName(Literal("f")),
+            function_part: FuncName(Literal("f".into())),
             args: Rc::new(vec![]),
-            output: Scalar("void"),
+            output: Scalar("void".into()),
+            span: Span::default(),
         }),
     );
     assert_eq!(instruction, expected);
@@ -175,21 +363,25 @@ fn json_path_square_braces() {
     let instruction = parse(source_code);
     let expected = Instruction::Call(Call {
         peer_part: PeerPk(JsonPath {
-            variable: "u",
-            path: r#"$["peer_id"]"#,
+            variable: "u".into(),
+            path: json_path(r#"$["peer_id"]"#),
+            span: Span::default(),
         }),
-        function_part: ServiceIdWithFuncName(Literal("return"), Literal("")),
+        function_part: ServiceIdWithFuncName(Literal("return".into()), Literal("".into())),
         args: Rc::new(vec![
             JsonPath {
-                variable: "u",
-                path: r#"$["peer_id"].cde[0]["abc"].abc"#,
+                variable: "u".into(),
+                path: json_path(r#"$["peer_id"].cde[0]["abc"].abc"#),
+                span: Span::default(),
             },
             JsonPath {
-                variable: "u",
-                path: r#"$["name"]"#,
+                variable: "u".into(),
+                path: json_path(r#"$["name"]"#),
+                span: Span::default(),
             },
         ]),
-        output: Accumulator("void"),
+        output: Accumulator("void".into()),
+        span: Span::default(),
     });
 
     assert_eq!(instruction, expected);
@@ -205,7 +397,11 @@ fn parse_null() {
         )
         "#;
     let instruction = parse(source_code);
-    let expected = Instruction::Seq(ast::Seq(Box::new(null()), Box::new(null())));
+    let expected = Instruction::Seq(ast::Seq(
+        Box::new(null()),
+        Box::new(null()),
+        Span::default(),
+    ));
     assert_eq!(instruction, expected)
 }
 
@@ -240,7 +436,11 @@ fn parse_fold() {
         )
         "#;
     let instruction = parse(&source_code.as_ref());
-    let expected = fold(ast::IterableValue::Variable("iterable"), "i", null());
+    let expected = fold(
+        ast::IterableValue::Variable("iterable".into(), Span::default()),
+        "i",
+        null(),
+    );
     assert_eq!(instruction, expected);
 }
 
@@ -254,7 +454,11 @@ fn parse_match() {
         )
         "#;
     let instruction = parse(&source_code.as_ref());
-    let expected = match_(Variable("v1"), Variable("v2"), null());
+    let expected = match_(
+        Variable("v1".into(), Span::default()),
+        Variable("v2".into(), Span::default()),
+        null(),
+    );
     assert_eq!(instruction, expected);
 }
 
@@ -268,7 +472,65 @@ fn parse_mismatch() {
         )
         "#;
     let instruction = parse(&source_code.as_ref());
-    let expected = mismatch(Variable("v1"), Variable("v2"), null());
+    let expected = mismatch(
+        Variable("v1".into(), Span::default()),
+        Variable("v2".into(), Span::default()),
+        null(),
+    );
+    assert_eq!(instruction, expected);
+}
+
+#[test]
+fn parse_compare() {
+    use ast::CompareOperator;
+    use ast::MatchableValue::*;
+
+    let source_code = r#"
+        (lte v1.$.a 42
+            (is_empty v2
+                (null)
+            )
+        )
+        "#;
+    let instruction = parse(&source_code.as_ref());
+    let expected = compare(
+        CompareOperator::LessOrEqual,
+        JsonPath {
+            variable: "v1".into(),
+            path: json_path("$.a"),
+            span: Span::default(),
+        },
+        Some(Number(42.into())),
+        compare(
+            CompareOperator::IsEmpty,
+            Variable("v2".into(), Span::default()),
+            None,
+            null(),
+        ),
+    );
+    assert_eq!(instruction, expected);
+}
+
+#[test]
+fn parse_compare_with_wrong_arity() {
+    for source_code in &["(is_empty v1 v2 (null))", "(contains v1 (null))"] {
+        assert!(crate::parse(source_code).is_err(), "{}", source_code);
+    }
+}
+
+#[test]
+fn parse_new() {
+    use ast::CallOutputValue::*;
+
+    let source_code = r#"
+        (new x
+            (new acc[]
+                (null)
+            )
+        )
+        "#;
+    let instruction = parse(&source_code.as_ref());
+    let expected = new(Scalar("x".into()), new(Accumulator("acc".into()), null()));
     assert_eq!(instruction, expected);
 }
 
@@ -284,7 +546,7 @@ fn parse_fold_with_xor_par_seq() {
         let instruction = parse(&source_code.as_ref());
         let instr = binary_instruction(*name);
         let expected = fold(
-            ast::IterableValue::Variable("iterable"),
+            ast::IterableValue::Variable("iterable".into(), Span::default()),
             "i",
             instr(null(), null()),
         );
@@ -313,19 +575,24 @@ fn parse_init_peer_id() {
     let instruction = parse(&source_code.as_ref());
     let expected = seq(
         Instruction::Call(Call {
-            peer_part: PeerPk(Literal(&peer_id)),
+            peer_part: PeerPk(Literal(peer_id.as_str().into())),
             function_part: ServiceIdWithFuncName(
-                Literal("local_service_id"),
-                Literal("local_fn_name"),
+                Literal("local_service_id".into()),
+                Literal("local_fn_name".into()),
             ),
             args: Rc::new(vec![]),
             output: None,
+            span: Span::default(),
         }),
         Instruction::Call(Call {
             peer_part: PeerPk(InitPeerId),
-            function_part: ServiceIdWithFuncName(Literal("service_id"), Literal("fn_name")),
+            function_part: ServiceIdWithFuncName(
+                Literal("service_id".into()),
+                Literal("fn_name".into()),
+            ),
             args: Rc::new(vec![]),
             output: None,
+            span: Span::default(),
         }),
     );
 
@@ -357,29 +624,35 @@ fn seq_par_call() {
     let expected = seq(
         par(
             Instruction::Call(Call {
-                peer_part: PeerPk(Literal(&peer_id)),
+                peer_part: PeerPk(Literal(peer_id.as_str().into())),
                 function_part: ServiceIdWithFuncName(
-                    Literal("local_service_id"),
-                    Literal("local_fn_name"),
+                    Literal("local_service_id".into()),
+                    Literal("local_fn_name".into()),
                 ),
                 args: Rc::new(vec![]),
-                output: Scalar("result_1"),
+                output: Scalar("result_1".into()),
+                span: Span::default(),
             }),
             Instruction::Call(Call {
-                peer_part: PeerPk(Literal(&peer_id)),
-                function_part: ServiceIdWithFuncName(Literal("service_id"), Literal("fn_name")),
+                peer_part: PeerPk(Literal(peer_id.as_str().into())),
+                function_part: ServiceIdWithFuncName(
+                    Literal("service_id".into()),
+                    Literal("fn_name".into()),
+                ),
                 args: Rc::new(vec![]),
-                output: Scalar("g"),
+                output: Scalar("g".into()),
+                span: Span::default(),
             }),
         ),
         Instruction::Call(Call {
-            peer_part: PeerPk(Literal(&peer_id)),
+            peer_part: PeerPk(Literal(peer_id.as_str().into())),
             function_part: ServiceIdWithFuncName(
-                Literal("local_service_id"),
-                Literal("local_fn_name"),
+                Literal("local_service_id".into()),
+                Literal("local_fn_name".into()),
             ),
             args: Rc::new(vec![]),
-            output: Scalar("result_2"),
+            output: Scalar("result_2".into()),
+            span: Span::default(),
         }),
     );
 
@@ -420,51 +693,73 @@ fn seq_with_empty_and_dash() {
         seq(
             seq(
                 Instruction::Call(Call {
-                    peer_part: PeerPk(Literal("set_variables")),
-                    function_part: ServiceIdWithFuncName(Literal(""), Literal("")),
-                    args: Rc::new(vec![Literal("module-bytes")]),
-                    output: Scalar("module-bytes"),
+                    peer_part: PeerPk(Literal("set_variables".into())),
+                    function_part: ServiceIdWithFuncName(Literal("".into()), Literal("".into())),
+                    args: Rc::new(vec![Literal("module-bytes".into())]),
+                    output: Scalar("module-bytes".into()),
+                    span: Span::default(),
                 }),
                 Instruction::Call(Call {
-                    peer_part: PeerPk(Literal("set_variables")),
-                    function_part: ServiceIdWithFuncName(Literal(""), Literal("")),
-                    args: Rc::new(vec![Literal("module_config")]),
-                    output: Scalar("module_config"),
+                    peer_part: PeerPk(Literal("set_variables".into())),
+                    function_part: ServiceIdWithFuncName(Literal("".into()), Literal("".into())),
+                    args: Rc::new(vec![Literal("module_config".into())]),
+                    output: Scalar("module_config".into()),
+                    span: Span::default(),
                 }),
             ),
             Instruction::Call(Call {
-                peer_part: PeerPk(Literal("set_variables")),
-                function_part: ServiceIdWithFuncName(Literal(""), Literal("")),
-                args: Rc::new(vec![Literal("blueprint")]),
-                output: Scalar("blueprint"),
+                peer_part: PeerPk(Literal("set_variables".into())),
+                function_part: ServiceIdWithFuncName(Literal("".into()), Literal("".into())),
+                args: Rc::new(vec![Literal("blueprint".into())]),
+                output: Scalar("blueprint".into()),
+                span: Span::default(),
             }),
         ),
         seq(
             Instruction::Call(Call {
-                peer_part: PeerPk(Literal("A")),
-                function_part: ServiceIdWithFuncName(Literal("add_module"), Literal("")),
-                args: Rc::new(vec![Variable("module-bytes"), Variable("module_config")]),
-                output: Scalar("module"),
+                peer_part: PeerPk(Literal("A".into())),
+                function_part: ServiceIdWithFuncName(
+                    Literal("add_module".into()),
+                    Literal("".into()),
+                ),
+                args: Rc::new(vec![
+                    Variable("module-bytes".into(), Span::default()),
+                    Variable("module_config".into(), Span::default()),
+                ]),
+                output: Scalar("module".into()),
+                span: Span::default(),
             }),
             seq(
                 Instruction::Call(Call {
-                    peer_part: PeerPk(Literal("A")),
-                    function_part: ServiceIdWithFuncName(Literal("add_blueprint"), Literal("")),
-                    args: Rc::new(vec![Variable("blueprint")]),
-                    output: Scalar("blueprint_id"),
+                    peer_part: PeerPk(Literal("A".into())),
+                    function_part: ServiceIdWithFuncName(
+                        Literal("add_blueprint".into()),
+                        Literal("".into()),
+                    ),
+                    args: Rc::new(vec![Variable("blueprint".into(), Span::default())]),
+                    output: Scalar("blueprint_id".into()),
+                    span: Span::default(),
                 }),
                 seq(
                     Instruction::Call(Call {
-                        peer_part: PeerPk(Literal("A")),
-                        function_part: ServiceIdWithFuncName(Literal("create"), Literal("")),
-                        args: Rc::new(vec![Variable("blueprint_id")]),
-                        output: Scalar("service_id"),
+                        peer_part: PeerPk(Literal("A".into())),
+                        function_part: ServiceIdWithFuncName(
+                            Literal("create".into()),
+                            Literal("".into()),
+                        ),
+                        args: Rc::new(vec![Variable("blueprint_id".into(), Span::default())]),
+                        output: Scalar("service_id".into()),
+                        span: Span::default(),
                     }),
                     Instruction::Call(Call {
-                        peer_part: PeerPk(Literal("remote_peer_id")),
-                        function_part: ServiceIdWithFuncName(Literal(""), Literal("")),
-                        args: Rc::new(vec![Variable("service_id")]),
-                        output: Scalar("client_result"),
+                        peer_part: PeerPk(Literal("remote_peer_id".into())),
+                        function_part: ServiceIdWithFuncName(
+                            Literal("".into()),
+                            Literal("".into()),
+                        ),
+                        args: Rc::new(vec![Variable("service_id".into(), Span::default())]),
+                        output: Scalar("client_result".into()),
+                        span: Span::default(),
                     }),
                 ),
             ),
@@ -487,14 +782,176 @@ fn no_output() {
     "#;
     let instruction = parse(&source_code.as_ref());
     let expected = Instruction::Call(Call {
-        peer_part: PeerPk(Variable("peer")),
-        function_part: ServiceIdWithFuncName(Variable("service"), Variable("fname")),
+        peer_part: PeerPk(Variable("peer".into(), Span::default())),
+        function_part: ServiceIdWithFuncName(
+            Variable("service".into(), Span::default()),
+            Variable("fname".into(), Span::default()),
+        ),
         args: Rc::new(vec![]),
         output: None,
+        span: Span::default(),
     });
     assert_eq!(instruction, expected);
 }
 
+#[test]
+fn parse_ap() {
+    use ast::Ap;
+    use ast::CallArgValue::*;
+    use ast::CallOutputValue::*;
+
+    let source_code = r#"
+    (seq
+        (ap value.$.field scalar)
+        (ap "literal" acc[])
+    )
+    "#;
+    let instruction = parse(&source_code.as_ref());
+    let expected = seq(
+        Instruction::Ap(Ap {
+            value: JsonPath {
+                variable: "value".into(),
+                path: json_path("$.field"),
+                span: Span::default(),
+            },
+            output: Scalar("scalar".into()),
+            span: Span::default(),
+        }),
+        Instruction::Ap(Ap {
+            value: Literal("literal".into()),
+            output: Accumulator("acc".into()),
+            span: Span::default(),
+        }),
+    );
+    assert_eq!(instruction, expected);
+
+    let ap = "(ap [1 2] result)";
+    let instruction = crate::parse(ap).expect("parsing failed");
+    assert_eq!(instruction.span(), Span::new(0, ap.len()));
+    assert_eq!(instruction.to_string(), ap);
+}
+
+#[test]
+fn ap_without_output_is_rejected() {
+    assert!(crate::parse(r#"(ap "value")"#).is_err());
+    assert!(crate::parse(r#"(ap value result extra)"#).is_err());
+}
+
+#[test]
+fn parse_fail() {
+    use ast::CallArgValue::*;
+    use ast::Fail;
+
+    let source_code = r#"
+    (xor
+        (fail 42 "error message")
+        (fail error.$.code error.$.message)
+    )
+    "#;
+    let instruction = parse(&source_code.as_ref());
+    let expected = xor(
+        Instruction::Fail(Fail {
+            ret_code: Number(42.into()),
+            message: Literal("error message".into()),
+            span: Span::default(),
+        }),
+        Instruction::Fail(Fail {
+            ret_code: JsonPath {
+                variable: "error".into(),
+                path: json_path("$.code"),
+                span: Span::default(),
+            },
+            message: JsonPath {
+                variable: "error".into(),
+                path: json_path("$.message"),
+                span: Span::default(),
+            },
+            span: Span::default(),
+        }),
+    );
+    assert_eq!(instruction, expected);
+
+    let fail = r#"(fail code "message")"#;
+    let instruction = crate::parse(fail).expect("parsing failed");
+    assert_eq!(instruction.span(), Span::new(0, fail.len()));
+    assert_eq!(instruction.to_string(), fail);
+}
+
+#[test]
+fn fail_with_invalid_arguments_is_rejected() {
+    assert!(crate::parse(r#"(fail "42" "message")"#).is_err());
+    assert!(crate::parse(r#"(fail 42 1)"#).is_err());
+    assert!(crate::parse(r#"(fail 42)"#).is_err());
+}
+
+#[test]
+fn parse_run_parameters() {
+    use ast::Call;
+    use ast::CallArgValue::*;
+    use ast::CallOutputValue::*;
+    use ast::FunctionPart::*;
+    use ast::PeerPart::*;
+
+    let source_code = r#"
+        (call %current_peer_id% (%particle_id% "function") [%timestamp% %ttl% %init_peer_id%] result)
+    "#;
+    let instruction = parse(source_code);
+    let expected = Instruction::Call(Call {
+        peer_part: PeerPk(CurrentPeerId),
+        function_part: ServiceIdWithFuncName(ParticleId, Literal("function".into())),
+        args: Rc::new(vec![Timestamp, Ttl, InitPeerId]),
+        output: Scalar("result".into()),
+        span: Span::default(),
+    });
+    assert_eq!(instruction, expected);
+
+    // numbers can't be used in a call triplet
+    assert!(crate::parse(r#"(call %ttl% ("service" "function") [])"#).is_err());
+}
+
+#[test]
+fn parse_last_error() {
+    use ast::Call;
+    use ast::CallArgValue::*;
+    use ast::CallOutputValue::*;
+    use ast::Fail;
+    use ast::FunctionPart::*;
+    use ast::PeerPart::*;
+
+    let source_code = r#"
+    (seq
+        (call "peer" ("service" "function") [%last_error% %last_error%.$.message])
+        (fail %last_error%.$.error_code %last_error%.$.message)
+    )
+    "#;
+    let instruction = parse(&source_code.as_ref());
+    let expected = seq(
+        Instruction::Call(Call {
+            peer_part: PeerPk(Literal("peer".into())),
+            function_part: ServiceIdWithFuncName(
+                Literal("service".into()),
+                Literal("function".into()),
+            ),
+            args: Rc::new(vec![
+                LastError(Option::None),
+                LastError(Some(json_path("$.message"))),
+            ]),
+            output: None,
+            span: Span::default(),
+        }),
+        Instruction::Fail(Fail {
+            ret_code: LastError(Some(json_path("$.error_code"))),
+            message: LastError(Some(json_path("$.message"))),
+            span: Span::default(),
+        }),
+    );
+    assert_eq!(instruction, expected);
+
+    let call = r#"(call "peer" ("service" "function") [%last_error%.$.instruction] result)"#;
+    let instruction = crate::parse(call).expect("parsing failed");
+    assert_eq!(instruction.to_string(), call);
+}
+
 #[test]
 fn fold_json_path() {
     use ast::Fold;
@@ -508,11 +965,13 @@ fn fold_json_path() {
     let instruction = parse(&source_code.as_ref());
     let expected = Instruction::Fold(Fold {
         iterable: JsonPath {
-            variable: "members",
-            path: "$.[\"users\"]",
+            variable: "members".into(),
+            path: json_path("$.[\"users\"]"),
+            span: Span::default(),
         },
-        iterator: "m",
+        iterator: "m".into(),
         instruction: Rc::new(null()),
+        span: Span::default(),
     });
     assert_eq!(instruction, expected);
 }
@@ -530,27 +989,493 @@ fn comments() {
     let instruction = parse(&source_code.as_ref());
     let expected = Instruction::Fold(Fold {
         iterable: JsonPath {
-            variable: "members",
-            path: "$.[\"users\"]",
+            variable: "members".into(),
+            path: json_path("$.[\"users\"]"),
+            span: Span::default(),
         },
-        iterator: "m",
+        iterator: "m".into(),
         instruction: Rc::new(null()),
+        span: Span::default(),
     });
     assert_eq!(instruction, expected);
 }
 
+#[test]
+fn parse_error_has_span() {
+    use crate::ParserErrorKind::*;
+
+    let source_code = "(seq (null) null)";
+    let error = crate::parse(source_code).expect_err("parsing should fail");
+
+    assert_eq!(error.errors.len(), 1);
+    let error = &error.errors[0];
+    assert_eq!(error.span, Span::new(12, 16));
+    match &error.kind {
+        UnrecognizedToken { token, expected } => {
+            assert_eq!(token, "null");
+            assert!(expected.contains(&String::from(r#""(""#)));
+        }
+        kind => panic!("unexpected error kind: {:?}", kind),
+    }
+}
+
+#[test]
+fn parse_error_from_lexer() {
+    use crate::LexerError;
+    use crate::ParserErrorKind::*;
+
+    let source_code = r#"(call peer_id! ("service_id" "function_name") [])"#;
+    let error = crate::parse(source_code).expect_err("parsing should fail");
+
+    let lexer_error = error
+        .errors
+        .iter()
+        .find(|error| matches!(error.kind, LexerError(_)))
+        .expect("lexer error should be reported");
+    assert_eq!(
+        lexer_error.kind,
+        LexerError(LexerError::IsNotAlphanumeric(13, 13))
+    );
+}
+
+#[test]
+fn parse_invalid_json_path() {
+    use crate::LexerError;
+    use crate::ParserErrorKind::*;
+
+    let source_code = r#"(seq (call peer.$.[ "f" []) (call peer.$.a.[0] "f" []))"#;
+    let error = crate::parse(source_code).expect_err("parsing should fail");
+
+    let lexer_errors = error
+        .errors
+        .iter()
+        .filter(|error| matches!(error.kind, LexerError(_)))
+        .collect::<Vec<_>>();
+    assert_eq!(lexer_errors.len(), 1);
+    assert_eq!(
+        lexer_errors[0].kind,
+        LexerError(LexerError::InvalidJsonPathSyntax(16, 19))
+    );
+    assert_eq!(lexer_errors[0].span, Span::new(16, 19));
+}
+
+#[test]
+fn parse_error_rendering() {
+    let source_code = "(seq (null) null)";
+    let error = crate::parse(source_code).expect_err("parsing should fail");
+
+    let report = error.render(source_code);
+    assert!(report.contains("expected \"(\""));
+    assert!(!report.contains('\u{1b}'));
+
+    let colored_report = error.render_colored(source_code);
+    assert!(colored_report.contains('\u{1b}'));
+}
+
+#[test]
+fn instruction_spans() {
+    let source_code = r#"(seq
+    (call peer ("service" "function") [] result)
+    (fold result i (next i))
+)"#;
+    let instruction = *crate::parse(source_code).expect("parsing failed");
+    assert_eq!(instruction.span(), Span::new(0, source_code.len()));
+
+    let (call, fold) = match &instruction {
+        Instruction::Seq(ast::Seq(call, fold, _)) => (call, fold),
+        instruction => panic!("expected seq, got {:?}", instruction),
+    };
+    assert_eq!(call.span(), Span::new(9, 53));
+    assert_eq!(call.span().line_column(source_code), (2, 5));
+    assert_eq!(fold.span(), Span::new(58, 82));
+    assert_eq!(fold.span().line_column(source_code), (3, 5));
+
+    match fold.as_ref() {
+        Instruction::Fold(fold) => {
+            assert_eq!(fold.instruction.span(), Span::new(73, 81));
+            assert_eq!(fold.instruction.span().line_column(source_code), (3, 20));
+        }
+        instruction => panic!("expected fold, got {:?}", instruction),
+    }
+}
+
+#[test]
+fn value_spans() {
+    let source_code = r#"(fold items.$.all! i (call i ("service" "function") [arg]))"#;
+    let instruction = *crate::parse(source_code).expect("parsing failed");
+
+    let fold = match instruction {
+        Instruction::Fold(fold) => fold,
+        instruction => panic!("expected fold, got {:?}", instruction),
+    };
+    assert_eq!(fold.iterable.span(), Span::new(6, 18));
+
+    match fold.instruction.as_ref() {
+        Instruction::Call(call) => {
+            let peer_pk = match &call.peer_part {
+                ast::PeerPart::PeerPk(peer_pk) => peer_pk,
+                peer_part => panic!("expected a peer id, got {:?}", peer_part),
+            };
+            assert_eq!(peer_pk.span(), Some(Span::new(27, 28)));
+            assert_eq!(call.args[0].span(), Some(Span::new(53, 56)));
+            assert_eq!(
+                call.args[0].span().unwrap().line_column(source_code),
+                (1, 54)
+            );
+        }
+        instruction => panic!("expected call, got {:?}", instruction),
+    }
+}
+
+#[test]
+fn parse_nary_instructions() {
+    let source_code = r#"
+        (seq
+            (null)
+            (par (null) (null) (null))
+            (xor (null) (seq (null) (null)) (null) (null))
+        )
+        "#;
+    let instruction = parse(source_code);
+
+    let expected = seq(
+        null(),
+        seq(
+            par(null(), par(null(), null())),
+            xor(null(), xor(seqnn(), xor(null(), null()))),
+        ),
+    );
+    assert_eq!(instruction, expected);
+}
+
+#[test]
+fn nary_instruction_spans() {
+    let source_code = "(seq (null) (null) (null))";
+    let instruction = *crate::parse(source_code).expect("parsing failed");
+    assert_eq!(instruction.span(), Span::new(0, 26));
+
+    match &instruction {
+        Instruction::Seq(ast::Seq(first, nested, _)) => {
+            assert_eq!(first.span(), Span::new(5, 11));
+            // a desugared instruction spans from its first child to the last one
+            assert_eq!(nested.span(), Span::new(12, 25));
+        }
+        instruction => panic!("expected seq, got {:?}", instruction),
+    }
+}
+
+#[test]
+fn unary_seq_is_rejected() {
+    let source_code = "(seq (null))";
+
+    assert!(crate::parse(source_code).is_err());
+}
+
+#[test]
+fn multibyte_error_rendering() {
+    use crate::LexerError;
+    use crate::ParserErrorKind::*;
+
+    let source_code = "(seq\n    (call \"пир\" (\"сервис\" \"функция\") [] результат)\n    (call пир! (\"服务\" \"函数\") [результат.$.名]))";
+    let error = crate::parse(source_code).expect_err("parsing should fail");
+
+    let bang_position = source_code.find('!').unwrap();
+    let lexer_error = error
+        .errors
+        .iter()
+        .find(|error| matches!(error.kind, LexerError(_)))
+        .expect("lexer error should be reported");
+    assert_eq!(
+        lexer_error.kind,
+        LexerError(LexerError::IsNotAlphanumeric(bang_position, bang_position))
+    );
+    assert_eq!(lexer_error.span.line_column(source_code), (3, 14));
+
+    // spans are on char boundaries, so rendering doesn't panic
+    let report = error.render(source_code);
+    assert!(report.contains("(call пир! (\"服务\" \"函数\") [результат.$.名]))"));
+    assert!(report.contains(":3:14"));
+}
+
+#[test]
+fn line_column_conversion() {
+    use crate::line_column_to_offset;
+    use crate::offset_to_line_column;
+
+    let source_code = "(seq\n  (null) ; комментарий\n\t(call 对等 (\"s\" \"f\") []))";
+
+    let call_offset = source_code.find("(call").unwrap();
+    assert_eq!(offset_to_line_column(source_code, call_offset), (3, 2));
+    assert_eq!(line_column_to_offset(source_code, 3, 2), Some(call_offset));
+
+    let peer_offset = source_code.find("对等").unwrap();
+    let after_peer = peer_offset + "对等".len();
+    assert_eq!(offset_to_line_column(source_code, after_peer), (3, 10));
+    assert_eq!(line_column_to_offset(source_code, 3, 10), Some(after_peer));
+    // an offset inside a multibyte char points to this char
+    assert_eq!(offset_to_line_column(source_code, peer_offset + 1), (3, 8));
+
+    let span = Span::new(peer_offset, after_peer);
+    assert_eq!(span.line_column(source_code), (3, 8));
+    assert_eq!(span.end_line_column(source_code), (3, 10));
+
+    assert_eq!(offset_to_line_column(source_code, 0), (1, 1));
+    assert_eq!(offset_to_line_column(source_code, 1000), (3, 25));
+    assert_eq!(line_column_to_offset(source_code, 1, 5), Some(4));
+    assert_eq!(line_column_to_offset(source_code, 1, 6), None);
+    assert_eq!(line_column_to_offset(source_code, 0, 1), None);
+    assert_eq!(line_column_to_offset(source_code, 4, 1), None);
+}
+
+#[test]
+fn parse_escaped_literals() {
+    use ast::Call;
+    use ast::CallArgValue::*;
+    use ast::CallOutputValue::*;
+    use ast::FunctionPart::*;
+    use ast::PeerPart::*;
+
+    let source_code = r#"
+        (call "peer" ("service" "function") ["quote \" backslash \\" "new\nline\ttab" "\u{1F600}"] result)
+        "#;
+    let instruction = parse(source_code);
+    let expected = Instruction::Call(Call {
+        peer_part: PeerPk(Literal("peer".into())),
+        function_part: ServiceIdWithFuncName(Literal("service".into()), Literal("function".into())),
+        args: Rc::new(vec![
+            Literal(r#"quote " backslash \"#.into()),
+            Literal("new\nline\ttab".into()),
+            Literal("😀".into()),
+        ]),
+        output: Scalar("result".into()),
+        span: Span::default(),
+    });
+    assert_eq!(instruction, expected);
+}
+
+#[test]
+fn parse_typed_literals() {
+    use ast::Call;
+    use ast::CallArgValue::*;
+    use ast::CallOutputValue::*;
+    use ast::FunctionPart::*;
+    use ast::PeerPart::*;
+    use serde_json::json;
+
+    let source_code = r#"
+        (call "peer" ("service" "function") [1 -2.5 true null [] ["a" [false] {"b": 1}] {"c": [null]}] result)
+        "#;
+    let instruction = parse(source_code);
+    let expected = Instruction::Call(Call {
+        peer_part: PeerPk(Literal("peer".into())),
+        function_part: ServiceIdWithFuncName(Literal("service".into()), Literal("function".into())),
+        args: Rc::new(vec![
+            Number(1.into()),
+            Number(serde_json::Number::from_f64(-2.5).unwrap()),
+            Boolean(true),
+            Null,
+            Json(json!([])),
+            Json(json!(["a", [false], {"b": 1}])),
+            Json(json!({"c": [null]})),
+        ]),
+        output: Scalar("result".into()),
+        span: Span::default(),
+    });
+    assert_eq!(instruction, expected);
+}
+
+#[test]
+fn parse_match_with_typed_literals() {
+    use ast::MatchableValue::*;
+    use serde_json::json;
+
+    let source_code = r#"
+        (match 42 [1 "a"]
+            (mismatch null {"a": true}
+                (null)
+            )
+        )
+        "#;
+    let instruction = parse(source_code);
+    let expected = match_(
+        Number(42.into()),
+        Json(json!([1, "a"])),
+        mismatch(Null, Json(json!({"a": true})), null()),
+    );
+    assert_eq!(instruction, expected);
+}
+
+#[test]
+fn typed_literals_in_triplet_are_rejected() {
+    let source_code = r#"(call 42 ("service" "function") [])"#;
+
+    assert!(crate::parse(source_code).is_err());
+}
+
+#[test]
+fn parsed_script_owns_ast() {
+    let source_code = r#"(seq (call peer.$.a ("s" "f") [] result) (null))"#;
+    let expected = crate::parse(source_code).expect("parsing failed");
+
+    let owned_source_code = String::from(source_code);
+    let parsed_scripts =
+        [crate::ParsedScript::parse(owned_source_code.as_str()).expect("parsing failed")];
+    drop(owned_source_code);
+
+    assert_eq!(parsed_scripts[0].instruction(), expected.as_ref());
+    assert_eq!(parsed_scripts[0].script(), source_code);
+}
+
+#[test]
+fn ast_json_round_trip() {
+    let source_code = r#"
+        (seq
+            (call %init_peer_id% ("service" "function") ["a\n\"b\"" 1 -2.5 true null [1 "c"] {"d": []}] result[])
+            (xor
+                (fold result.$.[0]! i
+                    (par
+                        (match i.$.name "name"
+                            (call (i "service") function [i.$.value result] value)
+                        )
+                        (next i)
+                    )
+                )
+                (mismatch result 42
+                    (null)
+                )
+            )
+        )
+        "#;
+    let instruction = crate::parse(source_code).expect("parsing failed");
+
+    let json_ast = serde_json::to_string(&instruction).expect("serialization failed");
+    let parsed_script = crate::ParsedScript::from_json(json_ast.as_str()).expect("invalid ast");
+
+    assert_eq!(parsed_script.instruction(), instruction.as_ref());
+    assert_eq!(
+        serde_json::to_string(parsed_script.instruction()).expect("serialization failed"),
+        json_ast
+    );
+}
+
+#[test]
+fn escaped_json_path_round_trip() {
+    let source_code = r#"(call peers.$["peer"] ("service" "function") [result.$["a"].b])"#;
+    let instruction = crate::parse(source_code).expect("parsing failed");
+
+    let json_ast = serde_json::to_string(&instruction).expect("serialization failed");
+    let parsed_script = crate::ParsedScript::from_json(json_ast.as_str()).expect("invalid ast");
+
+    assert_eq!(parsed_script.instruction(), instruction.as_ref());
+}
+
+#[test]
+fn definite_json_paths() {
+    assert!(json_path("$.a[0].b").is_definite());
+    assert!(json_path(r#"$["a.*"]['b,c']"#).is_definite());
+    assert!(!json_path("$.a[*]").is_definite());
+    assert!(!json_path("$.[*]").is_definite());
+    assert!(!json_path("$..a").is_definite());
+    assert!(!json_path("$.a[?(@.b == 1)]").is_definite());
+    assert!(!json_path("$.a[0,1]").is_definite());
+    assert!(!json_path("$.a[1:]").is_definite());
+}
+
+#[test]
+fn invalid_json_ast() {
+    let json_ast = r#"{"Fold":{"iterable":{"JsonPath":{"variable":"a","path":"$.["}},"iterator":"i","instruction":{"Null":{"left":0,"right":0}},"span":{"left":0,"right":0}}}"#;
+
+    assert!(crate::ParsedScript::from_json(json_ast).is_err());
+}
+
 // Test DSL
 
+#[test]
+fn parse_define_and_invoke() {
+    use ast::CallArgValue::*;
+    use ast::CallOutputValue;
+    use ast::Define;
+    use ast::Invoke;
+
+    let source_code = r#"
+        (define first [a b] result (ap a result))
+        (define second [] (null))
+        (seq
+            (invoke first ["value" value.$.field] output)
+            (invoke second [])
+        )
+        "#;
+    let instruction = parse(source_code);
+    let expected = Instruction::Define(Define {
+        name: "first".into(),
+        parameters: vec!["a".into(), "b".into()],
+        output: Some("result".into()),
+        body: Box::new(Instruction::Ap(ast::Ap {
+            value: Variable("a".into(), Span::default()),
+            output: CallOutputValue::Scalar("result".into()),
+            span: Span::default(),
+        })),
+        instruction: Box::new(Instruction::Define(Define {
+            name: "second".into(),
+            parameters: vec![],
+            output: None,
+            body: Box::new(null()),
+            instruction: Box::new(seq(
+                Instruction::Invoke(Invoke {
+                    name: "first".into(),
+                    args: Rc::new(vec![
+                        Literal("value".into()),
+                        JsonPath {
+                            variable: "value".into(),
+                            path: json_path("$.field"),
+                            span: Span::default(),
+                        },
+                    ]),
+                    output: CallOutputValue::Scalar("output".into()),
+                    span: Span::default(),
+                }),
+                Instruction::Invoke(Invoke {
+                    name: "second".into(),
+                    args: Rc::new(vec![]),
+                    output: CallOutputValue::None,
+                    span: Span::default(),
+                }),
+            )),
+            span: Span::default(),
+        })),
+        span: Span::default(),
+    });
+    assert_eq!(instruction, expected);
+
+    let script = "(define p [a] a (null)) (invoke p [1] acc[])";
+    let instruction = crate::parse(script).expect("parsing failed");
+    assert_eq!(
+        instruction.span(),
+        Span::new(0, script.find(" (invoke").unwrap())
+    );
+    assert_eq!(instruction.to_string(), script);
+}
+
+#[test]
+fn define_inside_instruction_is_rejected() {
+    assert!(crate::parse("(seq (define p [] (null)) (null))").is_err());
+    assert!(crate::parse("(define p [] (null))").is_err());
+}
+
+fn json_path(path: &str) -> ast::CompiledJsonPath<'_> {
+    ast::CompiledJsonPath::compile(path).expect("json path should be valid")
+}
+
 fn seq<'a>(l: Instruction<'a>, r: Instruction<'a>) -> Instruction<'a> {
-    Instruction::Seq(ast::Seq(Box::new(l), Box::new(r)))
+    Instruction::Seq(ast::Seq(Box::new(l), Box::new(r), Span::default()))
 }
 
 fn par<'a>(l: Instruction<'a>, r: Instruction<'a>) -> Instruction<'a> {
-    Instruction::Par(ast::Par(Box::new(l), Box::new(r)))
+    Instruction::Par(ast::Par(Box::new(l), Box::new(r), Span::default()))
 }
 
 fn xor<'a>(l: Instruction<'a>, r: Instruction<'a>) -> Instruction<'a> {
-    Instruction::Xor(ast::Xor(Box::new(l), Box::new(r)))
+    Instruction::Xor(ast::Xor(Box::new(l), Box::new(r), Span::default()))
 }
 
 fn seqnn() -> Instruction<'static> {
@@ -558,7 +1483,7 @@ fn seqnn() -> Instruction<'static> {
 }
 
 fn null() -> Instruction<'static> {
-    Instruction::Null(ast::Null)
+    Instruction::Null(ast::Null(Span::default()))
 }
 
 fn fold<'a>(
@@ -568,8 +1493,9 @@ fn fold<'a>(
 ) -> Instruction<'a> {
     Instruction::Fold(ast::Fold {
         iterable,
-        iterator,
+        iterator: iterator.into(),
         instruction: std::rc::Rc::new(instruction),
+        span: Span::default(),
     })
 }
 
@@ -582,6 +1508,30 @@ fn match_<'a>(
         left_value,
         right_value,
         instruction: Box::new(instruction),
+        span: Span::default(),
+    })
+}
+
+fn compare<'a>(
+    operator: ast::CompareOperator,
+    left_value: ast::MatchableValue<'a>,
+    right_value: Option<ast::MatchableValue<'a>>,
+    instruction: Instruction<'a>,
+) -> Instruction<'a> {
+    Instruction::Compare(ast::Compare {
+        operator,
+        left_value,
+        right_value,
+        instruction: Box::new(instruction),
+        span: Span::default(),
+    })
+}
+
+fn new<'a>(variable: ast::CallOutputValue<'a>, instruction: Instruction<'a>) -> Instruction<'a> {
+    Instruction::New(ast::New {
+        variable,
+        instruction: Box::new(instruction),
+        span: Span::default(),
     })
 }
 
@@ -594,6 +1544,7 @@ fn mismatch<'a>(
         left_value,
         right_value,
         instruction: Box::new(instruction),
+        span: Span::default(),
     })
 }
 
@@ -0,0 +1,543 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::ast;
+use super::ast::*;
+use super::span::Span;
+
+use codespan_reporting::diagnostic::{Diagnostic, Label};
+use codespan_reporting::files::SimpleFiles;
+use codespan_reporting::term;
+use codespan_reporting::term::termcolor::Buffer;
+use thiserror::Error as ThisError;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+/// Diagnostics found by the static validation of an AIR script.
+#[derive(ThisError, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Mistakes leading to an execution failure whenever the corresponding instruction is executed.
+    pub errors: Vec<SemanticDiagnostic>,
+    /// Suspicious places which could fail or hang depending on the execution flow.
+    pub warnings: Vec<SemanticDiagnostic>,
+}
+
+/// A semantic mistake together with the instruction where it was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticDiagnostic {
+    pub span: Span,
+    pub kind: SemanticDiagnosticKind,
+}
+
+#[derive(ThisError, Debug, Clone, PartialEq, Eq)]
+pub enum SemanticDiagnosticKind {
+    #[error("next over '{0}' is used outside of a fold with such iterator")]
+    NextOutsideFold(String),
+
+    #[error(
+        "variable '{0}' is already defined, only variables inside a fold or new could be redefined"
+    )]
+    MultipleDefinitions(String),
+
+    #[error("fold iterator '{0}' shadows a variable with the same name")]
+    IteratorShadowing(String),
+
+    #[error("variable '{0}' isn't produced by any call or fold")]
+    UndefinedVariable(String),
+
+    #[error("procedure '{0}' isn't defined")]
+    UndefinedProcedure(String),
+
+    #[error("procedure '{0}' is already defined")]
+    MultipleProcedureDefinitions(String),
+
+    #[error("procedure '{0}' takes {1} arguments, but {2} are supplied")]
+    ArgumentsCountMismatch(String, usize, usize),
+
+    #[error("procedure '{0}' doesn't return any value to the output")]
+    ProcedureWithoutOutput(String),
+
+    #[error(
+        "results of a call on several peers could be saved only to an accumulator, not to '{0}'"
+    )]
+    ScalarFanOutOutput(String),
+
+    /// Parser never produces such instructions for valid scripts, but they could come
+    /// with a deserialized AST.
+    #[error("instruction is malformed and can't be executed")]
+    MalformedInstruction,
+}
+
+/// Walks the AST and collects mistakes that could be found before execution.
+pub fn validate(instruction: &Instruction<'_>) -> ValidationReport {
+    let mut produced = HashSet::new();
+    collect_produced(instruction, &mut produced);
+
+    let mut validator = Validator {
+        produced,
+        fold_iterators: vec![],
+        new_variables: vec![],
+        procedures: HashMap::new(),
+        report: ValidationReport::default(),
+    };
+    validator.validate(instruction, false);
+
+    validator.report
+}
+
+/// A scalar defined outside of folds, such variables can't be defined twice.
+struct Definition<'i> {
+    name: &'i str,
+    span: Span,
+    /// True, if the defining call is executed whenever the enclosing instruction is executed.
+    is_certain: bool,
+}
+
+/// Parameters and output of a procedure, which its invocations should conform to.
+struct Signature {
+    parameters_count: usize,
+    has_output: bool,
+}
+
+struct Validator<'i> {
+    /// Names of all scalars and accumulators which are set by some call.
+    produced: HashSet<&'i str>,
+    /// Iterators of folds enclosing the currently validated instruction.
+    fold_iterators: Vec<&'i str>,
+    /// Variables declared by new instructions enclosing the currently validated instruction.
+    new_variables: Vec<&'i str>,
+    /// Signatures of procedures defined before the currently validated instruction by their names.
+    procedures: HashMap<&'i str, Signature>,
+    report: ValidationReport,
+}
+
+impl<'i> Validator<'i> {
+    /// Validates an instruction and returns scalars it defines outside of folds,
+    /// `is_conditional` is true if the instruction isn't always executed with its parent.
+    fn validate(
+        &mut self,
+        instruction: &'i Instruction<'_>,
+        is_conditional: bool,
+    ) -> Vec<Definition<'i>> {
+        use Instruction::*;
+
+        match instruction {
+            Call(call) => self.validate_call(call, is_conditional),
+            Ap(ap) => {
+                self.check_call_arg(&ap.value);
+                self.define_output(&ap.output, ap.span, is_conditional)
+            }
+            Seq(ast::Seq(left, right, _)) | Par(ast::Par(left, right, _)) => {
+                let mut definitions = self.validate(left, is_conditional);
+                let right_definitions = self.validate(right, is_conditional);
+                self.check_redefinitions(&definitions, &right_definitions);
+
+                definitions.extend(right_definitions);
+                definitions
+            }
+            Xor(ast::Xor(left, right, _)) => {
+                // the right branch is executed only if the left one failed,
+                // so the same variable could be defined in both of them
+                let mut definitions = self.validate(left, true);
+                definitions.extend(self.validate(right, true));
+                definitions
+            }
+            Match(match_) => {
+                self.check_matchable(&match_.left_value);
+                self.check_matchable(&match_.right_value);
+                self.validate(&match_.instruction, true)
+            }
+            MisMatch(mismatch) => {
+                self.check_matchable(&mismatch.left_value);
+                self.check_matchable(&mismatch.right_value);
+                self.validate(&mismatch.instruction, true)
+            }
+            Compare(compare) => {
+                self.check_matchable(&compare.left_value);
+                if let Some(right_value) = &compare.right_value {
+                    self.check_matchable(right_value);
+                }
+                self.validate(&compare.instruction, true)
+            }
+            Fold(fold) => {
+                self.validate_fold(fold, is_conditional);
+                // variables defined inside a fold are removed after it
+                vec![]
+            }
+            New(new) => {
+                if let CallOutputValue::None = new.variable {
+                    self.error(new.span, SemanticDiagnosticKind::MalformedInstruction);
+                }
+
+                let variable: &str = match &new.variable {
+                    CallOutputValue::Scalar(name) | CallOutputValue::Accumulator(name) => name,
+                    CallOutputValue::None => "",
+                };
+
+                // only the declared variable is scoped, others are defined as usual
+                self.new_variables.push(variable);
+                let definitions = self.validate(&new.instruction, is_conditional);
+                self.new_variables.pop();
+                definitions
+            }
+            Next(ast::Next(iterator, span)) => {
+                if !self.fold_iterators.contains(&iterator.as_ref()) {
+                    let kind = SemanticDiagnosticKind::NextOutsideFold(iterator.to_string());
+                    self.error(*span, kind);
+                }
+                vec![]
+            }
+            Fail(fail) => {
+                self.check_call_arg(&fail.ret_code);
+                self.check_call_arg(&fail.message);
+                vec![]
+            }
+            Define(define) => {
+                self.validate_define(define);
+                self.validate(&define.instruction, is_conditional)
+            }
+            Invoke(invoke) => self.validate_invoke(invoke, is_conditional),
+            Null(_) => vec![],
+            Error => {
+                self.error(
+                    Span::default(),
+                    SemanticDiagnosticKind::MalformedInstruction,
+                );
+                vec![]
+            }
+        }
+    }
+
+    fn validate_call(
+        &mut self,
+        call: &'i ast::Call<'_>,
+        is_conditional: bool,
+    ) -> Vec<Definition<'i>> {
+        let (peer_pk, peer_service_id) = match &call.peer_part {
+            PeerPart::PeerPk(peer_pk) | PeerPart::PeerPks(peer_pk) => (peer_pk, None),
+            PeerPart::PeerPkWithServiceId(peer_pk, service_id)
+            | PeerPart::PeerPksWithServiceId(peer_pk, service_id) => (peer_pk, Some(service_id)),
+        };
+        let triplet_values = match (peer_service_id, &call.function_part) {
+            (None, FunctionPart::FuncName(func_name)) => vec![peer_pk, func_name],
+            (_, FunctionPart::ServiceIdWithFuncName(service_id, func_name))
+            | (Some(service_id), FunctionPart::FuncName(func_name)) => {
+                vec![peer_pk, service_id, func_name]
+            }
+        };
+
+        for value in triplet_values.into_iter().chain(call.args.iter()) {
+            self.check_call_arg(value);
+        }
+
+        if let (true, CallOutputValue::Scalar(name)) = (call.peer_part.is_fan_out(), &call.output) {
+            let kind = SemanticDiagnosticKind::ScalarFanOutOutput(name.to_string());
+            self.error(call.span, kind);
+        }
+
+        self.define_output(&call.output, call.span, is_conditional)
+    }
+
+    /// Returns a definition of the scalar set by a call or an ap with such output.
+    fn define_output(
+        &mut self,
+        output: &'i CallOutputValue<'_>,
+        span: Span,
+        is_conditional: bool,
+    ) -> Vec<Definition<'i>> {
+        let name: &str = match output {
+            CallOutputValue::Scalar(name) => name,
+            _ => return vec![],
+        };
+
+        if self.fold_iterators.contains(&name) {
+            self.error(
+                span,
+                SemanticDiagnosticKind::IteratorShadowing(name.to_string()),
+            );
+        }
+
+        // shadowing is allowed inside folds and for variables declared by enclosing news
+        if !self.fold_iterators.is_empty() || self.new_variables.contains(&name) {
+            return vec![];
+        }
+
+        let definition = Definition {
+            name,
+            span,
+            is_certain: !is_conditional,
+        };
+        vec![definition]
+    }
+
+    fn validate_fold(&mut self, fold: &'i ast::Fold<'_>, is_conditional: bool) {
+        match &fold.iterable {
+            IterableValue::Variable(name, span)
+            | IterableValue::JsonPath {
+                variable: name,
+                span,
+                ..
+            } => self.check_variable(name, *span),
+        }
+
+        let iterator_kind = || SemanticDiagnosticKind::IteratorShadowing(fold.iterator.to_string());
+        let iterator: &str = &fold.iterator;
+        if self.fold_iterators.contains(&iterator) {
+            self.error(fold.span, iterator_kind());
+        } else if self.produced.contains(iterator) {
+            self.warning(fold.span, iterator_kind());
+        }
+
+        self.fold_iterators.push(iterator);
+        self.validate(&fold.instruction, is_conditional);
+        self.fold_iterators.pop();
+    }
+
+    /// A procedure body sees only its parameters, variables defined inside it and procedures
+    /// defined before it, so it's validated as a separate script and procedures can't be recursive.
+    fn validate_define(&mut self, define: &'i ast::Define<'_>) {
+        let mut produced = define.parameters.iter().map(|name| name.as_ref()).collect();
+        collect_produced(&define.body, &mut produced);
+
+        let outer_produced = std::mem::replace(&mut self.produced, produced);
+        let outer_fold_iterators = std::mem::take(&mut self.fold_iterators);
+        let outer_new_variables = std::mem::take(&mut self.new_variables);
+
+        self.validate(&define.body, false);
+        if let Some(output) = &define.output {
+            self.check_variable(output, define.span);
+        }
+
+        self.produced = outer_produced;
+        self.fold_iterators = outer_fold_iterators;
+        self.new_variables = outer_new_variables;
+
+        let signature = Signature {
+            parameters_count: define.parameters.len(),
+            has_output: define.output.is_some(),
+        };
+        if self.procedures.insert(&define.name, signature).is_some() {
+            let kind =
+                SemanticDiagnosticKind::MultipleProcedureDefinitions(define.name.to_string());
+            self.error(define.span, kind);
+        }
+    }
+
+    fn validate_invoke(
+        &mut self,
+        invoke: &'i ast::Invoke<'_>,
+        is_conditional: bool,
+    ) -> Vec<Definition<'i>> {
+        for arg in invoke.args.iter() {
+            self.check_call_arg(arg);
+        }
+
+        match self.procedures.get(invoke.name.as_ref()) {
+            Some(signature) => {
+                let parameters_count = signature.parameters_count;
+                let has_output = signature.has_output;
+
+                if parameters_count != invoke.args.len() {
+                    let kind = SemanticDiagnosticKind::ArgumentsCountMismatch(
+                        invoke.name.to_string(),
+                        parameters_count,
+                        invoke.args.len(),
+                    );
+                    self.error(invoke.span, kind);
+                }
+                if !has_output && invoke.output != CallOutputValue::None {
+                    let kind =
+                        SemanticDiagnosticKind::ProcedureWithoutOutput(invoke.name.to_string());
+                    self.error(invoke.span, kind);
+                }
+            }
+            None => {
+                let kind = SemanticDiagnosticKind::UndefinedProcedure(invoke.name.to_string());
+                self.error(invoke.span, kind);
+            }
+        }
+
+        self.define_output(&invoke.output, invoke.span, is_conditional)
+    }
+
+    fn check_redefinitions(
+        &mut self,
+        definitions: &[Definition<'i>],
+        new_definitions: &[Definition<'i>],
+    ) {
+        for new_definition in new_definitions {
+            let previous = definitions
+                .iter()
+                .find(|definition| definition.name == new_definition.name);
+            let previous = match previous {
+                Some(previous) => previous,
+                None => continue,
+            };
+
+            let kind = SemanticDiagnosticKind::MultipleDefinitions(new_definition.name.to_string());
+            if previous.is_certain && new_definition.is_certain {
+                self.error(new_definition.span, kind);
+            } else {
+                self.warning(new_definition.span, kind);
+            }
+        }
+    }
+
+    fn check_call_arg(&mut self, value: &CallArgValue<'_>) {
+        match value {
+            CallArgValue::Variable(name, span)
+            | CallArgValue::JsonPath {
+                variable: name,
+                span,
+                ..
+            } => self.check_variable(name, *span),
+            _ => {}
+        }
+    }
+
+    fn check_matchable(&mut self, value: &MatchableValue<'_>) {
+        match value {
+            MatchableValue::Variable(name, span)
+            | MatchableValue::JsonPath {
+                variable: name,
+                span,
+                ..
+            } => self.check_variable(name, *span),
+            _ => {}
+        }
+    }
+
+    fn check_variable(&mut self, name: &str, span: Span) {
+        if !self.produced.contains(name) && !self.fold_iterators.contains(&name) {
+            self.warning(
+                span,
+                SemanticDiagnosticKind::UndefinedVariable(name.to_string()),
+            );
+        }
+    }
+
+    fn error(&mut self, span: Span, kind: SemanticDiagnosticKind) {
+        self.report.errors.push(SemanticDiagnostic { span, kind });
+    }
+
+    fn warning(&mut self, span: Span, kind: SemanticDiagnosticKind) {
+        self.report.warnings.push(SemanticDiagnostic { span, kind });
+    }
+}
+
+/// Collects names of all variables set by calls and aps of the instruction.
+fn collect_produced<'i>(instruction: &'i Instruction<'_>, produced: &mut HashSet<&'i str>) {
+    use Instruction::*;
+
+    match instruction {
+        Call(ast::Call { output, .. })
+        | Ap(ast::Ap { output, .. })
+        | Invoke(ast::Invoke { output, .. }) => match output {
+            CallOutputValue::Scalar(name) | CallOutputValue::Accumulator(name) => {
+                produced.insert(name.as_ref());
+            }
+            CallOutputValue::None => {}
+        },
+        Seq(ast::Seq(left, right, _))
+        | Par(ast::Par(left, right, _))
+        | Xor(ast::Xor(left, right, _)) => {
+            collect_produced(left, produced);
+            collect_produced(right, produced);
+        }
+        Match(match_) => collect_produced(&match_.instruction, produced),
+        MisMatch(mismatch) => collect_produced(&mismatch.instruction, produced),
+        Compare(compare) => collect_produced(&compare.instruction, produced),
+        Fold(fold) => collect_produced(&fold.instruction, produced),
+        New(new) => collect_produced(&new.instruction, produced),
+        // variables of a procedure body are visible only inside it
+        Define(define) => collect_produced(&define.instruction, produced),
+        Next(_) | Null(_) | Fail(_) | Error => {}
+    }
+}
+
+impl ValidationReport {
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Renders errors and warnings as a plain text report with the corresponding fragments of `air_script`.
+    pub fn render(&self, air_script: &str) -> String {
+        let mut buffer = Buffer::no_color();
+        self.emit(air_script, &mut buffer);
+
+        String::from_utf8_lossy(buffer.as_slice()).to_string()
+    }
+
+    /// Renders errors and warnings as a report with terminal colors suitable for printing to a console.
+    pub fn render_colored(&self, air_script: &str) -> String {
+        let mut buffer = Buffer::ansi();
+        self.emit(air_script, &mut buffer);
+
+        String::from_utf8_lossy(buffer.as_slice()).to_string()
+    }
+
+    fn emit(&self, air_script: &str, buffer: &mut Buffer) {
+        let mut files = SimpleFiles::new();
+        let file_id = files.add("script.aqua", air_script);
+
+        let to_diagnostic = |diagnostic: Diagnostic<usize>, semantic: &SemanticDiagnostic| {
+            let label = Label::primary(file_id, semantic.span.left..semantic.span.right);
+            diagnostic
+                .with_message(semantic.kind.to_string())
+                .with_labels(vec![label])
+        };
+
+        let errors = self
+            .errors
+            .iter()
+            .map(|error| to_diagnostic(Diagnostic::error(), error));
+        let warnings = self
+            .warnings
+            .iter()
+            .map(|warning| to_diagnostic(Diagnostic::warning(), warning));
+
+        let config = term::Config::default();
+        for diagnostic in errors.chain(warnings) {
+            term::emit(buffer, &config, &files, &diagnostic).expect("term emit to buffer");
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors = self.errors.iter().map(|error| ("error", error));
+        let warnings = self.warnings.iter().map(|warning| ("warning", warning));
+
+        for (id, (severity, diagnostic)) in errors.chain(warnings).enumerate() {
+            if id != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{} {}", severity, diagnostic)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for SemanticDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}: {}", self.span.left, self.span.right, self.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,382 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::validate;
+use super::SemanticDiagnosticKind;
+use super::SemanticDiagnosticKind::*;
+use super::ValidationReport;
+
+fn run_validator(source_code: &str) -> ValidationReport {
+    let instruction = crate::parse(source_code).expect("parsing failed");
+    validate(&instruction)
+}
+
+fn kinds(diagnostics: &[super::SemanticDiagnostic]) -> Vec<SemanticDiagnosticKind> {
+    diagnostics.iter().map(|d| d.kind.clone()).collect()
+}
+
+#[test]
+fn valid_script() {
+    let source_code = r#"
+        (seq
+            (call "peer" ("service" "function") [] peers)
+            (fold peers i
+                (par
+                    (call i ("service" "function") [i] result)
+                    (next i)
+                )
+            )
+        )
+        "#;
+
+    let report = run_validator(source_code);
+    assert_eq!(report, ValidationReport::default());
+    assert!(!report.has_errors());
+}
+
+#[test]
+fn next_outside_fold() {
+    let source_code = r#"
+        (seq
+            (call "peer" ("service" "function") [] peers)
+            (seq
+                (fold peers i
+                    (next j)
+                )
+                (next i)
+            )
+        )
+        "#;
+
+    let report = run_validator(source_code);
+    assert_eq!(
+        kinds(&report.errors),
+        vec![
+            NextOutsideFold(String::from("j")),
+            NextOutsideFold(String::from("i"))
+        ]
+    );
+
+    let next_position = source_code.rfind("(next i)").unwrap();
+    assert_eq!(report.errors[1].span.left, next_position);
+    assert_eq!(
+        report.errors[1].span.right,
+        next_position + "(next i)".len()
+    );
+}
+
+#[test]
+fn multiple_definitions() {
+    let source_code = r#"
+        (seq
+            (par
+                (call "peer" ("service" "function") [] value)
+                (call "peer" ("service" "function") [] value)
+            )
+            (fold value i
+                (seq
+                    (call "peer" ("service" "function") [] value)
+                    (next i)
+                )
+            )
+        )
+        "#;
+
+    let report = run_validator(source_code);
+    assert_eq!(
+        kinds(&report.errors),
+        vec![MultipleDefinitions(String::from("value"))]
+    );
+    assert!(report.warnings.is_empty());
+}
+
+#[test]
+fn conditional_definitions() {
+    let source_code = r#"
+        (seq
+            (seq
+                (call "peer" ("service" "function") [] value)
+                (xor
+                    (call "peer" ("service" "function") [] result)
+                    (call "peer" ("service" "function") [] result)
+                )
+            )
+            (xor
+                (match value "a"
+                    (call "peer" ("service" "function") [] value)
+                )
+                (null)
+            )
+        )
+        "#;
+
+    let report = run_validator(source_code);
+    assert!(report.errors.is_empty());
+    assert_eq!(
+        kinds(&report.warnings),
+        vec![MultipleDefinitions(String::from("value"))]
+    );
+}
+
+#[test]
+fn new_definitions() {
+    let source_code = r#"
+        (seq
+            (seq
+                (call "peer" ("service" "function") [] value)
+                (new value
+                    (seq
+                        (call "peer" ("service" "function") [] value)
+                        (call "peer" ("service" "function") [] value)
+                    )
+                )
+            )
+            (call "peer" ("service" "function") [value] result)
+        )
+        "#;
+
+    let report = run_validator(source_code);
+    assert_eq!(report, ValidationReport::default());
+}
+
+#[test]
+fn new_scopes_only_declared_variable() {
+    let source_code = r#"
+        (seq
+            (call "peer" ("service" "function") [] result)
+            (new value
+                (seq
+                    (call "peer" ("service" "function") [] value)
+                    (call "peer" ("service" "function") [] result)
+                )
+            )
+        )
+        "#;
+
+    let report = run_validator(source_code);
+    assert_eq!(
+        kinds(&report.errors),
+        vec![MultipleDefinitions(String::from("result"))]
+    );
+    assert!(report.warnings.is_empty());
+}
+
+#[test]
+fn iterator_shadowing() {
+    let source_code = r#"
+        (seq
+            (call "peer" ("service" "function") [] i)
+            (fold i i
+                (fold i i
+                    (call "peer" ("service" "function") [] i)
+                )
+            )
+        )
+        "#;
+
+    let report = run_validator(source_code);
+    assert_eq!(
+        kinds(&report.errors),
+        vec![
+            IteratorShadowing(String::from("i")),
+            IteratorShadowing(String::from("i"))
+        ]
+    );
+    assert_eq!(
+        kinds(&report.warnings),
+        vec![IteratorShadowing(String::from("i"))]
+    );
+}
+
+#[test]
+fn undefined_variables() {
+    let source_code = r#"
+        (seq
+            (call peer ("service" "function") [arg.$.field acc] acc[])
+            (mismatch left "right"
+                (fold iterable i
+                    (call i ("service" "function") [])
+                )
+            )
+        )
+        "#;
+
+    let report = run_validator(source_code);
+    assert!(report.errors.is_empty());
+    assert_eq!(
+        kinds(&report.warnings),
+        vec![
+            UndefinedVariable(String::from("peer")),
+            UndefinedVariable(String::from("arg")),
+            UndefinedVariable(String::from("left")),
+            UndefinedVariable(String::from("iterable")),
+        ]
+    );
+}
+
+#[test]
+fn report_rendering() {
+    let source_code = "(seq (next i) (call peer (\"service\" \"function\") []))";
+
+    let report = run_validator(source_code);
+    assert_eq!(
+        report.to_string(),
+        "error 5..13: next over 'i' is used outside of a fold with such iterator\n\
+         warning 20..24: variable 'peer' isn't produced by any call or fold"
+    );
+
+    let rendered = report.render(source_code);
+    assert!(rendered.contains("error: next over 'i' is used outside of a fold"));
+    assert!(rendered.contains("warning: variable 'peer' isn't produced"));
+}
+
+#[test]
+fn malformed_instruction() {
+    use crate::ast;
+    use ast::Instruction;
+    use ast::Span;
+
+    // such instruction could come only with a deserialized AST
+    let instruction = Instruction::Seq(ast::Seq(
+        Box::new(Instruction::Null(ast::Null(Span::default()))),
+        Box::new(Instruction::Error),
+        Span::default(),
+    ));
+
+    let report = validate(&instruction);
+    assert_eq!(kinds(&report.errors), vec![MalformedInstruction]);
+}
+
+#[test]
+fn ap_definitions() {
+    let source_code = r#"
+        (seq
+            (call "peer" ("service" "function") [] result)
+            (seq
+                (ap result.$.field field)
+                (seq
+                    (ap unknown.$.field acc[])
+                    (ap "value" field)
+                )
+            )
+        )
+        "#;
+
+    let report = run_validator(source_code);
+    assert_eq!(
+        kinds(&report.errors),
+        vec![MultipleDefinitions(String::from("field"))]
+    );
+    assert_eq!(
+        kinds(&report.warnings),
+        vec![UndefinedVariable(String::from("unknown"))]
+    );
+
+    let ap_position = source_code.find(r#"(ap "value" field)"#).unwrap();
+    assert_eq!(report.errors[0].span.left, ap_position);
+}
+
+#[test]
+fn fail_arguments() {
+    let source_code = r#"
+        (seq
+            (call "peer" ("service" "function") [] error)
+            (xor
+                (fail error.$.code error.$.message)
+                (fail 1 message)
+            )
+        )
+        "#;
+
+    let report = run_validator(source_code);
+    assert!(!report.has_errors());
+    assert_eq!(
+        kinds(&report.warnings),
+        vec![UndefinedVariable(String::from("message"))]
+    );
+}
+
+#[test]
+fn procedures() {
+    let source_code = r#"
+        (define relay [peer relay_id] result
+            (seq
+                (call relay_id ("op" "identity") [])
+                (call peer ("service" "function") [outer] result)
+            )
+        )
+        (define relay [peer] (call peer ("service" "function") []))
+        (seq
+            (call "peer" ("service" "function") [] outer)
+            (seq
+                (invoke relay [outer "relay"] relayed)
+                (seq
+                    (invoke relay ["peer"])
+                    (invoke unknown [relayed])
+                )
+            )
+        )
+        "#;
+
+    let report = run_validator(source_code);
+    assert_eq!(
+        kinds(&report.errors),
+        vec![
+            MultipleProcedureDefinitions(String::from("relay")),
+            ArgumentsCountMismatch(String::from("relay"), 1, 2),
+            ProcedureWithoutOutput(String::from("relay")),
+            UndefinedProcedure(String::from("unknown")),
+        ]
+    );
+    // a procedure body doesn't see variables of the script
+    assert_eq!(
+        kinds(&report.warnings),
+        vec![UndefinedVariable(String::from("outer"))]
+    );
+}
+
+#[test]
+fn recursive_procedure() {
+    let source_code = r#"
+        (define loop [value] (invoke loop [value]))
+        (invoke loop [1])
+        "#;
+
+    let report = run_validator(source_code);
+    assert_eq!(
+        kinds(&report.errors),
+        vec![UndefinedProcedure(String::from("loop"))]
+    );
+}
+
+#[test]
+fn fan_out_output() {
+    let source_code = r#"
+        (seq
+            (call "peer" ("service" "function") [] peers)
+            (seq
+                (call [peers] ("service" "function") [] results[])
+                (call [peers.$.relays] ("service" "function") [] result)
+            )
+        )
+        "#;
+
+    let report = run_validator(source_code);
+    assert_eq!(
+        kinds(&report.errors),
+        vec![ScalarFanOutOutput(String::from("result"))]
+    );
+}
@@ -16,6 +16,7 @@
 
 use fluence::fce;
 
+use fluence_it_types::ne_vec::NEVec;
 use fluence_it_types::IValue;
 use serde::Deserialize;
 use serde::Serialize;
@@ -38,11 +39,52 @@ pub struct StepperOutcome {
 
     /// Public keys of peers that should receive data.
     pub next_peer_pks: Vec<String>,
+
+    /// Gas consumed by this execution, it's reported regardless of ret_code value.
+    pub gas_used: Gas,
+}
+
+/// Parameters of a particle the stepper is run with.
+#[fce]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunParameters {
+    /// Id of a peer that created the particle.
+    pub init_peer_id: String,
+
+    /// Id of the particle.
+    pub particle_id: String,
+
+    /// Particle creation time in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+
+    /// Particle time to live in milliseconds.
+    pub ttl: u32,
+
+    /// Limits of work the stepper could do in this execution.
+    pub gas_limit: Gas,
+
+    /// If true, calls of the built-in service are passed to the host as any other calls.
+    pub disable_builtins: bool,
+}
+
+/// Amounts of work done by the stepper in one execution.
+/// Used as a limit, a zero amount means that such work isn't limited.
+#[fce]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Gas {
+    /// Count of executed instructions.
+    pub instructions: u64,
+
+    /// Count of calls to services of the host.
+    pub service_calls: u64,
+
+    /// Count of trace states produced by an execution, states replayed from previous data aren't counted.
+    pub trace_states: u64,
 }
 
 impl StepperOutcome {
     pub fn from_ivalues(mut ivalues: Vec<IValue>) -> Result<Self, String> {
-        const OUTCOME_FIELDS_COUNT: usize = 4;
+        const OUTCOME_FIELDS_COUNT: usize = 5;
 
         let record_values = match ivalues.remove(0) {
             IValue::Record(record_values) => record_values,
@@ -50,6 +92,14 @@ impl StepperOutcome {
         };
 
         let mut record_values = record_values.into_vec();
+        // steppers with the interface before 0.2.0 don't report gas_used
+        if record_values.len() == OUTCOME_FIELDS_COUNT - 1 {
+            return Err(format!(
+                "expected StepperOutcome struct with {} fields, got an outcome without gas_used \
+                 of a stepper with the interface before 0.2.0: {:?}",
+                OUTCOME_FIELDS_COUNT, record_values
+            ));
+        }
         if record_values.len() != OUTCOME_FIELDS_COUNT {
             return Err(format!(
                 "expected StepperOutcome struct with {} fields, got {:?}",
@@ -96,13 +146,114 @@ impl StepperOutcome {
             v => Err(format!("expected array for next_peer_pks, got {:?}", v)),
         }?;
 
+        let gas_used = Gas::from_ivalue(record_values.remove(0))?;
+
         let outcome = Self {
             ret_code,
             error_message,
             data,
             next_peer_pks,
+            gas_used,
         };
 
         Ok(outcome)
     }
 }
+
+impl RunParameters {
+    /// Converts parameters to a record, that a host passes to the stepper `invoke` function.
+    pub fn into_ivalue(self) -> IValue {
+        let record_values = vec![
+            IValue::String(self.init_peer_id),
+            IValue::String(self.particle_id),
+            IValue::U64(self.timestamp),
+            IValue::U32(self.ttl),
+            self.gas_limit.into_ivalue(),
+            IValue::Boolean(self.disable_builtins),
+        ];
+
+        IValue::Record(NEVec::new(record_values).expect("record values aren't empty"))
+    }
+}
+
+impl Gas {
+    /// Converts gas amounts to a record, it's the inverse of `from_ivalue`.
+    pub fn into_ivalue(self) -> IValue {
+        let record_values = vec![
+            IValue::U64(self.instructions),
+            IValue::U64(self.service_calls),
+            IValue::U64(self.trace_states),
+        ];
+
+        IValue::Record(NEVec::new(record_values).expect("record values aren't empty"))
+    }
+
+    pub fn from_ivalue(ivalue: IValue) -> Result<Self, String> {
+        const GAS_FIELDS_COUNT: usize = 3;
+
+        let record_values = match ivalue {
+            IValue::Record(record_values) => record_values.into_vec(),
+            v => return Err(format!("expected record for Gas, got {:?}", v)),
+        };
+
+        if record_values.len() != GAS_FIELDS_COUNT {
+            return Err(format!(
+                "expected Gas struct with {} fields, got {:?}",
+                GAS_FIELDS_COUNT, record_values
+            ));
+        }
+
+        let amounts = record_values
+            .into_iter()
+            .map(|v| match v {
+                IValue::U64(amount) => Ok(amount),
+                v => Err(format!("expected u64 for a gas amount, got {:?}", v)),
+            })
+            .collect::<Result<Vec<u64>, _>>()?;
+
+        let gas = Self {
+            instructions: amounts[0],
+            service_calls: amounts[1],
+            trace_states: amounts[2],
+        };
+
+        Ok(gas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome_ivalues(gas_used: Option<Gas>) -> Vec<IValue> {
+        let mut record_values = vec![
+            IValue::S32(STEPPER_SUCCESS),
+            IValue::String(String::new()),
+            IValue::Array(vec![IValue::U8(b'[')]),
+            IValue::Array(vec![IValue::String(String::from("peer"))]),
+        ];
+        record_values.extend(gas_used.map(Gas::into_ivalue));
+
+        vec![IValue::Record(NEVec::new(record_values).unwrap())]
+    }
+
+    #[test]
+    fn outcome_with_gas_used() {
+        let gas_used = Gas {
+            instructions: 1,
+            service_calls: 2,
+            trace_states: 3,
+        };
+
+        let outcome = StepperOutcome::from_ivalues(outcome_ivalues(Some(gas_used))).unwrap();
+        assert_eq!(outcome.gas_used, gas_used);
+        assert_eq!(outcome.data, b"[");
+        assert_eq!(outcome.next_peer_pks, vec![String::from("peer")]);
+    }
+
+    #[test]
+    fn outcome_of_previous_stepper_rejected() {
+        let error = StepperOutcome::from_ivalues(outcome_ivalues(None)).unwrap_err();
+        assert!(error.contains("without gas_used"));
+    }
+}
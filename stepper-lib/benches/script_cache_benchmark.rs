@@ -0,0 +1,55 @@
+use aqua_test_utils::create_aqua_vm;
+use aqua_test_utils::unit_call_service;
+use aqua_test_utils::AquamarineVM;
+use aqua_test_utils::AquamarineVMError;
+use aqua_test_utils::StepperOutcome;
+
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+
+use std::cell::RefCell;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+thread_local!(static VM: RefCell<AquamarineVM> = RefCell::new(create_aqua_vm(unit_call_service(), "test_peer_id")));
+thread_local!(static SCRIPT: String = big_script(100));
+
+static SCRIPT_ID: AtomicU64 = AtomicU64::new(0);
+
+// a sequence of calls to a remote peer, so the script is mostly parsed rather than executed,
+// every call has its own output, because a scalar couldn't be set twice
+fn big_script(calls_count: usize) -> String {
+    let call = |id: usize| {
+        format!(
+            r#"(call "remote_peer_id" ("local_service_id" "local_fn_name") [] result_{})"#,
+            id
+        )
+    };
+
+    (1..calls_count).fold(call(0), |script, id| format!("(seq {} {})", call(id), script))
+}
+
+fn same_script_call() -> Result<StepperOutcome, AquamarineVMError> {
+    VM.with(|vm| SCRIPT.with(|script| vm.borrow_mut().call_with_prev_data("", script.clone(), "[]", "[]")))
+}
+
+fn unique_script_call() -> Result<StepperOutcome, AquamarineVMError> {
+    // a trailing comment makes every script unique, so it's parsed on each invocation
+    let script_id = SCRIPT_ID.fetch_add(1, Ordering::Relaxed);
+    let script = SCRIPT.with(|script| format!("{}\n; {}", script, script_id));
+
+    VM.with(|vm| vm.borrow_mut().call_with_prev_data("", script, "[]", "[]"))
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    // a rejected script isn't cached, so both benchmarks would measure the same path
+    let outcome = same_script_call().expect("the script should be executed");
+    assert_eq!(outcome.ret_code, 0, "{}", outcome.error_message);
+
+    c.bench_function("same_script_call", move |b| b.iter(same_script_call));
+    c.bench_function("unique_script_call", move |b| b.iter(unique_script_call));
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
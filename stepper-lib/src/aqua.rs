@@ -16,43 +16,103 @@
 
 mod outcome;
 
+use crate::builtins::Builtins;
 use crate::execution::ExecutableInstruction;
 use crate::preparation::prepare;
+use crate::preparation::AquaSource;
 use crate::preparation::PreparationDescriptor;
 
+use stepper_interface::RunParameters;
 use stepper_interface::StepperOutcome;
 
-pub fn execute_aqua(init_peer_id: String, aqua: String, prev_data: Vec<u8>, data: Vec<u8>) -> StepperOutcome {
+pub fn execute_aqua(params: RunParameters, aqua: String, prev_data: Vec<u8>, data: Vec<u8>) -> StepperOutcome {
+    execute_aqua_with_builtins(params, Builtins::default(), aqua, prev_data, data)
+}
+
+/// Execute an AIR script with the supplied built-in functions instead of the default ones.
+pub fn execute_aqua_with_builtins(
+    params: RunParameters,
+    builtins: Builtins,
+    aqua: String,
+    prev_data: Vec<u8>,
+    data: Vec<u8>,
+) -> StepperOutcome {
+    use std::convert::identity;
+
+    log::trace!(
+        "aquamarine version is {}, run parameters are {:?}",
+        env!("CARGO_PKG_VERSION"),
+        params
+    );
+
+    execute_aqua_impl(params, builtins, AquaSource::Script(&aqua), prev_data, data).unwrap_or_else(identity)
+}
+
+/// Execute an AST serialized to JSON, as it's returned by the `ast` export, without parsing AIR.
+pub fn execute_aqua_ast(params: RunParameters, aqua_ast: String, prev_data: Vec<u8>, data: Vec<u8>) -> StepperOutcome {
+    execute_aqua_ast_with_builtins(params, Builtins::default(), aqua_ast, prev_data, data)
+}
+
+/// Execute an AST serialized to JSON with the supplied built-in functions instead of the default ones.
+pub fn execute_aqua_ast_with_builtins(
+    params: RunParameters,
+    builtins: Builtins,
+    aqua_ast: String,
+    prev_data: Vec<u8>,
+    data: Vec<u8>,
+) -> StepperOutcome {
     use std::convert::identity;
 
     log::trace!(
-        "aquamarine version is {}, init user id is {}",
+        "aquamarine version is {}, run parameters are {:?}, executing a pre-compiled ast",
         env!("CARGO_PKG_VERSION"),
-        init_peer_id
+        params
     );
 
-    execute_aqua_impl(init_peer_id, aqua, prev_data, data).unwrap_or_else(identity)
+    execute_aqua_impl(params, builtins, AquaSource::JsonAst(&aqua_ast), prev_data, data).unwrap_or_else(identity)
 }
 
 fn execute_aqua_impl(
-    init_peer_id: String,
-    aqua: String,
+    params: RunParameters,
+    builtins: Builtins,
+    aqua_source: AquaSource<'_>,
     prev_data: Vec<u8>,
     data: Vec<u8>,
 ) -> Result<StepperOutcome, StepperOutcome> {
+    let disable_builtins = params.disable_builtins;
     let PreparationDescriptor {
         mut exec_ctx,
         mut trace_ctx,
         aqua,
-    } = prepare(&prev_data, &data, aqua.as_str(), init_peer_id)
+    } = prepare(&prev_data, &data, aqua_source, params)
         // return the initial data in case of errors
         .map_err(|e| outcome::from_preparation_error(data, e))?;
+    // builtins supplied by an embedding host are used unless they're disabled by run parameters
+    if !disable_builtins {
+        exec_ctx.builtins = builtins;
+    }
 
-    aqua.execute(&mut exec_ctx, &mut trace_ctx)
+    aqua.instruction().execute(&mut exec_ctx, &mut trace_ctx).map_err(|e| {
+        // spans of a deserialized AST don't point to any text
+        let location = match aqua_source {
+            AquaSource::Script(raw_aqua) => exec_ctx
+                .failed_instruction
+                .as_ref()
+                .map(|failed| outcome::ErrorLocation::new(raw_aqua, failed.span)),
+            AquaSource::JsonAst(_) => None,
+        };
         // return new collected trace in case of errors
-        .map_err(|e| outcome::from_execution_error(&trace_ctx.new_trace, exec_ctx.next_peer_pks.clone(), e))?;
+        outcome::from_execution_error(
+            &trace_ctx.new_trace,
+            exec_ctx.next_peer_pks.clone(),
+            exec_ctx.gas.used(),
+            e,
+            location,
+        )
+    })?;
 
-    let outcome = outcome::from_path_and_peers(&trace_ctx.new_trace, exec_ctx.next_peer_pks);
+    let gas_used = exec_ctx.gas.used();
+    let outcome = outcome::from_path_and_peers(&trace_ctx.new_trace, exec_ctx.next_peer_pks, gas_used);
 
     Ok(outcome)
 }
@@ -17,17 +17,21 @@
 use crate::execution::ExecutionError;
 use crate::preparation::PreparationError;
 
+use crate::Gas;
 use crate::StepperOutcome;
 use crate::STEPPER_SUCCESS;
 
+use air_parser::ast::Span;
 use serde::Serialize;
+
+use std::fmt;
 use std::hash::Hash;
 
 const EXECUTION_ERRORS_START_ID: i32 = 1000;
 
-/// Create StepperOutcome from supplied data and next_peer_pks,
+/// Create StepperOutcome from supplied data, next_peer_pks and consumed gas,
 /// set ret_code to STEPPER_SUCCESS.
-pub(crate) fn from_path_and_peers<T>(data: &T, next_peer_pks: Vec<String>) -> StepperOutcome
+pub(crate) fn from_path_and_peers<T>(data: &T, next_peer_pks: Vec<String>, gas_used: Gas) -> StepperOutcome
 where
     T: ?Sized + Serialize,
 {
@@ -39,6 +43,7 @@ where
         error_message: String::new(),
         data,
         next_peer_pks,
+        gas_used,
     }
 }
 
@@ -53,33 +58,96 @@ pub(crate) fn from_preparation_error(data: impl Into<Vec<u8>>, err: PreparationE
         error_message: format!("{}", err),
         data,
         next_peer_pks: vec![],
+        gas_used: Gas::default(),
+    }
+}
+
+/// Points to the instruction or the value of an AIR script that failed.
+pub(crate) struct ErrorLocation<'s> {
+    line: usize,
+    column: usize,
+    snippet: &'s str,
+    is_truncated: bool,
+}
+
+impl<'s> ErrorLocation<'s> {
+    pub(crate) fn new(air_script: &'s str, span: Span) -> Self {
+        let (line, column) = span.line_column(air_script);
+
+        // instructions could be quite big, so only the first line of them is shown
+        let instruction = air_script.get(span.left..span.right).unwrap_or_default();
+        let snippet = instruction.lines().next().unwrap_or_default().trim_end();
+        let is_truncated = snippet.len() < instruction.trim_end().len();
+
+        Self {
+            line,
+            column,
+            snippet,
+            is_truncated,
+        }
+    }
+}
+
+impl fmt::Display for ErrorLocation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  --> {}:{}", self.line, self.column)?;
+        write!(f, "   | {}", self.snippet)?;
+        if self.is_truncated {
+            write!(f, " ...")?;
+        }
+
+        Ok(())
     }
 }
 
-/// Create StepperOutcome from supplied data, next_peer_pks and error,
+/// Create StepperOutcome from supplied data, next_peer_pks, consumed gas and error,
 /// set ret_code based on the error.
-pub(crate) fn from_execution_error<T>(data: &T, next_peer_pks: Vec<String>, err: ExecutionError) -> StepperOutcome
+pub(crate) fn from_execution_error<T>(
+    data: &T,
+    next_peer_pks: Vec<String>,
+    gas_used: Gas,
+    err: ExecutionError,
+    location: Option<ErrorLocation<'_>>,
+) -> StepperOutcome
 where
     T: ?Sized + Serialize,
 {
+    let data = serde_json::to_vec(data).expect("default serializer shouldn't fail");
+    let next_peer_pks = dedup(next_peer_pks);
+
+    // errors raised by a script itself are returned as they were provided by the script
+    if let ExecutionError::FailError(ret_code, message) = err {
+        return StepperOutcome {
+            ret_code,
+            error_message: message,
+            data,
+            next_peer_pks,
+            gas_used,
+        };
+    }
+
     let ret_code = err.to_error_code() as i32;
     let ret_code = EXECUTION_ERRORS_START_ID + ret_code;
 
-    let data = serde_json::to_vec(data).expect("default serializer shouldn't fail");
-    let next_peer_pks = dedup(next_peer_pks);
+    let error_message = match location {
+        Some(location) => format!("{}\n{}", err, location),
+        None => format!("{}", err),
+    };
 
     StepperOutcome {
         ret_code,
-        error_message: format!("{}", err),
+        error_message,
         data,
         next_peer_pks,
+        gas_used,
     }
 }
 
-/// Deduplicate values in a supplied vector.
-fn dedup<T: Eq + Hash>(mut vec: Vec<T>) -> Vec<T> {
+/// Deduplicate values in a supplied vector, keeping the first occurrence of each one.
+fn dedup<T: Eq + Hash + Clone>(mut vec: Vec<T>) -> Vec<T> {
     use std::collections::HashSet;
 
-    let set: HashSet<_> = vec.drain(..).collect();
-    set.into_iter().collect()
+    let mut seen = HashSet::new();
+    vec.retain(|value| seen.insert(value.clone()));
+    vec
 }
@@ -0,0 +1,107 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+mod functions;
+
+use serde_json::Value as JValue;
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Service id of functions executed by the interpreter itself, it's written in the same way
+/// as other reserved names of AIR, so it doesn't take over a service id of a host.
+pub const BUILTIN_SERVICE_ID: &str = "%builtin%";
+
+/// A pure function called with values of call arguments, an error is handled
+/// in the same way as an error returned by call_service.
+pub type BuiltinFunction = Rc<dyn Fn(&[JValue]) -> Result<JValue, String>>;
+
+/// Functions of the built-in service. Calls of them on the current peer are executed
+/// inside the interpreter without crossing the call_service boundary, calls of other
+/// functions of this service are passed to the host as usual.
+#[derive(Clone)]
+pub struct Builtins {
+    functions: HashMap<String, BuiltinFunction>,
+}
+
+impl Builtins {
+    /// Returns builtins without any function, so every call is passed to the host.
+    pub fn disabled() -> Self {
+        Self {
+            functions: HashMap::new(),
+        }
+    }
+
+    /// Adds a function to the built-in service, a function with the same name is replaced.
+    pub fn insert(
+        &mut self,
+        function_name: impl Into<String>,
+        function: impl Fn(&[JValue]) -> Result<JValue, String> + 'static,
+    ) {
+        self.functions.insert(function_name.into(), Rc::new(function));
+    }
+
+    /// Removes a function from the built-in service, so its calls are passed to the host.
+    pub fn remove(&mut self, function_name: &str) {
+        self.functions.remove(function_name);
+    }
+
+    pub(crate) fn get(&self, service_id: &str, function_name: &str) -> Option<&BuiltinFunction> {
+        if service_id != BUILTIN_SERVICE_ID {
+            return None;
+        }
+
+        self.functions.get(function_name)
+    }
+}
+
+impl Default for Builtins {
+    /// Returns builtins with identity, concat, length, element, join and set_field functions.
+    fn default() -> Self {
+        let mut builtins = Self::disabled();
+        builtins.insert("identity", functions::identity);
+        builtins.insert("concat", functions::concat);
+        builtins.insert("length", functions::length);
+        builtins.insert("element", functions::element);
+        builtins.insert("join", functions::join);
+        builtins.insert("set_field", functions::set_field);
+
+        builtins
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contexts::execution::ExecutionCtx;
+    use crate::RunParameters;
+
+    #[test]
+    fn builtins_disabled_by_run_parameters() {
+        let builtins = |disable_builtins| {
+            let params = RunParameters {
+                disable_builtins,
+                ..RunParameters::default()
+            };
+            ExecutionCtx::new(String::from("A"), params).builtins
+        };
+
+        assert!(builtins(false).get(BUILTIN_SERVICE_ID, "identity").is_some());
+        assert!(builtins(true).get(BUILTIN_SERVICE_ID, "identity").is_none());
+        // service ids of a host aren't taken over
+        assert!(builtins(false).get("op", "identity").is_none());
+    }
+}
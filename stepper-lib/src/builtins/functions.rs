@@ -0,0 +1,125 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use serde_json::Value as JValue;
+
+use std::convert::TryFrom;
+
+type BuiltinResult = Result<JValue, String>;
+
+/// Returns its argument or null if it's called without arguments.
+pub(super) fn identity(args: &[JValue]) -> BuiltinResult {
+    match args {
+        [] => Ok(JValue::Null),
+        [value] => Ok(value.clone()),
+        _ => Err(format!("identity expects at most 1 argument, but got {}", args.len())),
+    }
+}
+
+/// Concatenates arrays into one array.
+pub(super) fn concat(args: &[JValue]) -> BuiltinResult {
+    let mut result = Vec::new();
+    for arg in args {
+        result.extend(as_array(arg, "concat")?.iter().cloned());
+    }
+
+    Ok(JValue::Array(result))
+}
+
+/// Returns the length of an array.
+pub(super) fn length(args: &[JValue]) -> BuiltinResult {
+    match args {
+        [array] => Ok(JValue::from(as_array(array, "length")?.len())),
+        _ => Err(format!("length expects 1 argument, but got {}", args.len())),
+    }
+}
+
+/// Returns an element of an array by its index.
+pub(super) fn element(args: &[JValue]) -> BuiltinResult {
+    let (array, index) = match args {
+        [array, JValue::Number(index)] => (as_array(array, "element")?, index),
+        _ => return Err(String::from("element expects an array and an index")),
+    };
+
+    // an index that doesn't fit into usize, e.g. on wasm32, is out of bounds as well
+    index
+        .as_u64()
+        .and_then(|index| usize::try_from(index).ok())
+        .and_then(|index| array.get(index))
+        .cloned()
+        .ok_or_else(|| {
+            format!(
+                "element index {} is out of bounds of an array with length {}",
+                index,
+                array.len()
+            )
+        })
+}
+
+/// Joins an array of strings with a separator.
+pub(super) fn join(args: &[JValue]) -> BuiltinResult {
+    let (array, separator) = match args {
+        [array, JValue::String(separator)] => (as_array(array, "join")?, separator),
+        _ => return Err(String::from("join expects an array of strings and a separator")),
+    };
+
+    let strings = array
+        .iter()
+        .map(|value| match value {
+            JValue::String(string) => Ok(string.as_str()),
+            value => Err(format!("join expects an array of strings, but it contains {}", value)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(JValue::String(strings.join(separator)))
+}
+
+/// Returns an object with a field set to a value.
+pub(super) fn set_field(args: &[JValue]) -> BuiltinResult {
+    match args {
+        [JValue::Object(object), JValue::String(field), value] => {
+            let mut object = object.clone();
+            object.insert(field.clone(), value.clone());
+            Ok(JValue::Object(object))
+        }
+        _ => Err(String::from("set_field expects an object, a field name and a value")),
+    }
+}
+
+fn as_array<'v>(value: &'v JValue, function_name: &str) -> Result<&'v Vec<JValue>, String> {
+    match value {
+        JValue::Array(array) => Ok(array),
+        value => Err(format!("{} expects an array, but got {}", function_name, value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn element_out_of_bounds() {
+        let array = json!(["a", "b"]);
+
+        assert_eq!(element(&[array.clone(), json!(1)]), Ok(json!("b")));
+        assert!(element(&[array.clone(), json!(2)]).is_err());
+        // an index isn't truncated to an address width
+        let index = u32::MAX as u64 + 1;
+        assert!(element(&[array, json!(index)]).is_err());
+    }
+}
@@ -15,18 +15,30 @@
  */
 
 mod avalue;
+mod gas_meter;
+mod last_error;
+mod scope;
 
 pub(crate) use avalue::AValue;
 pub(crate) use avalue::ResolvedCallResult;
+pub(crate) use gas_meter::GasMeter;
+pub(crate) use last_error::FailedInstruction;
+pub(crate) use last_error::LastError;
+pub(crate) use scope::Scope;
 
+use crate::builtins::Builtins;
+
+use air_parser::ast::Span;
+use stepper_interface::RunParameters;
+
+use std::cell::Cell;
 use std::collections::HashMap;
-use std::collections::VecDeque;
 
 /// Contains all necessary state needed to execute aqua script.
-pub(crate) struct ExecutionCtx<'i> {
+pub(crate) struct ExecutionCtx {
     /// Contains all set variables.
     // TODO: use shared string (Rc<String>) to avoid copying.
-    pub data_cache: HashMap<String, AValue<'i>>,
+    pub data_cache: HashMap<String, AValue>,
 
     /// Set of peer public keys that should receive resulted data.
     pub next_peer_pks: Vec<String>,
@@ -37,6 +49,15 @@ pub(crate) struct ExecutionCtx<'i> {
     /// PeerId of a peer send this aqua script.
     pub init_peer_id: String,
 
+    /// Id of a particle carrying this aqua script.
+    pub particle_id: String,
+
+    /// Particle creation time in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+
+    /// Particle time to live in milliseconds.
+    pub ttl: u32,
+
     /// Indicates that previous executed subtree is complete.
     /// A subtree treats as a complete if all subtree elements satisfy the following rules:
     ///   - at least one of par subtrees is completed
@@ -45,19 +66,102 @@ pub(crate) struct ExecutionCtx<'i> {
     ///   - call executed successfully (executed state is Executed)
     pub subtree_complete: bool,
 
-    /// List of met folds used to determine whether a variable can be shadowed.
-    pub met_folds: VecDeque<&'i str>,
+    /// Lexical scopes opened by folds and new instructions, the innermost one is the last.
+    /// Variables could be shadowed only inside some scope.
+    pub scopes: Vec<Scope>,
+
+    /// The innermost instruction that returned an error, it's set while the error
+    /// is bubbling up and taken when the error is caught by xor.
+    pub failed_instruction: Option<FailedInstruction>,
+
+    /// A part of the script referring to a value that couldn't be resolved, it's set while
+    /// the value is resolved and taken when the instruction resolving it completes.
+    pub failed_value: Cell<Option<Span>>,
+
+    /// The last error caught by xor, it's accessible by scripts through `%last_error%`.
+    pub last_error: Option<LastError>,
+
+    /// Functions called on the current peer without crossing the call_service boundary.
+    pub builtins: Builtins,
+
+    /// Gas consumed by this execution and its limit.
+    pub gas: GasMeter,
 }
 
-impl<'i> ExecutionCtx<'i> {
-    pub(crate) fn new(current_peer_id: String, init_peer_id: String) -> Self {
+impl ExecutionCtx {
+    pub(crate) fn new(current_peer_id: String, params: RunParameters) -> Self {
+        let RunParameters {
+            init_peer_id,
+            particle_id,
+            timestamp,
+            ttl,
+            gas_limit,
+            disable_builtins,
+        } = params;
+
+        // a host could opt out of builtins to handle all calls itself
+        let builtins = if disable_builtins {
+            Builtins::disabled()
+        } else {
+            Builtins::default()
+        };
+
         Self {
             data_cache: HashMap::new(),
             next_peer_pks: vec![],
             current_peer_id,
             init_peer_id,
+            particle_id,
+            timestamp,
+            ttl,
             subtree_complete: true,
-            met_folds: VecDeque::new(),
+            scopes: vec![],
+            failed_instruction: None,
+            failed_value: Cell::new(None),
+            last_error: None,
+            builtins,
+            gas: GasMeter::new(gas_limit),
+        }
+    }
+
+    /// Remembers a part of the script referring to a value if its resolution failed,
+    /// so the error points at this value rather than at the whole instruction.
+    pub(crate) fn point_at_value<T, E>(&self, span: Option<Span>, result: Result<T, E>) -> Result<T, E> {
+        if let (Err(_), Some(span)) = (&result, span) {
+            self.failed_value.set(Some(span));
+        }
+
+        result
+    }
+
+    /// Opens a new innermost scope holding all variables defined inside it.
+    pub(crate) fn open_scope(&mut self) {
+        self.scopes.push(Scope::default());
+    }
+
+    /// Opens a new innermost scope holding only the declared variable, it's undefined inside
+    /// the scope until it's set there.
+    pub(crate) fn open_scope_for(&mut self, name: &str) {
+        let previous = self.data_cache.remove(name);
+        self.scopes.push(Scope::with_declared(name, previous));
+    }
+
+    /// Returns true if a variable defined now belongs to some scope, so it could be shadowed.
+    pub(crate) fn is_scoped(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.holds(name))
+    }
+
+    /// Remembers the previous value of a variable in the innermost scope holding it, if there is any.
+    pub(crate) fn shadow(&mut self, name: &str, previous: Option<AValue>) {
+        if let Some(scope) = self.scopes.iter_mut().rev().find(|scope| scope.holds(name)) {
+            scope.shadow(name, previous);
+        }
+    }
+
+    /// Closes the innermost scope restoring variables defined inside it.
+    pub(crate) fn close_scope(&mut self) {
+        if let Some(scope) = self.scopes.pop() {
+            scope.restore(&mut self.data_cache);
         }
     }
 }
@@ -65,7 +169,7 @@ impl<'i> ExecutionCtx<'i> {
 use std::fmt::Display;
 use std::fmt::Formatter;
 
-impl<'i> Display for ExecutionCtx<'i> {
+impl Display for ExecutionCtx {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "data cache:")?;
         for (key, value) in self.data_cache.iter() {
@@ -17,6 +17,7 @@
 use crate::execution::FoldState;
 use crate::JValue;
 use crate::ResolvedTriplet;
+use crate::SecurityTetraplet;
 
 use serde::Deserialize;
 use serde::Serialize;
@@ -30,15 +31,48 @@ use std::rc::Rc;
 pub struct ResolvedCallResult {
     pub result: Rc<JValue>,
     pub triplet: Rc<ResolvedTriplet>,
+    /// A json path applied to the output of the triplet to get this result, empty for the whole output.
+    pub json_path: String,
 }
 
-pub(crate) enum AValue<'i> {
+impl ResolvedCallResult {
+    /// Creates a result with the origin described by the tetraplet.
+    pub(crate) fn from_tetraplet(result: Rc<JValue>, tetraplet: SecurityTetraplet) -> Self {
+        Self {
+            result,
+            triplet: tetraplet.triplet,
+            json_path: tetraplet.json_path,
+        }
+    }
+
+    /// Returns a tetraplet describing the origin of this result.
+    pub(crate) fn tetraplet(&self) -> SecurityTetraplet {
+        SecurityTetraplet {
+            triplet: self.triplet.clone(),
+            json_path: self.json_path.clone(),
+        }
+    }
+
+    /// Returns a json path applied to the output of the triplet to get a value
+    /// selected from this result by the supplied json path.
+    pub(crate) fn source_json_path(&self, json_path: &str) -> String {
+        if self.json_path.is_empty() {
+            return json_path.to_string();
+        }
+
+        // both paths start from the root, so the root of the supplied one is skipped
+        let relative_path = json_path.strip_prefix('$').unwrap_or(json_path);
+        format!("{}{}", self.json_path, relative_path)
+    }
+}
+
+pub(crate) enum AValue {
     JValueRef(ResolvedCallResult),
     JValueAccumulatorRef(RefCell<Vec<ResolvedCallResult>>),
-    JValueFoldCursor(FoldState<'i>),
+    JValueFoldCursor(FoldState),
 }
 
-impl<'i> Display for AValue<'i> {
+impl Display for AValue {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             AValue::JValueRef(value) => write!(f, "{:?}", value)?,
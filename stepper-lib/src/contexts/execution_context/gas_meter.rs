@@ -0,0 +1,73 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::execution::ExecutionError;
+
+use stepper_interface::Gas;
+
+/// Counts gas consumed by an execution and checks that it doesn't exceed the limit.
+pub(crate) struct GasMeter {
+    limit: Gas,
+    used: Gas,
+}
+
+impl GasMeter {
+    pub(crate) fn new(limit: Gas) -> Self {
+        Self {
+            limit,
+            used: Gas::default(),
+        }
+    }
+
+    pub(crate) fn charge_instruction(&mut self) -> Result<(), ExecutionError> {
+        charge_one(&mut self.used.instructions, self.limit.instructions, "instructions")
+    }
+
+    pub(crate) fn charge_service_call(&mut self) -> Result<(), ExecutionError> {
+        charge_one(&mut self.used.service_calls, self.limit.service_calls, "service calls")
+    }
+
+    /// Trace states aren't charged one by one, because they are produced by different instructions,
+    /// so the count of states produced so far is checked instead.
+    pub(crate) fn check_trace_states(&mut self, produced_states_count: usize) -> Result<(), ExecutionError> {
+        let produced_states_count = produced_states_count as u64;
+        self.used.trace_states = produced_states_count;
+
+        if is_limited(self.limit.trace_states) && produced_states_count > self.limit.trace_states {
+            return Err(ExecutionError::GasExhausted("trace states", self.limit.trace_states));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn used(&self) -> Gas {
+        self.used
+    }
+}
+
+fn charge_one(used: &mut u64, limit: u64, kind: &'static str) -> Result<(), ExecutionError> {
+    if is_limited(limit) && *used >= limit {
+        return Err(ExecutionError::GasExhausted(kind, limit));
+    }
+
+    *used += 1;
+    Ok(())
+}
+
+/// A zero limit means that such work isn't limited.
+fn is_limited(limit: u64) -> bool {
+    limit != 0
+}
@@ -0,0 +1,63 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::ResolvedCallResult;
+use crate::ResolvedTriplet;
+
+use air_parser::ast::Span;
+use serde::Serialize;
+
+use std::rc::Rc;
+
+/// The innermost instruction that returned an error, it's set while the error is bubbling up
+/// and taken when the error is caught by xor.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct FailedInstruction {
+    pub span: Span,
+    /// Text representation of the instruction.
+    pub instruction: String,
+    /// Peer where the instruction failed: a called peer for calls and the init peer otherwise,
+    /// because other instructions fail the same way on all peers.
+    pub peer_id: String,
+}
+
+/// An error caught by the innermost xor, it's accessible by scripts through `%last_error%`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub(crate) struct LastError {
+    pub message: String,
+    pub error_code: i32,
+    pub instruction: String,
+    pub peer_id: String,
+}
+
+impl LastError {
+    /// Converts this error to a value with a literal tetraplet of the failed peer,
+    /// so json paths could be applied to it as to any other call result.
+    pub(crate) fn to_call_result(&self) -> ResolvedCallResult {
+        let result = serde_json::to_value(self).expect("default serializer shouldn't fail");
+        let triplet = ResolvedTriplet {
+            peer_pk: self.peer_id.clone(),
+            service_id: String::new(),
+            function_name: String::new(),
+        };
+
+        ResolvedCallResult {
+            result: Rc::new(result),
+            triplet: Rc::new(triplet),
+            json_path: String::new(),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::AValue;
+
+use std::collections::HashMap;
+
+/// A lexical scope opened by a fold or a new instruction. Variables defined inside it
+/// get back their previous values when the scope is closed.
+#[derive(Default)]
+pub(crate) struct Scope {
+    /// A variable declared by a new instruction, the scope of a fold has no declared variable
+    /// and holds all variables defined inside it.
+    declared: Option<String>,
+    /// Values variables had before they were defined in this scope,
+    /// None means that a variable wasn't defined at all.
+    shadowed: HashMap<String, Option<AValue>>,
+}
+
+impl Scope {
+    /// Creates a scope of a new instruction holding only the declared variable.
+    pub(crate) fn with_declared(name: &str, previous: Option<AValue>) -> Self {
+        let mut scope = Self {
+            declared: Some(name.to_string()),
+            shadowed: HashMap::new(),
+        };
+        scope.shadow(name, previous);
+
+        scope
+    }
+
+    /// Returns true if a variable defined inside this scope belongs to it.
+    pub(crate) fn holds(&self, name: &str) -> bool {
+        match &self.declared {
+            Some(declared) => declared == name,
+            None => true,
+        }
+    }
+
+    /// Remembers a value of a variable that is being defined in this scope. Only the first value
+    /// is remembered, because it's the one the variable had outside of the scope.
+    pub(crate) fn shadow(&mut self, name: &str, previous: Option<AValue>) {
+        self.shadowed.entry(name.to_string()).or_insert(previous);
+    }
+
+    /// Restores variables defined in this scope to their values outside of it.
+    pub(crate) fn restore(self, data_cache: &mut HashMap<String, AValue>) {
+        for (name, previous) in self.shadowed {
+            match previous {
+                Some(value) => data_cache.insert(name, value),
+                None => data_cache.remove(&name),
+            };
+        }
+    }
+}
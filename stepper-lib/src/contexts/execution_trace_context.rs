@@ -39,6 +39,9 @@ pub(crate) struct ExecutionTraceCtx {
     // TODO: consider change it to Vec for optimization
     /// Accumulator for resulted path produced by the stepper after execution.
     pub(crate) new_trace: ExecutionTrace,
+
+    /// Length of the trace merged from current and previous data before execution.
+    merged_trace_len: usize,
 }
 
 impl ExecutionTraceCtx {
@@ -51,8 +54,16 @@ impl ExecutionTraceCtx {
             current_trace,
             current_subtree_size,
             new_trace,
+            merged_trace_len: current_subtree_size,
         }
     }
+
+    /// Returns a count of states produced by this execution, states of the merged trace
+    /// are moved to the new one when their instructions are replayed, so they aren't counted.
+    pub fn produced_states_count(&self) -> usize {
+        let replayed_states_count = self.merged_trace_len - self.current_trace.len();
+        self.new_trace.len().saturating_sub(replayed_states_count)
+    }
 }
 
 impl Display for ExecutionTraceCtx {
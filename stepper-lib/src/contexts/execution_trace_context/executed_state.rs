@@ -38,6 +38,20 @@ pub enum CallResult {
 pub enum ExecutedState {
     Par(usize, usize),
     Call(CallResult),
+    /// Value an ap instruction's been executed with.
+    Ap(Rc<JValue>),
+    /// Error raised by a fail instruction.
+    Fail {
+        ret_code: i32,
+        message: String,
+    },
+    /// A call executed on every peer of an array followed by a state of the call on each of them.
+    FanOut(usize),
+    /// An invocation of a procedure followed by `size` states produced by its body.
+    Invoke {
+        name: String,
+        size: usize,
+    },
 }
 
 impl std::fmt::Display for ExecutedState {
@@ -50,6 +64,10 @@ impl std::fmt::Display for ExecutedState {
             Call(RequestSentBy(peer_id)) => write!(f, "RequestSentBy({})", peer_id),
             Call(Executed(result)) => write!(f, "Executed({:?})", result),
             Call(CallServiceFailed(err_msg)) => write!(f, "CallServiceFailed({})", err_msg),
+            Ap(result) => write!(f, "Ap({:?})", result),
+            Fail { ret_code, message } => write!(f, "Fail({}, {})", ret_code, message),
+            FanOut(calls_count) => write!(f, "FanOut({})", calls_count),
+            Invoke { name, size } => write!(f, "Invoke({}, {})", name, size),
         }
     }
 }
@@ -27,5 +27,7 @@ pub mod execution_trace {
 pub(crate) mod execution {
     pub(crate) use super::execution_context::AValue;
     pub(crate) use super::execution_context::ExecutionCtx;
+    pub(crate) use super::execution_context::FailedInstruction;
+    pub(crate) use super::execution_context::LastError;
     pub(crate) use super::execution_context::ResolvedCallResult;
 }
@@ -0,0 +1,223 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::call::set_local_result;
+use super::ExecutionCtx;
+use super::ExecutionError;
+use super::ExecutionResult;
+use super::ExecutionTraceCtx;
+use crate::contexts::execution::ResolvedCallResult;
+use crate::contexts::execution_trace::ExecutedState;
+use crate::execution::utils::resolve_to_args;
+use crate::joinable;
+use crate::log_instruction;
+use crate::log_targets::EXECUTED_STATE_CHANGING;
+use crate::JValue;
+use crate::SecurityTetraplet;
+
+use air_parser::ast::Ap;
+use air_parser::ast::CallArgValue;
+
+use std::rc::Rc;
+
+impl<'i> super::ExecutableInstruction<'i> for Ap<'i> {
+    fn execute(&self, exec_ctx: &mut ExecutionCtx, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
+        log_instruction!(ap, exec_ctx, trace_ctx);
+
+        let (result, tetraplet) = joinable!(resolve_value(&self.value, exec_ctx), exec_ctx)?;
+        // the result saved by a previous execution is preferred to keep it the same on all peers
+        let result = match prev_ap_result(trace_ctx)? {
+            Some(prev_result) => prev_result,
+            None => Rc::new(result),
+        };
+
+        let executed_result = ResolvedCallResult::from_tetraplet(result.clone(), tetraplet);
+        set_local_result(executed_result, &self.output, exec_ctx)?;
+
+        let new_executed_state = ExecutedState::Ap(result);
+        log::trace!(
+            target: EXECUTED_STATE_CHANGING,
+            "  adding new ap executed state {:?}",
+            new_executed_state
+        );
+        trace_ctx.new_trace.push_back(new_executed_state);
+
+        Ok(())
+    }
+}
+
+/// Resolves a value together with a tetraplet of its source, a value composed from several sources
+/// (e.g. a json path applied to an accumulator) gets a tetraplet of a literal like other values defined by the script.
+pub(super) fn resolve_value<'i>(
+    value: &CallArgValue<'i>,
+    exec_ctx: &ExecutionCtx,
+) -> ExecutionResult<(JValue, SecurityTetraplet)> {
+    let (result, mut tetraplets) = resolve_to_args(value, exec_ctx)?;
+
+    let tetraplet = match tetraplets.as_slice() {
+        [first, rest @ ..] if rest.iter().all(|tetraplet| tetraplet == first) => tetraplets.swap_remove(0),
+        _ => SecurityTetraplet::literal_tetraplet(exec_ctx.init_peer_id.clone()),
+    };
+
+    Ok((result, tetraplet))
+}
+
+/// Returns a result of this ap from the previous executed trace, if it's been executed before.
+fn prev_ap_result(trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<Option<Rc<JValue>>> {
+    if trace_ctx.current_subtree_size == 0 {
+        log::trace!(
+            target: EXECUTED_STATE_CHANGING,
+            "  previous executed trace state wasn't found"
+        );
+        return Ok(None);
+    }
+
+    trace_ctx.current_subtree_size -= 1;
+    // unwrap is safe here, because current_subtree_size depends on current_path len,
+    // and it's been checked previously
+    let prev_state = trace_ctx.current_trace.pop_front().unwrap();
+
+    log::trace!(
+        target: EXECUTED_STATE_CHANGING,
+        "  previous executed trace found {:?}",
+        prev_state
+    );
+
+    match prev_state {
+        ExecutedState::Ap(result) => Ok(Some(result)),
+        state => Err(ExecutionError::InvalidExecutedState(String::from("ap"), state)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contexts::execution_trace::ExecutionTrace;
+    use crate::JValue;
+
+    use aqua_test_utils::call_vm;
+    use aqua_test_utils::create_aqua_vm;
+    use aqua_test_utils::echo_string_call_service;
+    use aqua_test_utils::set_variable_call_service;
+
+    use serde_json::json;
+
+    use std::rc::Rc;
+
+    // Check that ap sets scalars and accumulators locally and records its results to the trace.
+    #[test]
+    fn ap_sets_variables() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let vm_peer_id = String::from("A");
+        let mut vm = create_aqua_vm(set_variable_call_service(r#"{"field": "value"}"#), vm_peer_id.clone());
+
+        let script = format!(
+            r#"
+            (seq
+                (call "{0}" ("service" "function") [] result)
+                (seq
+                    (ap result.$.field value)
+                    (seq
+                        (ap value acc[])
+                        (seq
+                            (ap "literal" acc[])
+                            (call "{0}" ("service" "function") [acc] acc_result)
+                        )
+                    )
+                )
+            )
+            "#,
+            vm_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "[]", "[]");
+        let trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be a valid json");
+
+        let expected_trace = vec![
+            Call(Executed(Rc::new(json!({"field": "value"})))),
+            Ap(Rc::new(json!(["value"]))),
+            Ap(Rc::new(json!(["value"]))),
+            Ap(Rc::new(JValue::String(String::from("literal")))),
+            Call(Executed(Rc::new(json!({"field": "value"})))),
+        ];
+        assert_eq!(trace, expected_trace);
+        assert!(res.next_peer_pks.is_empty());
+    }
+
+    // Check that ap waits for its value and then is replayed from the trace on other peers.
+    #[test]
+    fn ap_with_remote_value() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let mut vm_a = create_aqua_vm(echo_string_call_service(), "A");
+        let mut vm_b = create_aqua_vm(echo_string_call_service(), "B");
+
+        let script = r#"
+            (seq
+                (call "B" ("service" "function") ["value"] result)
+                (seq
+                    (ap result copy)
+                    (call "A" ("service" "function") [copy] copy_result)
+                )
+            )
+            "#;
+
+        let res = call_vm!(vm_a, "asd", script, "[]", "[]");
+        let trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be a valid json");
+        assert_eq!(trace, vec![Call(RequestSentBy(String::from("A")))]);
+        assert_eq!(res.next_peer_pks, vec![String::from("B")]);
+
+        let res = call_vm!(vm_b, "asd", script, "[]", res.data);
+        let trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be a valid json");
+        let value = Rc::new(JValue::String(String::from("value")));
+        assert_eq!(
+            trace,
+            vec![
+                Call(Executed(value.clone())),
+                Ap(value.clone()),
+                Call(RequestSentBy(String::from("B")))
+            ]
+        );
+        assert_eq!(res.next_peer_pks, vec![String::from("A")]);
+
+        let res = call_vm!(vm_a, "asd", script, "[]", res.data);
+        let trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be a valid json");
+        assert_eq!(
+            trace,
+            vec![Call(Executed(value.clone())), Ap(value.clone()), Call(Executed(value))]
+        );
+        assert!(res.next_peer_pks.is_empty());
+    }
+
+    // Check that ap can't be executed with a state of another instruction.
+    #[test]
+    fn ap_with_invalid_state() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let mut vm = create_aqua_vm(echo_string_call_service(), "A");
+
+        let script = r#"(ap "value" result)"#;
+        let prev_trace = vec![Call(Executed(Rc::new(JValue::String(String::from("value")))))];
+        let prev_trace = serde_json::to_string(&prev_trace).unwrap();
+
+        let res = call_vm!(vm, "asd", script, "[]", prev_trace);
+        // InvalidExecutedState
+        assert_eq!(res.ret_code, 1013);
+    }
+}
@@ -14,68 +14,55 @@
  * limitations under the License.
  */
 
+mod fan_out;
 mod resolved_call;
 mod triplet;
 mod utils;
 
+pub(super) use utils::set_local_result;
+
 use resolved_call::ResolvedCall;
 
 use super::ExecutionCtx;
 use super::ExecutionError;
 use super::ExecutionResult;
 use super::ExecutionTraceCtx;
+use super::FailedInstruction;
+use crate::joinable;
 use crate::log_instruction;
 
 use air_parser::ast::Call;
 
-/// This macro converts joinable errors to Ok and sets subtree complete to true.
-macro_rules! joinable {
-    ($cmd:expr, $exec_ctx:expr) => {
-        match $cmd {
-            Err(e) if is_joinable_error_type(&e) => {
-                $exec_ctx.subtree_complete = false;
-                return Ok(());
-            }
-            v => v,
-        }
-    };
-}
-
 impl<'i> super::ExecutableInstruction<'i> for Call<'i> {
-    fn execute(&self, exec_ctx: &mut ExecutionCtx<'i>, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
+    fn execute(&self, exec_ctx: &mut ExecutionCtx, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
         log_instruction!(call, exec_ctx, trace_ctx);
 
+        if self.peer_part.is_fan_out() {
+            return fan_out::execute_fan_out(self, exec_ctx, trace_ctx);
+        }
+
         let resolved_call = joinable!(ResolvedCall::new(self, exec_ctx), exec_ctx)?;
-        joinable!(resolved_call.execute(exec_ctx, trace_ctx), exec_ctx)
-    }
-}
+        let peer_pk = resolved_call.peer_pk().to_string();
+
+        let result = joinable!(resolved_call.execute(exec_ctx, trace_ctx), exec_ctx);
+        if result.is_err() {
+            set_failed_call(self, peer_pk, exec_ctx);
+        }
 
-macro_rules! log_join {
-    ($($args:tt)*) => {
-        log::trace!(target: crate::log_targets::JOIN_BEHAVIOUR, $($args)*)
+        result
     }
 }
 
-/// Returns true, if supplied error is related to variable not found errors type.
-/// Print log if this is joinable error type.
-#[rustfmt::skip::macros(log_join)]
-fn is_joinable_error_type(exec_error: &ExecutionError) -> bool {
-    use ExecutionError::*;
-
-    match exec_error {
-        VariableNotFound(var_name) => {
-            log_join!("  call is waiting for an argument with name '{}'", var_name);
-            true
-        }
-        JValueJsonPathError(value, json_path, _) => {
-            log_join!("  call is waiting for an argument with path '{}' on jvalue '{:?}'", json_path, value);
-            true
-        }
-        JValueAccJsonPathError(acc, json_path, _) => {
-            log_join!("  call is waiting for an argument with path '{}' on accumulator '{:?}'", json_path, acc);
-            true
-        }
-        _ => false,
+/// A resolved call fails on the called peer, this is the one reported by %last_error%.
+fn set_failed_call(call: &Call<'_>, peer_pk: String, exec_ctx: &mut ExecutionCtx) {
+    if exec_ctx.failed_instruction.is_none() {
+        // arguments are resolved while executing, so an argument could fail as well
+        let span = exec_ctx.failed_value.take().unwrap_or(call.span);
+        exec_ctx.failed_instruction = Some(FailedInstruction {
+            span,
+            instruction: call.to_string(),
+            peer_id: peer_pk,
+        });
     }
 }
 
@@ -93,6 +80,7 @@ mod tests {
     use aqua_test_utils::IValue;
     use aqua_test_utils::NEVec;
 
+    use serde_json::json;
     use std::rc::Rc;
 
     // Check that %init_peer_id% alias works correctly (by comparing result with it and explicit peer id).
@@ -232,4 +220,237 @@ mod tests {
             ]))))
         );
     }
+
+    // Check that number, boolean, null and json literals are passed to a service with their json types.
+    #[test]
+    fn typed_parameters() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let call_service: CallServiceClosure = Box::new(|_, args| -> Option<IValue> {
+            let arg = match &args[2] {
+                IValue::String(str) => str,
+                _ => unreachable!(),
+            };
+
+            Some(IValue::Record(
+                NEVec::new(vec![IValue::S32(0), IValue::String(arg.clone())]).unwrap(),
+            ))
+        });
+
+        let vm_peer_id = String::from("A");
+        let mut vm = create_aqua_vm(call_service, vm_peer_id.clone());
+
+        let script = format!(
+            r#"(call "{}" ("some_service_id" "local_fn_name") [1 -2.5 true null ["a" []] {{"b": 1}}] result)"#,
+            vm_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "[]", "[]");
+        let call_path: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be a valid json");
+
+        let expected_args = serde_json::json!([1, -2.5, true, null, ["a", []], {"b": 1}]);
+        assert_eq!(call_path.len(), 1);
+        assert_eq!(call_path[0], Call(Executed(Rc::new(expected_args))));
+    }
+
+    #[test]
+    fn builtin_functions() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let local_peer_id = "local_peer_id";
+        // builtins don't reach the host, which returns an error for every call
+        let call_service: CallServiceClosure = Box::new(|_, _| -> Option<IValue> {
+            Some(IValue::Record(
+                NEVec::new(vec![IValue::S32(1), IValue::String(String::from("host is called"))]).unwrap(),
+            ))
+        });
+        let mut vm = create_aqua_vm(call_service, local_peer_id);
+
+        let script = format!(
+            r#"
+            (seq
+                (seq
+                    (seq
+                        (call "{0}" ("%builtin%" "identity") [["a" "b"]] array)
+                        (call "{0}" ("%builtin%" "concat") [array ["c"]] concatenated)
+                    )
+                    (seq
+                        (call "{0}" ("%builtin%" "length") [concatenated] length)
+                        (call "{0}" ("%builtin%" "element") [concatenated 2] element)
+                    )
+                )
+                (seq
+                    (call "{0}" ("%builtin%" "join") [concatenated "-"] joined)
+                    (call "{0}" ("%builtin%" "set_field") [{{"a": 1}} "b" joined] object)
+                )
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "", "");
+        assert_eq!(res.ret_code, 0);
+
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        let expected_trace = vec![
+            Call(Executed(Rc::new(json!(["a", "b"])))),
+            Call(Executed(Rc::new(json!(["a", "b", "c"])))),
+            Call(Executed(Rc::new(json!(3)))),
+            Call(Executed(Rc::new(json!("c")))),
+            Call(Executed(Rc::new(json!("a-b-c")))),
+            Call(Executed(Rc::new(json!({"a": 1, "b": "a-b-c"})))),
+        ];
+
+        assert_eq!(actual_trace, expected_trace);
+    }
+
+    #[test]
+    fn builtin_function_error() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(unit_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (xor
+                (call "{0}" ("%builtin%" "element") [["a"] 1] element)
+                (call "{0}" ("%builtin%" "identity") [%last_error%.$.message] message)
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "", "");
+        assert_eq!(res.ret_code, 0);
+
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        let expected_message = "element index 1 is out of bounds of an array with length 1";
+
+        assert_eq!(actual_trace.len(), 2);
+        assert_eq!(actual_trace[0], Call(CallServiceFailed(String::from(expected_message))));
+        assert_eq!(actual_trace[1], Call(Executed(Rc::new(json!([expected_message])))));
+    }
+
+    // Check that a call on an array of peers is sent to all of them and responses are merged in any order.
+    #[test]
+    fn call_on_array_of_peers() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let mut vm_a = create_aqua_vm(echo_string_call_service(), "A");
+        let mut vm_b = create_aqua_vm(echo_string_call_service(), "B");
+        let mut vm_c = create_aqua_vm(echo_string_call_service(), "C");
+
+        let script = r#"
+            (seq
+                (ap ["A" "B" "C"] peers)
+                (call [peers] ("service" "function") [%current_peer_id%] results[])
+            )"#;
+
+        let res_a = call_vm!(vm_a, "asd", script, "", "");
+        assert_eq!(res_a.ret_code, 0);
+        assert_eq!(res_a.next_peer_pks, vec![String::from("B"), String::from("C")]);
+
+        let res_b = call_vm!(vm_b, "asd", script, "", res_a.data.clone());
+        let res_c = call_vm!(vm_c, "asd", script, "", res_a.data.clone());
+        assert!(res_b.next_peer_pks.is_empty());
+        assert!(res_c.next_peer_pks.is_empty());
+
+        let executed = |peer: &str| Call(Executed(Rc::new(JValue::String(String::from(peer)))));
+        let expected_trace = vec![
+            Ap(Rc::new(json!(["A", "B", "C"]))),
+            FanOut(3),
+            executed("A"),
+            executed("B"),
+            executed("C"),
+        ];
+
+        // the first response to come back doesn't matter
+        let res_c_first = call_vm!(vm_a, "asd", script, res_a.data.clone(), res_c.data.clone());
+        let res_c_first = call_vm!(vm_a, "asd", script, res_c_first.data, res_b.data.clone());
+        let res_b_first = call_vm!(vm_a, "asd", script, res_a.data, res_b.data);
+        let res_b_first = call_vm!(vm_a, "asd", script, res_b_first.data, res_c.data);
+        assert_eq!(res_c_first.data, res_b_first.data);
+
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res_c_first.data).expect("should be valid json");
+        assert_eq!(actual_trace, expected_trace);
+    }
+
+    #[test]
+    fn call_on_peers_selected_by_json_path() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (seq
+                (ap {{"peers": ["{0}", "{0}"]}} object)
+                (seq
+                    (call [object.$.peers[*]] ("service" "function") ["first"] results[])
+                    (call ([object.$.peers[0]] "service") "function" ["second"])
+                )
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "", "");
+        assert_eq!(res.ret_code, 0);
+
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        let executed = |value: &str| Call(Executed(Rc::new(JValue::String(String::from(value)))));
+        let expected_trace = vec![
+            Ap(Rc::new(json!({"peers": [local_peer_id, local_peer_id]}))),
+            FanOut(2),
+            executed("first"),
+            executed("first"),
+            FanOut(1),
+            executed("second"),
+        ];
+
+        assert_eq!(actual_trace, expected_trace);
+    }
+
+    #[test]
+    fn call_on_json_path_selecting_array_rejected() {
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        // a json path should select peer ids themselves, a selected array isn't flattened
+        let script = format!(
+            r#"
+            (seq
+                (ap {{"peers": ["{0}"]}} object)
+                (call [object.$.peers] ("service" "function") [] results[])
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "", "");
+        assert_eq!(res.ret_code, 1008);
+    }
+
+    #[test]
+    fn error_points_at_failed_argument() {
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (seq
+                (ap {{"field": 1}} object)
+                (call "{0}" (object.$.field "function") [])
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "", "");
+        assert_eq!(res.ret_code, 1008);
+        assert!(res.error_message.contains("--> 4:40"));
+        assert!(res.error_message.contains("| object.$.field"));
+    }
 }
@@ -0,0 +1,120 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::resolved_call::ResolvedCall;
+use super::set_failed_call;
+use super::ExecutionCtx;
+use super::ExecutionError;
+use super::ExecutionResult;
+use super::ExecutionTraceCtx;
+use crate::contexts::execution_trace::CallResult;
+use crate::contexts::execution_trace::ExecutedState;
+use crate::execution::air::joinable::is_joinable_error_type;
+use crate::joinable;
+use crate::log_targets::EXECUTED_STATE_CHANGING;
+
+use air_parser::ast::Call;
+use air_parser::ast::CallOutputValue;
+
+/// Executes a call on every peer of an array. States of these calls follow a FanOut state holding
+/// their count, so a state of each peer has the same position in traces of all peers,
+/// and they are merged regardless of the order in which the peers respond.
+pub(super) fn execute_fan_out(
+    call: &Call<'_>,
+    exec_ctx: &mut ExecutionCtx,
+    trace_ctx: &mut ExecutionTraceCtx,
+) -> ExecutionResult<()> {
+    use ExecutionError::LocalServiceError;
+
+    if let CallOutputValue::Scalar(name) = &call.output {
+        return Err(ExecutionError::InstructionError(format!(
+            "results of a call on several peers could be saved only to an accumulator, not to '{}'",
+            name
+        )));
+    }
+
+    let resolved_calls = joinable!(ResolvedCall::fan_out(call, exec_ctx), exec_ctx)?;
+    let calls_count = resolved_calls.len();
+
+    let prev_calls_count = prev_fan_out_size(calls_count, trace_ctx)?;
+    trace_ctx.new_trace.push_back(ExecutedState::FanOut(calls_count));
+    let before_subtree_size = trace_ctx.current_subtree_size;
+    trace_ctx.current_subtree_size = prev_calls_count;
+
+    let mut subtree_complete = true;
+    let mut service_error = None;
+    for resolved_call in resolved_calls {
+        exec_ctx.subtree_complete = true;
+        let peer_pk = resolved_call.peer_pk().to_string();
+
+        match resolved_call.execute(exec_ctx, trace_ctx) {
+            Ok(()) => {}
+            Err(e) if is_joinable_error_type(&e) => {
+                // the call keeps its position and will be executed by this peer when arguments are set
+                let state = ExecutedState::Call(CallResult::RequestSentBy(exec_ctx.current_peer_id.clone()));
+                trace_ctx.new_trace.push_back(state);
+                exec_ctx.subtree_complete = false;
+            }
+            // calls on the other peers are still executed to fill the whole region
+            Err(e @ LocalServiceError(_)) => {
+                set_failed_call(call, peer_pk, exec_ctx);
+                service_error = service_error.or(Some(e));
+            }
+            Err(e) => {
+                set_failed_call(call, peer_pk, exec_ctx);
+                trace_ctx.current_subtree_size = before_subtree_size - prev_calls_count;
+                return Err(e);
+            }
+        }
+
+        subtree_complete &= exec_ctx.subtree_complete;
+    }
+
+    trace_ctx.current_subtree_size = before_subtree_size - prev_calls_count;
+    // the fan out is completed only when all the calls are completed
+    exec_ctx.subtree_complete = subtree_complete;
+
+    match service_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Returns the number of call states of this fan out in the previous trace, it must be the same
+/// as the number of peers, because the array of peers is resolved from the same data on all peers.
+fn prev_fan_out_size(calls_count: usize, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<usize> {
+    if trace_ctx.current_subtree_size == 0 {
+        return Ok(0);
+    }
+
+    trace_ctx.current_subtree_size -= 1;
+    // unwrap is safe here because of length's been checked
+    let prev_state = trace_ctx.current_trace.pop_front().unwrap();
+
+    log::trace!(
+        target: EXECUTED_STATE_CHANGING,
+        "  previous fan out executed state was found {:?}",
+        prev_state
+    );
+
+    match prev_state {
+        ExecutedState::FanOut(prev_calls_count) if prev_calls_count == calls_count => Ok(prev_calls_count),
+        state => Err(ExecutionError::InvalidExecutedState(
+            format!("fan out of {} calls", calls_count),
+            state,
+        )),
+    }
+}
@@ -23,6 +23,7 @@ use super::ExecutionCtx;
 use super::ExecutionError;
 use super::ExecutionResult;
 use crate::build_targets::CALL_SERVICE_SUCCESS;
+use crate::builtins::BuiltinFunction;
 use crate::contexts::execution_trace::*;
 use crate::log_targets::EXECUTED_STATE_CHANGING;
 use crate::JValue;
@@ -34,22 +35,22 @@ use air_parser::ast::{CallArgValue, CallOutputValue};
 use std::rc::Rc;
 
 /// Represents Call instruction with resolved internal parts.
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub(super) struct ResolvedCall<'i> {
     triplet: Rc<ResolvedTriplet>,
     function_arg_paths: Rc<Vec<CallArgValue<'i>>>,
     output: CallOutputValue<'i>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 struct ResolvedArguments {
-    call_arguments: String,
+    call_arguments: Vec<JValue>,
     tetraplets: Vec<Vec<SecurityTetraplet>>,
 }
 
 impl<'i> ResolvedCall<'i> {
     /// Build `ResolvedCall` from `Call` by transforming `PeerPart` & `FunctionPart` into `ResolvedTriplet`.
-    pub(super) fn new(raw_call: &Call<'i>, exec_ctx: &ExecutionCtx<'i>) -> ExecutionResult<Self> {
+    pub(super) fn new(raw_call: &Call<'i>, exec_ctx: &ExecutionCtx) -> ExecutionResult<Self> {
         let triplet = Triplet::try_from(&raw_call.peer_part, &raw_call.function_part)?;
         let triplet = triplet.resolve(exec_ctx)?;
         let triplet = Rc::new(triplet);
@@ -61,12 +62,30 @@ impl<'i> ResolvedCall<'i> {
         })
     }
 
+    /// Build a `ResolvedCall` for each peer of a `Call` with an array of peers in `PeerPart`.
+    pub(super) fn fan_out(raw_call: &Call<'i>, exec_ctx: &ExecutionCtx) -> ExecutionResult<Vec<Self>> {
+        let triplet = Triplet::try_from(&raw_call.peer_part, &raw_call.function_part)?;
+        let triplets = triplet.resolve_fan_out(exec_ctx)?;
+
+        let calls = triplets
+            .into_iter()
+            .map(|triplet| Self {
+                triplet: Rc::new(triplet),
+                function_arg_paths: raw_call.args.clone(),
+                output: raw_call.output.clone(),
+            })
+            .collect();
+
+        Ok(calls)
+    }
+
+    /// Returns a peer where this call should be executed.
+    pub(super) fn peer_pk(&self) -> &str {
+        &self.triplet.peer_pk
+    }
+
     /// Executes resolved instruction, updates contexts based on a execution result.
-    pub(super) fn execute(
-        self,
-        exec_ctx: &mut ExecutionCtx<'i>,
-        trace_ctx: &mut ExecutionTraceCtx,
-    ) -> ExecutionResult<()> {
+    pub(super) fn execute(self, exec_ctx: &mut ExecutionCtx, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
         use CallResult::*;
         use ExecutedState::Call;
         use ExecutionError::CallServiceResultDeError as DeError;
@@ -88,6 +107,17 @@ impl<'i> ResolvedCall<'i> {
             tetraplets,
         } = self.resolve_args(exec_ctx)?;
 
+        let builtin = exec_ctx
+            .builtins
+            .get(&self.triplet.service_id, &self.triplet.function_name)
+            .cloned();
+        if let Some(builtin) = builtin {
+            return self.execute_builtin(builtin, &call_arguments, exec_ctx, trace_ctx);
+        }
+
+        exec_ctx.gas.charge_service_call()?;
+
+        let call_arguments = JValue::Array(call_arguments).to_string();
         let tetraplets = serde_json::to_string(&tetraplets).expect("default serializer shouldn't fail");
 
         let service_result = unsafe {
@@ -109,7 +139,40 @@ impl<'i> ResolvedCall<'i> {
         }
 
         let result: JValue = serde_json::from_str(&service_result.result).map_err(|e| DeError(service_result, e))?;
-        let result = Rc::new(result);
+
+        self.set_result(Rc::new(result), exec_ctx, trace_ctx)
+    }
+
+    /// Executes a built-in function inside the interpreter, it behaves as a service call
+    /// both on success and on error.
+    fn execute_builtin(
+        &self,
+        builtin: BuiltinFunction,
+        call_arguments: &[JValue],
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> ExecutionResult<()> {
+        use CallResult::*;
+        use ExecutedState::Call;
+
+        match builtin(call_arguments) {
+            Ok(result) => self.set_result(Rc::new(result), exec_ctx, trace_ctx),
+            Err(message) => {
+                trace_ctx.new_trace.push_back(Call(CallServiceFailed(message.clone())));
+                Err(ExecutionError::LocalServiceError(message))
+            }
+        }
+    }
+
+    /// Saves a result of a local call to the output and to the new executed trace.
+    fn set_result(
+        &self,
+        result: Rc<JValue>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> ExecutionResult<()> {
+        use CallResult::*;
+        use ExecutedState::Call;
 
         set_local_call_result(result.clone(), self.triplet.clone(), &self.output, exec_ctx)?;
         let new_executed_state = Call(Executed(result));
@@ -128,7 +191,7 @@ impl<'i> ResolvedCall<'i> {
     /// Determine whether this call should be really called and adjust prev executed trace accordingly.
     fn prepare_executed_state(
         &self,
-        exec_ctx: &mut ExecutionCtx<'i>,
+        exec_ctx: &mut ExecutionCtx,
         trace_ctx: &mut ExecutionTraceCtx,
     ) -> ExecutionResult<bool> {
         if trace_ctx.current_subtree_size == 0 {
@@ -154,7 +217,7 @@ impl<'i> ResolvedCall<'i> {
     }
 
     /// Prepare arguments of this call instruction by resolving and preparing their security tetraplets.
-    fn resolve_args(&self, exec_ctx: &ExecutionCtx<'i>) -> ExecutionResult<ResolvedArguments> {
+    fn resolve_args(&self, exec_ctx: &ExecutionCtx) -> ExecutionResult<ResolvedArguments> {
         use crate::execution::utils::resolve_to_args;
 
         let function_args = self.function_arg_paths.iter();
@@ -166,9 +229,6 @@ impl<'i> ResolvedCall<'i> {
             tetraplets.push(tetraplet);
         }
 
-        let call_arguments = JValue::Array(call_arguments);
-        let call_arguments = call_arguments.to_string();
-
         let resolved_arguments = ResolvedArguments {
             call_arguments,
             tetraplets,
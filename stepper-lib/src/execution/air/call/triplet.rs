@@ -36,15 +36,18 @@ impl<'a, 'i> Triplet<'a, 'i> {
         use air_parser::ast::FunctionPart::*;
         use air_parser::ast::PeerPart::*;
 
-        let (peer_pk, service_id, function_name) = match (peer, f) {
-            (PeerPkWithServiceId(peer_pk, _peer_service_id), ServiceIdWithFuncName(service_id, func_name)) => {
-                Ok((peer_pk, service_id, func_name))
+        // for a call on several peers, peer_pk is an array of them
+        let (peer_pk, peer_service_id) = match peer {
+            PeerPk(peer_pk) | PeerPks(peer_pk) => (peer_pk, None),
+            PeerPkWithServiceId(peer_pk, service_id) | PeerPksWithServiceId(peer_pk, service_id) => {
+                (peer_pk, Some(service_id))
             }
-            (PeerPkWithServiceId(peer_pk, peer_service_id), FuncName(func_name)) => {
-                Ok((peer_pk, peer_service_id, func_name))
-            }
-            (PeerPk(peer_pk), ServiceIdWithFuncName(service_id, func_name)) => Ok((peer_pk, service_id, func_name)),
-            (PeerPk(_), FuncName(_)) => Err(ExecutionError::InstructionError(String::from(
+        };
+
+        let (service_id, function_name) = match (peer_service_id, f) {
+            (_, ServiceIdWithFuncName(service_id, func_name)) => Ok((service_id, func_name)),
+            (Some(peer_service_id), FuncName(func_name)) => Ok((peer_service_id, func_name)),
+            (None, FuncName(_)) => Err(ExecutionError::InstructionError(String::from(
                 "call should have service id specified by peer part or function part",
             ))),
         }?;
@@ -57,15 +60,15 @@ impl<'a, 'i> Triplet<'a, 'i> {
     }
 
     /// Resolve variables, literals, etc in the `Triplet`, and build a `ResolvedTriplet`.
-    pub fn resolve(self, ctx: &ExecutionCtx<'i>) -> ExecutionResult<ResolvedTriplet> {
+    pub fn resolve(self, ctx: &ExecutionCtx) -> ExecutionResult<ResolvedTriplet> {
         let Triplet {
             peer_pk,
             service_id,
             function_name,
         } = self;
-        let peer_pk = resolve_to_string(peer_pk, ctx)?;
-        let service_id = resolve_to_string(service_id, ctx)?;
-        let function_name = resolve_to_string(function_name, ctx)?;
+        let peer_pk = ctx.point_at_value(peer_pk.span(), resolve_to_string(peer_pk, ctx))?;
+        let service_id = ctx.point_at_value(service_id.span(), resolve_to_string(service_id, ctx))?;
+        let function_name = ctx.point_at_value(function_name.span(), resolve_to_string(function_name, ctx))?;
 
         Ok(ResolvedTriplet {
             peer_pk,
@@ -73,31 +76,85 @@ impl<'a, 'i> Triplet<'a, 'i> {
             function_name,
         })
     }
+
+    /// Resolve the `Triplet` of a call on several peers to a `ResolvedTriplet` for each of them.
+    pub fn resolve_fan_out(self, ctx: &ExecutionCtx) -> ExecutionResult<Vec<ResolvedTriplet>> {
+        let Triplet {
+            peer_pk,
+            service_id,
+            function_name,
+        } = self;
+        let peer_pks = ctx.point_at_value(peer_pk.span(), resolve_to_strings(peer_pk, ctx))?;
+        let service_id = ctx.point_at_value(service_id.span(), resolve_to_string(service_id, ctx))?;
+        let function_name = ctx.point_at_value(function_name.span(), resolve_to_string(function_name, ctx))?;
+
+        let triplets = peer_pks
+            .into_iter()
+            .map(|peer_pk| ResolvedTriplet {
+                peer_pk,
+                service_id: service_id.clone(),
+                function_name: function_name.clone(),
+            })
+            .collect();
+
+        Ok(triplets)
+    }
 }
 
 /// Resolve value to string by either resolving variable from `ExecutionCtx`, taking literal value, or etc.
 // TODO: return Rc<String> to avoid excess cloning
-fn resolve_to_string<'i>(value: &CallArgValue<'i>, ctx: &ExecutionCtx<'i>) -> ExecutionResult<String> {
+fn resolve_to_string<'i>(value: &CallArgValue<'i>, ctx: &ExecutionCtx) -> ExecutionResult<String> {
+    use crate::execution::utils::resolve_to_args;
     use crate::execution::utils::resolve_to_jvaluable;
 
     let resolved = match value {
         CallArgValue::InitPeerId => ctx.init_peer_id.clone(),
+        CallArgValue::CurrentPeerId => ctx.current_peer_id.clone(),
+        CallArgValue::ParticleId => ctx.particle_id.clone(),
         CallArgValue::Literal(value) => value.to_string(),
-        CallArgValue::Variable(name) => {
+        CallArgValue::Variable(name, _) => {
             let resolved = resolve_to_jvaluable(name, ctx)?;
             let jvalue = resolved.into_jvalue();
             jvalue_to_string(jvalue)?
         }
-        CallArgValue::JsonPath { variable, path } => {
+        CallArgValue::JsonPath { variable, path, .. } => {
             let resolved = resolve_to_jvaluable(variable, ctx)?;
             let resolved = resolved.apply_json_path(path)?;
-            vec_to_string(resolved, path)?
+            vec_to_string(resolved, path.as_str())?
+        }
+        // the parser allows only string values in a triplet, but they still could be constructed manually
+        CallArgValue::Number(value) => jvalue_to_string(JValue::Number(value.clone()))?,
+        CallArgValue::Boolean(value) => jvalue_to_string(JValue::Bool(*value))?,
+        CallArgValue::Null => jvalue_to_string(JValue::Null)?,
+        CallArgValue::Timestamp => jvalue_to_string(JValue::from(ctx.timestamp))?,
+        CallArgValue::Ttl => jvalue_to_string(JValue::from(ctx.ttl))?,
+        CallArgValue::Json(value) => jvalue_to_string(value.clone())?,
+        CallArgValue::LastError(path) => {
+            let (jvalue, _) = resolve_to_args(value, ctx)?;
+            match (path, jvalue) {
+                (Some(path), JValue::Array(values)) => vec_to_string(values.iter().collect(), path.as_str())?,
+                (_, jvalue) => jvalue_to_string(jvalue)?,
+            }
         }
     };
 
     Ok(resolved)
 }
 
+/// Resolve value to an array of strings, a json path is resolved to all values selected by it.
+fn resolve_to_strings<'i>(value: &CallArgValue<'i>, ctx: &ExecutionCtx) -> ExecutionResult<Vec<String>> {
+    use crate::execution::utils::resolve_to_args;
+    use ExecutionError::IncompatibleJValueType;
+
+    let (jvalue, _) = resolve_to_args(value, ctx)?;
+    let values = match jvalue {
+        JValue::Array(values) => values,
+        jvalue => return Err(IncompatibleJValueType(jvalue, "array")),
+    };
+
+    values.into_iter().map(jvalue_to_string).collect()
+}
+
 fn jvalue_to_string(jvalue: JValue) -> ExecutionResult<String> {
     use ExecutionError::IncompatibleJValueType;
 
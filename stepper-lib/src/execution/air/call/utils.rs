@@ -28,38 +28,43 @@ use polyplets::ResolvedTriplet;
 use std::rc::Rc;
 
 /// Writes result of a local `Call` instruction to `ExecutionCtx` at `output`.
-pub(super) fn set_local_call_result<'i>(
+pub(crate) fn set_local_call_result<'i>(
     result: Rc<JValue>,
     triplet: Rc<ResolvedTriplet>,
     output: &CallOutputValue<'i>,
-    exec_ctx: &mut ExecutionCtx<'i>,
+    exec_ctx: &mut ExecutionCtx,
+) -> ExecutionResult<()> {
+    let executed_result = ResolvedCallResult {
+        result,
+        triplet,
+        json_path: String::new(),
+    };
+
+    set_local_result(executed_result, output, exec_ctx)
+}
+
+/// Writes a value to `ExecutionCtx` at `output`, e.g. a result of `Ap`, keeping the whole tetraplet of its source.
+pub(crate) fn set_local_result<'i>(
+    executed_result: ResolvedCallResult,
+    output: &CallOutputValue<'i>,
+    exec_ctx: &mut ExecutionCtx,
 ) -> ExecutionResult<()> {
     use crate::contexts::execution::AValue;
     use std::cell::RefCell;
     use std::collections::hash_map::Entry::{Occupied, Vacant};
     use ExecutionError::*;
 
-    let executed_result = ResolvedCallResult { result, triplet };
-
     match output {
         CallOutputValue::Scalar(name) => {
-            if let Some(fold_block_name) = exec_ctx.met_folds.back() {
-                let fold_state = match exec_ctx.data_cache.get_mut(*fold_block_name) {
-                    Some(AValue::JValueFoldCursor(fold_state)) => fold_state,
-                    _ => unreachable!("fold block data must be represented as fold cursor"),
-                };
-
-                fold_state.met_variables.insert(name, executed_result.clone());
-            }
-
-            match exec_ctx.data_cache.entry(name.to_string()) {
+            // shadowing is allowed only inside folds and news declaring the variable
+            let is_scoped = exec_ctx.is_scoped(name);
+            let previous = match exec_ctx.data_cache.entry(name.to_string()) {
                 Vacant(entry) => {
                     entry.insert(AValue::JValueRef(executed_result));
+                    None
                 }
                 Occupied(mut entry) => {
-                    // check that current execution flow is inside a fold block
-                    if exec_ctx.met_folds.is_empty() {
-                        // shadowing is allowed only inside fold blocks
+                    if !is_scoped {
                         return Err(MultipleVariablesFound(entry.key().clone()));
                     }
 
@@ -69,9 +74,11 @@ pub(super) fn set_local_call_result<'i>(
                         _ => return Err(ShadowingError(entry.key().clone())),
                     };
 
-                    entry.insert(AValue::JValueRef(executed_result));
+                    Some(entry.insert(AValue::JValueRef(executed_result)))
                 }
             };
+
+            exec_ctx.shadow(name, previous);
         }
         CallOutputValue::Accumulator(name) => {
             match exec_ctx.data_cache.entry(name.to_string()) {
@@ -92,11 +99,7 @@ pub(super) fn set_local_call_result<'i>(
 }
 
 /// Writes an executed state of a particle being sent to remote node
-pub(super) fn set_remote_call_result<'i>(
-    peer_pk: String,
-    exec_ctx: &mut ExecutionCtx<'i>,
-    trace_ctx: &mut ExecutionTraceCtx,
-) {
+pub(super) fn set_remote_call_result(peer_pk: String, exec_ctx: &mut ExecutionCtx, trace_ctx: &mut ExecutionTraceCtx) {
     exec_ctx.next_peer_pks.push(peer_pk);
     exec_ctx.subtree_complete = false;
 
@@ -115,7 +118,7 @@ pub(super) fn handle_prev_state<'i>(
     triplet: &Rc<ResolvedTriplet>,
     output: &CallOutputValue<'i>,
     prev_state: ExecutedState,
-    exec_ctx: &mut ExecutionCtx<'i>,
+    exec_ctx: &mut ExecutionCtx,
     trace_ctx: &mut ExecutionTraceCtx,
 ) -> ExecutionResult<bool> {
     use CallResult::*;
@@ -149,9 +152,8 @@ pub(super) fn handle_prev_state<'i>(
             Ok(false)
         }
         // state has inconsistent order - return a error, call shouldn't be executed
-        par_state @ Par(..) => Err(ExecutionError::InvalidExecutedState(
-            String::from("call"),
-            par_state.clone(),
-        )),
+        state @ Par(..) | state @ Ap(..) | state @ Fail { .. } | state @ FanOut(_) | state @ Invoke { .. } => Err(
+            ExecutionError::InvalidExecutedState(String::from("call"), state.clone()),
+        ),
     }
 }
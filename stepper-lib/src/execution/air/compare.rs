@@ -0,0 +1,165 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::compare_matchable::is_comparison_true;
+use super::ExecutionCtx;
+use super::ExecutionError;
+use super::ExecutionTraceCtx;
+use super::Interpreter;
+use super::Step;
+use crate::log_instruction;
+
+use air_parser::ast::Compare;
+
+impl<'i> super::SchedulableInstruction<'i> for Compare<'i> {
+    fn schedule<'a>(
+        &'a self,
+        interpreter: &mut Interpreter<'a, 'i>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> Step {
+        log_instruction!(compare, exec_ctx, trace_ctx);
+
+        let is_true = match is_comparison_true(self.operator, &self.left_value, self.right_value.as_ref(), exec_ctx) {
+            Ok(is_true) => is_true,
+            Err(e) => return Step::Completed(Err(e)),
+        };
+
+        if !is_true {
+            return Step::Completed(Err(ExecutionError::CompareWithoutXorError(self.operator)));
+        }
+
+        interpreter.execute(&self.instruction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contexts::execution_trace::ExecutionTrace;
+    use crate::JValue;
+
+    use aqua_test_utils::call_vm;
+    use aqua_test_utils::create_aqua_vm;
+    use aqua_test_utils::echo_string_call_service;
+    use aqua_test_utils::set_variable_call_service;
+
+    fn check_comparison(condition: &str) -> JValue {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let set_variable_peer_id = "set_variable_peer_id";
+        let mut set_variable_vm = create_aqua_vm(
+            set_variable_call_service(
+                r#"{"number": 42, "name": "peer_a", "peers": ["peer_a", "peer_b"], "empty": {}}"#,
+            ),
+            set_variable_peer_id,
+        );
+
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (seq
+                (call "{0}" ("" "") [] value)
+                (xor
+                    ({2}
+                        (call "{1}" ("" "") ["true"] result)
+                    )
+                    (call "{1}" ("" "") ["false"] result)
+                )
+            )"#,
+            set_variable_peer_id, local_peer_id, condition
+        );
+
+        let res = call_vm!(set_variable_vm, "asd", script.clone(), "", "");
+        let res = call_vm!(vm, "asd", script, "", res.data);
+
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        assert_eq!(actual_trace.len(), 2);
+
+        match &actual_trace[1] {
+            Call(Executed(result)) => result.as_ref().clone(),
+            state => panic!("expected executed call, got {:?}", state),
+        }
+    }
+
+    #[test]
+    fn compare_ordering() {
+        let expected_true = JValue::String(String::from("true"));
+        let expected_false = JValue::String(String::from("false"));
+
+        assert_eq!(check_comparison("lt value.$.number 43"), expected_true);
+        assert_eq!(check_comparison("lt value.$.number 42"), expected_false);
+        assert_eq!(check_comparison("lte value.$.number 42.0"), expected_true);
+        assert_eq!(check_comparison("gt value.$.number -1"), expected_true);
+        assert_eq!(check_comparison("gte 41.5 value.$.number"), expected_false);
+        assert_eq!(check_comparison(r#"lt value.$.name "peer_b""#), expected_true);
+    }
+
+    #[test]
+    fn compare_contains_and_types() {
+        let expected_true = JValue::String(String::from("true"));
+        let expected_false = JValue::String(String::from("false"));
+
+        assert_eq!(check_comparison("contains value.$.peers value.$.name"), expected_true);
+        assert_eq!(check_comparison(r#"contains value.$.peers "peer_c""#), expected_false);
+        assert_eq!(check_comparison(r#"contains value "peers""#), expected_true);
+        assert_eq!(check_comparison(r#"contains value.$.name "peer""#), expected_true);
+        assert_eq!(check_comparison(r#"type_of value.$.number "number""#), expected_true);
+        assert_eq!(check_comparison(r#"type_of value.$.peers "object""#), expected_false);
+        assert_eq!(check_comparison("is_empty value.$.empty"), expected_true);
+        assert_eq!(check_comparison("is_empty value.$.peers"), expected_false);
+    }
+
+    #[test]
+    fn compare_incomparable_values() {
+        let expected_false = JValue::String(String::from("false"));
+
+        // an error is caught by xor as well as a comparison that doesn't hold
+        assert_eq!(check_comparison(r#"lt value.$.number "43""#), expected_false);
+        assert_eq!(check_comparison("is_empty value.$.number"), expected_false);
+    }
+
+    #[test]
+    fn compare_without_xor() {
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (gt 1 2
+                (call "{0}" ("" "") ["result"] result)
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script.clone(), "", "");
+        assert_eq!(res.ret_code, 1018);
+        assert!(res.error_message.contains("gt is used without corresponding xor"));
+
+        let script = format!(
+            r#"
+            (lt [1] 2
+                (call "{0}" ("" "") ["result"] result)
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "", "");
+        assert_eq!(res.ret_code, 1017);
+    }
+}
@@ -14,7 +14,9 @@
  * limitations under the License.
  */
 
+use super::are_jvalues_eq;
 use crate::contexts::execution::ExecutionCtx;
+use crate::execution::air::ExecutionError;
 use crate::execution::air::ExecutionResult;
 use crate::execution::utils::resolve_to_jvaluable;
 use crate::JValue;
@@ -24,67 +26,45 @@ use air_parser::ast::MatchableValue;
 pub(crate) fn are_matchable_eq<'ctx>(
     left: &MatchableValue<'_>,
     right: &MatchableValue<'_>,
-    exec_ctx: &'ctx ExecutionCtx<'_>,
+    exec_ctx: &'ctx ExecutionCtx,
 ) -> ExecutionResult<bool> {
-    use MatchableValue::*;
-
-    match (left, right) {
-        (Literal(name), matchable) => compare_matchable_and_literal(matchable, name, exec_ctx),
-        (matchable, Literal(name)) => compare_matchable_and_literal(matchable, name, exec_ctx),
-        (Variable(left_name), Variable(right_name)) => {
-            let left_jvaluable = resolve_to_jvaluable(left_name, exec_ctx)?;
-            let left_value = left_jvaluable.as_jvalue();
+    let left_value = resolve_matchable(left, exec_ctx)?;
+    let right_value = resolve_matchable(right, exec_ctx)?;
 
-            let right_jvaluable = resolve_to_jvaluable(right_name, exec_ctx)?;
-            let right_value = right_jvaluable.as_jvalue();
-
-            Ok(left_value == right_value)
-        }
-        (JsonPath { variable: lv, path: lp }, JsonPath { variable: rv, path: rp }) => {
-            let left_jvaluable = resolve_to_jvaluable(lv, exec_ctx)?;
-            let left_value = left_jvaluable.apply_json_path(lp)?;
-
-            let right_jvaluable = resolve_to_jvaluable(rv, exec_ctx)?;
-            let right_value = right_jvaluable.apply_json_path(rp)?;
+    Ok(are_jvalues_eq(&left_value, &right_value))
+}
 
-            Ok(left_value == right_value)
-        }
-        _ => Ok(false),
-    }
+/// Resolves a matchable to a json value. A definite json path is resolved to the only value
+/// it selects, any other json path is resolved to an array of selected values regardless of
+/// their count, so the kind of a comparison doesn't depend on the compared data.
+pub(crate) fn resolve_matchable(matchable: &MatchableValue<'_>, exec_ctx: &ExecutionCtx) -> ExecutionResult<JValue> {
+    let value = resolve_matchable_impl(matchable, exec_ctx);
+    exec_ctx.point_at_value(matchable.span(), value)
 }
 
-fn compare_matchable_and_literal<'ctx>(
-    matchable: &MatchableValue<'_>,
-    string_literal: &str,
-    exec_ctx: &'ctx ExecutionCtx<'_>,
-) -> ExecutionResult<bool> {
-    use std::borrow::Cow;
+fn resolve_matchable_impl(matchable: &MatchableValue<'_>, exec_ctx: &ExecutionCtx) -> ExecutionResult<JValue> {
     use MatchableValue::*;
 
-    fn compare_jvalue_and_literal(jvalue: Cow<'_, JValue>, string_literal: &str) -> bool {
-        use std::ops::Deref;
-
-        match jvalue.deref() {
-            JValue::String(value) => value == string_literal,
-            _ => false,
-        }
-    }
-
-    match matchable {
-        Literal(name) => Ok(name == &string_literal),
-        Variable(name) => {
-            let jvaluable = resolve_to_jvaluable(name, exec_ctx)?;
-            let jvalue = jvaluable.as_jvalue();
-            Ok(compare_jvalue_and_literal(jvalue, string_literal))
-        }
-        JsonPath { variable, path } => {
+    let value = match matchable {
+        Literal(value) => JValue::String(value.to_string()),
+        Number(value) => JValue::Number(value.clone()),
+        Boolean(value) => JValue::Bool(*value),
+        Null => JValue::Null,
+        Json(value) => value.clone(),
+        Variable(name, _) => resolve_to_jvaluable(name, exec_ctx)?.into_jvalue(),
+        JsonPath { variable, path, .. } => {
             let jvaluable = resolve_to_jvaluable(variable, exec_ctx)?;
             let jvalues = jvaluable.apply_json_path(path)?;
-            if jvalues.len() != 1 {
-                return Ok(false);
+            if !path.is_definite() {
+                return Ok(JValue::Array(jvalues.into_iter().cloned().collect()));
             }
 
-            Ok(compare_jvalue_and_literal(Cow::Borrowed(jvalues[0]), string_literal))
+            match jvalues.as_slice() {
+                [jvalue] => (*jvalue).clone(),
+                _ => return Err(ExecutionError::MultipleValuesInJsonPath(path.as_str().to_string())),
+            }
         }
-    }
+    };
+
+    Ok(value)
 }
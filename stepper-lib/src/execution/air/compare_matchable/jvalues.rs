@@ -0,0 +1,76 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::execution::air::ExecutionError;
+use crate::execution::air::ExecutionResult;
+use crate::JValue;
+
+use serde_json::Number;
+
+use std::cmp::Ordering;
+
+/// Compares json values structurally, numbers are equal if they have the same value
+/// regardless of their representation, e.g. 1 equals to 1.0.
+pub(crate) fn are_jvalues_eq(left: &JValue, right: &JValue) -> bool {
+    match (left, right) {
+        (JValue::Number(left), JValue::Number(right)) => compare_numbers(left, right) == Ordering::Equal,
+        (JValue::Array(left), JValue::Array(right)) => {
+            left.len() == right.len() && left.iter().zip(right.iter()).all(|(l, r)| are_jvalues_eq(l, r))
+        }
+        (JValue::Object(left), JValue::Object(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .all(|(key, l)| matches!(right.get(key), Some(r) if are_jvalues_eq(l, r)))
+        }
+        (left, right) => left == right,
+    }
+}
+
+/// Orders numbers numerically and strings lexicographically, other values couldn't be ordered.
+pub(crate) fn compare_jvalues(left: &JValue, right: &JValue) -> ExecutionResult<Ordering> {
+    match (left, right) {
+        (JValue::Number(left), JValue::Number(right)) => Ok(compare_numbers(left, right)),
+        (JValue::String(left), JValue::String(right)) => Ok(left.cmp(right)),
+        (left, right) => Err(ExecutionError::IncomparableValues(left.clone(), right.clone())),
+    }
+}
+
+/// Returns a name of the json value type that could be checked with type_of.
+pub(crate) fn type_name(value: &JValue) -> &'static str {
+    match value {
+        JValue::Null => "null",
+        JValue::Bool(_) => "boolean",
+        JValue::Number(_) => "number",
+        JValue::String(_) => "string",
+        JValue::Array(_) => "array",
+        JValue::Object(_) => "object",
+    }
+}
+
+fn compare_numbers(left: &Number, right: &Number) -> Ordering {
+    if let (Some(left), Some(right)) = (left.as_i64(), right.as_i64()) {
+        return left.cmp(&right);
+    }
+    if let (Some(left), Some(right)) = (left.as_u64(), right.as_u64()) {
+        return left.cmp(&right);
+    }
+
+    // json numbers can't be NaN, so they are always comparable
+    let left = left.as_f64().unwrap_or_default();
+    let right = right.as_f64().unwrap_or_default();
+    left.partial_cmp(&right).unwrap_or(Ordering::Equal)
+}
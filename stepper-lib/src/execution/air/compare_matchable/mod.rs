@@ -15,5 +15,13 @@
  */
 
 mod compare_matchable;
+mod jvalues;
+mod operators;
 
 pub(super) use compare_matchable::are_matchable_eq;
+pub(super) use operators::is_comparison_true;
+
+use compare_matchable::resolve_matchable;
+use jvalues::are_jvalues_eq;
+use jvalues::compare_jvalues;
+use jvalues::type_name;
@@ -0,0 +1,84 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::are_jvalues_eq;
+use super::compare_jvalues;
+use super::resolve_matchable;
+use super::type_name;
+use crate::contexts::execution::ExecutionCtx;
+use crate::execution::air::ExecutionError;
+use crate::execution::air::ExecutionResult;
+use crate::JValue;
+
+use air_parser::ast::CompareOperator;
+use air_parser::ast::MatchableValue;
+
+use std::cmp::Ordering;
+
+pub(crate) fn is_comparison_true(
+    operator: CompareOperator,
+    left: &MatchableValue<'_>,
+    right: Option<&MatchableValue<'_>>,
+    exec_ctx: &ExecutionCtx,
+) -> ExecutionResult<bool> {
+    use CompareOperator::*;
+
+    let left = resolve_matchable(left, exec_ctx)?;
+    let right = match (operator.is_unary(), right) {
+        (true, None) => JValue::Null,
+        (false, Some(right)) => resolve_matchable(right, exec_ctx)?,
+        _ => {
+            return Err(ExecutionError::InstructionError(format!(
+                "{} is used with a wrong number of values",
+                operator
+            )))
+        }
+    };
+
+    match operator {
+        Less => Ok(compare_jvalues(&left, &right)? == Ordering::Less),
+        LessOrEqual => Ok(compare_jvalues(&left, &right)? != Ordering::Greater),
+        Greater => Ok(compare_jvalues(&left, &right)? == Ordering::Greater),
+        GreaterOrEqual => Ok(compare_jvalues(&left, &right)? != Ordering::Less),
+        Contains => contains(&left, &right),
+        TypeOf => match right {
+            JValue::String(type_) => Ok(type_name(&left) == type_),
+            right => Err(ExecutionError::IncompatibleJValueType(right, "string")),
+        },
+        IsEmpty => match left {
+            JValue::Array(array) => Ok(array.is_empty()),
+            JValue::Object(object) => Ok(object.is_empty()),
+            JValue::String(string) => Ok(string.is_empty()),
+            left => Err(ExecutionError::IncompatibleJValueType(left, "array, object or string")),
+        },
+    }
+}
+
+/// Checks that an array contains an element, an object contains a key or a string contains a substring.
+fn contains(container: &JValue, value: &JValue) -> ExecutionResult<bool> {
+    match (container, value) {
+        (JValue::Array(array), value) => Ok(array.iter().any(|element| are_jvalues_eq(element, value))),
+        (JValue::Object(object), JValue::String(key)) => Ok(object.contains_key(key)),
+        (JValue::String(string), JValue::String(substring)) => Ok(string.contains(substring.as_str())),
+        (JValue::Object(_), value) | (JValue::String(_), value) => {
+            Err(ExecutionError::IncompatibleJValueType(value.clone(), "string"))
+        }
+        (container, _) => Err(ExecutionError::IncompatibleJValueType(
+            container.clone(),
+            "array, object or string",
+        )),
+    }
+}
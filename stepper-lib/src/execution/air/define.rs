@@ -0,0 +1,38 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::ExecutionCtx;
+use super::ExecutionTraceCtx;
+use super::Interpreter;
+use super::Step;
+use crate::log_instruction;
+
+use air_parser::ast::Define;
+
+impl<'i> super::SchedulableInstruction<'i> for Define<'i> {
+    fn schedule<'a>(
+        &'a self,
+        interpreter: &mut Interpreter<'a, 'i>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> Step {
+        log_instruction!(define, exec_ctx, trace_ctx);
+
+        // a procedure body is executed only by invocations, so here it's just remembered
+        interpreter.define(self);
+        interpreter.execute(&self.instruction)
+    }
+}
@@ -0,0 +1,366 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::ExecutionCtx;
+use super::ExecutionError;
+use super::ExecutionResult;
+use super::ExecutionTraceCtx;
+use crate::contexts::execution::LastError;
+use crate::contexts::execution_trace::ExecutedState;
+use crate::execution::boxed_value::JValuable;
+use crate::execution::utils::resolve_to_jvaluable;
+use crate::joinable;
+use crate::log_instruction;
+use crate::log_targets::EXECUTED_STATE_CHANGING;
+use crate::JValue;
+
+use air_parser::ast::CallArgValue;
+use air_parser::ast::Fail;
+
+use std::convert::TryFrom;
+
+/// The lowest code a script could fail with, lower codes are reserved for the interpreter.
+pub(crate) const FAIL_CODES_START_ID: i32 = 10_000;
+
+impl<'i> super::ExecutableInstruction<'i> for Fail<'i> {
+    fn execute(&self, exec_ctx: &mut ExecutionCtx, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
+        log_instruction!(fail, exec_ctx, trace_ctx);
+
+        // the error saved by a previous execution is preferred to fail the same way on all peers,
+        // so arguments are resolved only if this fail hasn't been executed before
+        let (ret_code, message) = match prev_fail_result(trace_ctx)? {
+            Some(prev_result) => prev_result,
+            None => joinable!(resolve_fail_args(self, exec_ctx), exec_ctx)?,
+        };
+
+        let new_executed_state = ExecutedState::Fail {
+            ret_code,
+            message: message.clone(),
+        };
+        log::trace!(
+            target: EXECUTED_STATE_CHANGING,
+            "  adding new fail executed state {:?}",
+            new_executed_state
+        );
+        trace_ctx.new_trace.push_back(new_executed_state);
+
+        Err(ExecutionError::FailError(ret_code, message))
+    }
+}
+
+fn resolve_fail_args<'i>(fail: &Fail<'i>, exec_ctx: &ExecutionCtx) -> ExecutionResult<(i32, String)> {
+    use ExecutionError::IncompatibleJValueType;
+    use ExecutionError::InstructionError;
+
+    let ret_code = resolve_fail_arg(&fail.ret_code, exec_ctx).and_then(|ret_code| {
+        match ret_code.as_i64().and_then(|code| i32::try_from(code).ok()) {
+            Some(ret_code) => Ok(ret_code),
+            None => Err(IncompatibleJValueType(ret_code, "i32")),
+        }
+    });
+    let ret_code = exec_ctx.point_at_value(fail.ret_code.span(), ret_code)?;
+
+    // lower codes mean a successful execution or an error of the interpreter for a host
+    if ret_code < FAIL_CODES_START_ID {
+        return Err(InstructionError(format!(
+            "fail code {} is reserved, a script could fail only with codes starting from {}",
+            ret_code, FAIL_CODES_START_ID
+        )));
+    }
+
+    let message = resolve_fail_arg(&fail.message, exec_ctx).and_then(|message| match message {
+        JValue::String(message) => Ok(message),
+        value => Err(IncompatibleJValueType(value, "string")),
+    });
+    let message = exec_ctx.point_at_value(fail.message.span(), message)?;
+
+    Ok((ret_code, message))
+}
+
+/// Resolves a fail argument to a single json value.
+fn resolve_fail_arg<'i>(value: &CallArgValue<'i>, exec_ctx: &ExecutionCtx) -> ExecutionResult<JValue> {
+    match value {
+        CallArgValue::Number(number) => Ok(JValue::Number(number.clone())),
+        CallArgValue::Literal(literal) => Ok(JValue::String(literal.to_string())),
+        CallArgValue::Variable(name, _) => {
+            let resolved = resolve_to_jvaluable(name, exec_ctx)?;
+            Ok(resolved.into_jvalue())
+        }
+        CallArgValue::JsonPath { variable, path, .. } => {
+            let resolved = resolve_to_jvaluable(variable, exec_ctx)?;
+            let jvalues = resolved.apply_json_path(path)?;
+            match jvalues.as_slice() {
+                [jvalue] => Ok((*jvalue).clone()),
+                _ => Err(ExecutionError::MultipleValuesInJsonPath(path.as_str().to_string())),
+            }
+        }
+        CallArgValue::LastError(Some(path)) => {
+            let last_error = exec_ctx.last_error.as_ref().map(LastError::to_call_result);
+            let last_error = last_error.ok_or_else(|| ExecutionError::VariableNotFound(value.to_string()))?;
+            let jvalues = last_error.apply_json_path(path)?;
+            match jvalues.as_slice() {
+                [jvalue] => Ok((*jvalue).clone()),
+                _ => Err(ExecutionError::MultipleValuesInJsonPath(path.as_str().to_string())),
+            }
+        }
+        // parser doesn't allow other values as fail arguments
+        value => Err(ExecutionError::InstructionError(format!(
+            "'{}' can't be used as a fail argument",
+            value
+        ))),
+    }
+}
+
+/// Returns an error of this fail from the previous executed trace, if it's been executed before.
+fn prev_fail_result(trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<Option<(i32, String)>> {
+    if trace_ctx.current_subtree_size == 0 {
+        log::trace!(
+            target: EXECUTED_STATE_CHANGING,
+            "  previous executed trace state wasn't found"
+        );
+        return Ok(None);
+    }
+
+    trace_ctx.current_subtree_size -= 1;
+    // unwrap is safe here, because current_subtree_size depends on current_path len,
+    // and it's been checked previously
+    let prev_state = trace_ctx.current_trace.pop_front().unwrap();
+
+    log::trace!(
+        target: EXECUTED_STATE_CHANGING,
+        "  previous executed trace found {:?}",
+        prev_state
+    );
+
+    match prev_state {
+        // a trace of another peer shouldn't make this one fail with a code of the interpreter
+        ExecutedState::Fail { ret_code, message } if ret_code >= FAIL_CODES_START_ID => Ok(Some((ret_code, message))),
+        state => Err(ExecutionError::InvalidExecutedState(String::from("fail"), state)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contexts::execution_trace::ExecutionTrace;
+
+    use aqua_test_utils::call_vm;
+    use aqua_test_utils::create_aqua_vm;
+    use aqua_test_utils::set_variable_call_service;
+    use aqua_test_utils::unit_call_service;
+
+    // Check that an uncaught fail is returned with the provided code and message.
+    #[test]
+    fn uncaught_fail() {
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let mut vm = create_aqua_vm(unit_call_service(), "A");
+
+        let res = call_vm!(vm, "asd", r#"(fail 10042 "error message")"#, "[]", "[]");
+        assert_eq!(res.ret_code, 10042);
+        assert_eq!(res.error_message, "error message");
+
+        let trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be a valid json");
+        let expected_state = Fail {
+            ret_code: 10042,
+            message: String::from("error message"),
+        };
+        assert_eq!(trace, vec![expected_state]);
+    }
+
+    // Check that fail is caught by xor and the error is kept in the trace.
+    #[test]
+    fn fail_caught_by_xor() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+        use crate::JValue;
+
+        use std::rc::Rc;
+
+        let mut vm = create_aqua_vm(unit_call_service(), "A");
+
+        let script = r#"
+            (xor
+                (fail 10001 "error message")
+                (call "A" ("service" "function") [] result)
+            )
+            "#;
+
+        let res = call_vm!(vm, "asd", script, "[]", "[]");
+        assert_eq!(res.ret_code, 0);
+
+        let trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be a valid json");
+        let expected_trace = vec![
+            Fail {
+                ret_code: 10001,
+                message: String::from("error message"),
+            },
+            Call(Executed(Rc::new(JValue::String(String::from("test"))))),
+        ];
+        assert_eq!(trace, expected_trace);
+    }
+
+    // Check that fail arguments could be taken from variables.
+    #[test]
+    fn fail_with_variables() {
+        let mut vm = create_aqua_vm(set_variable_call_service(r#"{"code": 10007, "message": "error"}"#), "A");
+
+        let script = r#"
+            (seq
+                (call "A" ("service" "function") [] error)
+                (fail error.$.code error.$.message)
+            )
+            "#;
+
+        let res = call_vm!(vm, "asd", script, "[]", "[]");
+        assert_eq!(res.ret_code, 10007);
+        assert_eq!(res.error_message, "error");
+
+        let script = r#"
+            (seq
+                (call "A" ("service" "function") [] error)
+                (fail error.$.message error.$.code)
+            )
+            "#;
+
+        let res = call_vm!(vm, "asd", script, "[]", "[]");
+        // IncompatibleJValueType
+        assert_eq!(res.ret_code, 1008);
+    }
+
+    // Check that %last_error% keeps the code provided by a script.
+    #[test]
+    fn fail_code_in_last_error() {
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        use serde_json::json;
+        use std::rc::Rc;
+
+        let mut vm = create_aqua_vm(unit_call_service(), "A");
+
+        let script = r#"
+            (xor
+                (fail 10042 "error message")
+                (ap %last_error%.$.error_code code)
+            )
+            "#;
+
+        let res = call_vm!(vm, "asd", script, "[]", "[]");
+        assert_eq!(res.ret_code, 0);
+
+        let trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be a valid json");
+        assert_eq!(trace[1], Ap(Rc::new(json!([10042]))));
+    }
+
+    // Check that a peer replaying the trace fails with the recorded error.
+    #[test]
+    fn fail_replayed_from_trace() {
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let mut vm_a = create_aqua_vm(set_variable_call_service(r#""first""#), "A");
+        let mut vm_b = create_aqua_vm(set_variable_call_service(r#""second""#), "B");
+
+        let script = r#"
+            (seq
+                (call "A" ("service" "function") [] message)
+                (seq
+                    (xor
+                        (fail 10001 message)
+                        (null)
+                    )
+                    (call "B" ("service" "function") [] result)
+                )
+            )
+            "#;
+
+        let res = call_vm!(vm_a, "asd", script, "[]", "[]");
+        assert_eq!(res.next_peer_pks, vec![String::from("B")]);
+
+        let res = call_vm!(vm_b, "asd", script, "[]", res.data);
+        assert_eq!(res.ret_code, 0);
+
+        let trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be a valid json");
+        let expected_state = Fail {
+            ret_code: 10001,
+            message: String::from("first"),
+        };
+        assert_eq!(trace[1], expected_state);
+        assert_eq!(trace.len(), 3);
+    }
+
+    // Check that a fail inside par keeps its region, so peers replaying it reach the same error handler.
+    #[test]
+    fn fail_in_par_replayed_from_trace() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+        use crate::JValue;
+
+        use std::rc::Rc;
+
+        let mut vm_a = create_aqua_vm(set_variable_call_service(r#""a""#), "A");
+        let mut vm_b = create_aqua_vm(set_variable_call_service(r#""b""#), "B");
+
+        let script = r#"
+            (seq
+                (xor
+                    (par
+                        (fail 10001 "error message")
+                        (null)
+                    )
+                    (call "B" ("service" "function") [] handled)
+                )
+                (call "A" ("service" "function") [] result)
+            )
+            "#;
+
+        let res = call_vm!(vm_a, "asd", script, "[]", "[]");
+        assert_eq!(res.next_peer_pks, vec![String::from("B")]);
+
+        let res = call_vm!(vm_b, "asd", script, "[]", res.data);
+        assert_eq!(res.next_peer_pks, vec![String::from("A")]);
+
+        let res = call_vm!(vm_a, "asd", script, "[]", res.data);
+        assert_eq!(res.ret_code, 0);
+        assert!(res.next_peer_pks.is_empty());
+
+        let trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be a valid json");
+        let expected_trace = vec![
+            Par(1, 0),
+            Fail {
+                ret_code: 10001,
+                message: String::from("error message"),
+            },
+            Call(Executed(Rc::new(JValue::String(String::from("b"))))),
+            Call(Executed(Rc::new(JValue::String(String::from("a"))))),
+        ];
+        assert_eq!(trace, expected_trace);
+    }
+
+    // Check that fail can't be used with codes of the interpreter errors or a code meaning success.
+    #[test]
+    fn fail_with_reserved_code() {
+        let mut vm = create_aqua_vm(unit_call_service(), "A");
+
+        for code in &["0", "-1", "1", "1001", "9999"] {
+            let script = format!(r#"(fail {} "error message")"#, code);
+            let res = call_vm!(vm, "asd", script, "[]", "[]");
+            // InstructionError
+            assert_eq!(res.ret_code, 1002, "{}", code);
+        }
+
+        let res = call_vm!(vm, "asd", r#"(fail 10000 "error message")"#, "[]", "[]");
+        assert_eq!(res.ret_code, 10000);
+    }
+}
@@ -18,138 +18,123 @@ mod utils;
 
 use super::ExecutionCtx;
 use super::ExecutionError;
-use super::ExecutionResult;
 use super::ExecutionTraceCtx;
-use super::Instruction;
+use super::Interpreter;
+use super::Step;
 use crate::contexts::execution::AValue;
-use crate::contexts::execution::ResolvedCallResult;
 use crate::execution::boxed_value::*;
 use crate::log_instruction;
 
 use air_parser::ast::Fold;
 use air_parser::ast::Next;
 
-use std::collections::HashMap;
-use std::rc::Rc;
-
 use utils::IterableValue;
 
-pub(crate) struct FoldState<'i> {
+pub(crate) struct FoldState {
     pub(crate) iterable: IterableValue,
-    pub(crate) instr_head: Rc<Instruction<'i>>,
-    // map of met variables inside this (not any inner) fold block with their initial values
-    pub(crate) met_variables: HashMap<&'i str, ResolvedCallResult>,
 }
 
-impl<'i> FoldState<'i> {
-    pub fn new(iterable: IterableValue, instr_head: Rc<Instruction<'i>>) -> Self {
-        Self {
-            iterable,
-            instr_head,
-            met_variables: HashMap::new(),
-        }
+impl FoldState {
+    pub fn new(iterable: IterableValue) -> Self {
+        Self { iterable }
     }
 }
 
-impl<'i> super::ExecutableInstruction<'i> for Fold<'i> {
-    fn execute(&self, exec_ctx: &mut ExecutionCtx<'i>, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
+impl<'i> super::SchedulableInstruction<'i> for Fold<'i> {
+    fn schedule<'a>(
+        &'a self,
+        interpreter: &mut Interpreter<'a, 'i>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> Step {
         use ExecutionError::MultipleFoldStates;
 
         log_instruction!(fold, exec_ctx, trace_ctx);
 
-        let iterable = match utils::construct_iterable_value(&self.iterable, exec_ctx)? {
-            Some(iterable) => iterable,
-            None => return Ok(()),
+        let iterable = match utils::construct_iterable_value(&self.iterable, exec_ctx) {
+            Ok(Some(iterable)) => iterable,
+            Ok(None) => return Step::Completed(Ok(())),
+            Err(e) => return Step::Completed(Err(e)),
         };
 
-        let fold_state = FoldState::new(iterable, self.instruction.clone());
+        let fold_state = FoldState::new(iterable);
 
         let previous_value = exec_ctx
             .data_cache
             .insert(self.iterator.to_string(), AValue::JValueFoldCursor(fold_state));
 
         if previous_value.is_some() {
-            return Err(MultipleFoldStates(self.iterator.to_string()));
+            return Step::Completed(Err(MultipleFoldStates(self.iterator.to_string())));
         }
-        exec_ctx.met_folds.push_back(self.iterator);
-
-        self.instruction.execute(exec_ctx, trace_ctx)?;
 
-        cleanup_variables(exec_ctx, &self.iterator);
+        // the iterator and variables defined inside the fold are removed after it
+        exec_ctx.open_scope();
+        exec_ctx.shadow(&self.iterator, None);
+        interpreter.enter_fold(&self.iterator, &self.instruction);
 
-        Ok(())
+        interpreter.execute_then(&self.instruction, move |result, interpreter, exec_ctx, _| {
+            interpreter.leave_fold(&self.iterator);
+            exec_ctx.close_scope();
+            Step::Completed(result)
+        })
     }
 }
 
-impl<'i> super::ExecutableInstruction<'i> for Next<'i> {
-    fn execute(&self, exec_ctx: &mut ExecutionCtx<'i>, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
+impl<'i> super::SchedulableInstruction<'i> for Next<'i> {
+    fn schedule<'a>(
+        &'a self,
+        interpreter: &mut Interpreter<'a, 'i>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> Step {
         use ExecutionError::FoldStateNotFound;
         use ExecutionError::IncompatibleAValueType;
 
         log_instruction!(next, exec_ctx, trace_ctx);
 
-        let iterator_name = self.0;
-        let avalue = exec_ctx
-            .data_cache
-            .get_mut(iterator_name)
-            .ok_or_else(|| FoldStateNotFound(iterator_name.to_string()))?;
+        let iterator_name: &str = &self.0;
+        let avalue = match exec_ctx.data_cache.get_mut(iterator_name) {
+            Some(avalue) => avalue,
+            None => return Step::Completed(Err(FoldStateNotFound(iterator_name.to_string()))),
+        };
 
         let fold_state = match avalue {
             AValue::JValueFoldCursor(state) => state,
             v => {
                 // it's not possible to use unreachable here
                 // because at now next syntactically could be used without fold
-                return Err(IncompatibleAValueType(
+                return Step::Completed(Err(IncompatibleAValueType(
                     format!("{}", v),
                     String::from("JValueFoldCursor"),
-                ));
+                )));
             }
         };
 
         if !fold_state.iterable.next() {
             // just do nothing to exit
-            return Ok(());
+            return Step::Completed(Ok(()));
         }
 
-        let next_instr = fold_state.instr_head.clone();
-        next_instr.execute(exec_ctx, trace_ctx)?;
-
-        // get the same fold state again because of borrow checker
-        match exec_ctx.data_cache.get_mut(iterator_name) {
-            // move iterator back to provide correct value for possible subtree after next
-            // (for example for cases such as right fold)
-            Some(AValue::JValueFoldCursor(fold_state)) => fold_state.iterable.prev(),
-            _ => unreachable!("iterator value shouldn't changed inside fold"),
+        let next_instr = match interpreter.fold_body(iterator_name) {
+            Some(next_instr) => next_instr,
+            None => return Step::Completed(Err(FoldStateNotFound(iterator_name.to_string()))),
         };
 
-        Ok(())
-    }
-}
-
-fn cleanup_variables(exec_ctx: &mut ExecutionCtx<'_>, iterator: &str) {
-    let fold_state = match exec_ctx.data_cache.remove(iterator) {
-        Some(AValue::JValueFoldCursor(fold_state)) => fold_state,
-        _ => unreachable!("fold cursor is changed only inside fold block"),
-    };
-
-    for (variable_name, _) in fold_state.met_variables {
-        exec_ctx.data_cache.remove(variable_name);
-    }
-    exec_ctx.met_folds.pop_back();
-
-    // TODO: fix 3 or more inner folds behaviour
-    if let Some(fold_block_name) = exec_ctx.met_folds.back() {
-        let fold_state = match exec_ctx.data_cache.get(*fold_block_name) {
-            Some(AValue::JValueFoldCursor(fold_state)) => fold_state,
-            _ => unreachable!("fold block data must be represented as fold cursor"),
-        };
+        interpreter.execute_then(next_instr, move |result, _, exec_ctx, _| {
+            if result.is_err() {
+                return Step::Completed(result);
+            }
 
-        let mut upper_fold_values = HashMap::new();
-        for (variable_name, variable) in fold_state.met_variables.iter() {
-            upper_fold_values.insert(variable_name.to_string(), AValue::JValueRef(variable.clone()));
-        }
+            // get the same fold state again because it could be changed by the fold body
+            match exec_ctx.data_cache.get_mut(iterator_name) {
+                // move iterator back to provide correct value for possible subtree after next
+                // (for example for cases such as right fold)
+                Some(AValue::JValueFoldCursor(fold_state)) => fold_state.iterable.prev(),
+                _ => unreachable!("iterator value shouldn't changed inside fold"),
+            };
 
-        exec_ctx.data_cache.extend(upper_fold_values);
+            Step::Completed(Ok(()))
+        })
     }
 }
 
@@ -311,7 +296,9 @@ mod tests {
 
         let res = call_vm!(vm, "", script, "[]", "[]");
 
-        assert_eq!(res.ret_code, 1012);
+        // such scripts are rejected by the static validation before execution
+        assert_eq!(res.ret_code, 7);
+        assert!(res.error_message.contains("fold iterator 'i' shadows a variable"));
     }
 
     #[test]
@@ -382,6 +369,100 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fold_over_object() {
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let mut vm = create_aqua_vm(echo_string_call_service(), "A");
+        let mut set_variable_vm = create_aqua_vm(
+            set_variable_call_service(r#"{ "peers": { "b": "2", "c": "3", "a": "1" } }"#),
+            "set_variable",
+        );
+
+        let script = String::from(
+            r#"
+            (seq
+                (call "set_variable" ("" "") [] result)
+                (fold result.$.peers entry
+                    (seq
+                        (ap entry.$.key keys[])
+                        (next entry)
+                    )
+                )
+            )"#,
+        );
+
+        let res = call_vm!(set_variable_vm, "", script.clone(), "[]", "[]");
+        let res = call_vm!(vm, "", script, "[]", res.data);
+        let res: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid executed trace");
+
+        // entries are iterated in the order of keys
+        assert_eq!(res.len(), 4);
+        assert_eq!(res[1], Ap(Rc::new(json!(["a"]))));
+        assert_eq!(res[2], Ap(Rc::new(json!(["b"]))));
+        assert_eq!(res[3], Ap(Rc::new(json!(["c"]))));
+    }
+
+    #[test]
+    fn fold_over_object_variable() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let mut vm = create_aqua_vm(echo_string_call_service(), "A");
+        let mut set_variable_vm = create_aqua_vm(set_variable_call_service(r#"{ "b": 2, "a": 1 }"#), "set_variable");
+
+        let script = String::from(
+            r#"
+            (seq
+                (call "set_variable" ("" "") [] peers)
+                (fold peers entry
+                    (seq
+                        (ap entry entries[])
+                        (next entry)
+                    )
+                )
+            )"#,
+        );
+
+        let res = call_vm!(set_variable_vm, "", script.clone(), "[]", "[]");
+        let res = call_vm!(vm, "", script, "[]", res.data);
+        let res: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid executed trace");
+
+        assert_eq!(res.len(), 3);
+        assert_eq!(res[0], Call(Executed(Rc::new(json!({ "a": 1, "b": 2 })))));
+        assert_eq!(res[1], Ap(Rc::new(json!({ "key": "a", "value": 1 }))));
+        assert_eq!(res[2], Ap(Rc::new(json!({ "key": "b", "value": 2 }))));
+    }
+
+    #[test]
+    fn fold_over_objects_selected_by_wildcard() {
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let mut vm = create_aqua_vm(echo_string_call_service(), "A");
+        let mut set_variable_vm = create_aqua_vm(set_variable_call_service(r#"[{ "a": 1, "b": 2 }]"#), "set_variable");
+
+        let script = String::from(
+            r#"
+            (seq
+                (call "set_variable" ("" "") [] xs)
+                (fold xs.$.[*] x
+                    (seq
+                        (ap x objects[])
+                        (next x)
+                    )
+                )
+            )"#,
+        );
+
+        let res = call_vm!(set_variable_vm, "", script.clone(), "[]", "[]");
+        let res = call_vm!(vm, "", script, "[]", res.data);
+        let res: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid executed trace");
+
+        // a single object selected by a wildcard is an element, not a source of entries
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[1], Ap(Rc::new(json!({ "a": 1, "b": 2 }))));
+    }
+
     #[test]
     fn shadowing() {
         use crate::contexts::execution_trace::CallResult::*;
@@ -494,4 +575,81 @@ mod tests {
             assert!(matches!(res[i], Call(Executed(_))));
         }
     }
+
+    #[test]
+    fn shadowing_in_three_nested_folds() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let mut set_variable_vm = create_aqua_vm(set_variable_call_service(r#"["1","2"]"#), "set_variable");
+        let mut vm = create_aqua_vm(echo_string_call_service(), "A");
+
+        let script = String::from(
+            r#"
+            (seq
+                (seq
+                    (call "set_variable" ("" "") [] iterable)
+                    (call "A" ("" "") ["outer"] value)
+                )
+                (seq
+                    (fold iterable i
+                        (seq
+                            (fold iterable j
+                                (seq
+                                    (fold iterable k
+                                        (seq
+                                            (call "A" ("" "") [k] value)
+                                            (next k)
+                                        )
+                                    )
+                                    (next j)
+                                )
+                            )
+                            (next i)
+                        )
+                    )
+                    (call "A" ("" "") [value] result)
+                )
+            )"#,
+        );
+
+        let res = call_vm!(set_variable_vm, "", script.clone(), "[]", "[]");
+        let res = call_vm!(vm, "", script, "[]", res.data);
+        assert_eq!(res.ret_code, 0);
+
+        let res: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid executed trace");
+        let expected_result = Call(Executed(Rc::new(JValue::String(String::from("outer")))));
+
+        assert_eq!(res.len(), 11);
+        assert_eq!(res[10], expected_result);
+    }
+
+    #[test]
+    fn long_fold() {
+        let iterations_count = 5_000;
+        let iterable = serde_json::to_string(&(0..iterations_count).collect::<Vec<_>>()).unwrap();
+
+        let mut set_variable_vm = create_aqua_vm(set_variable_call_service(iterable), "set_variable");
+        let mut vm = create_aqua_vm(echo_number_call_service(), "A");
+
+        let script = String::from(
+            r#"
+            (seq
+                (call "set_variable" ("" "") [] iterable)
+                (fold iterable i
+                    (seq
+                        (ap i value)
+                        (next i)
+                    )
+                )
+            )"#,
+        );
+
+        let res = call_vm!(set_variable_vm, "", script.clone(), "[]", "[]");
+        let res = call_vm!(vm, "", script, "[]", res.data);
+        assert_eq!(res.ret_code, 0);
+
+        let res: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid executed trace");
+        assert_eq!(res.len(), iterations_count + 1);
+    }
 }
@@ -15,13 +15,14 @@
  */
 
 use super::*;
+use crate::contexts::execution::ResolvedCallResult;
+use crate::execution::ExecutionResult;
 use crate::JValue;
 use crate::ResolvedTriplet;
 use crate::SecurityTetraplet;
 
 use air_parser::ast;
-use jsonpath_lib::select;
-use jsonpath_lib::select_with_iter;
+use air_parser::ast::CompiledJsonPath;
 
 use std::ops::Deref;
 use std::rc::Rc;
@@ -32,18 +33,17 @@ pub(super) type IterableValue = Box<dyn for<'ctx> Iterable<'ctx, Item = Iterable
 /// return Some if iterable isn't empty and None otherwise.
 pub(super) fn construct_iterable_value<'ctx>(
     ast_iterable: &ast::IterableValue<'ctx>,
-    exec_ctx: &ExecutionCtx<'ctx>,
+    exec_ctx: &ExecutionCtx,
 ) -> ExecutionResult<Option<IterableValue>> {
-    match ast_iterable {
-        ast::IterableValue::Variable(name) => handle_instruction_variable(exec_ctx, name),
-        ast::IterableValue::JsonPath { variable, path } => handle_instruction_json_path(exec_ctx, variable, path),
-    }
+    let iterable = match ast_iterable {
+        ast::IterableValue::Variable(name, _) => handle_instruction_variable(exec_ctx, name),
+        ast::IterableValue::JsonPath { variable, path, .. } => handle_instruction_json_path(exec_ctx, variable, path),
+    };
+
+    exec_ctx.point_at_value(Some(ast_iterable.span()), iterable)
 }
 
-fn handle_instruction_variable<'ctx>(
-    exec_ctx: &ExecutionCtx<'ctx>,
-    variable_name: &str,
-) -> ExecutionResult<Option<IterableValue>> {
+fn handle_instruction_variable(exec_ctx: &ExecutionCtx, variable_name: &str) -> ExecutionResult<Option<IterableValue>> {
     let iterable: Option<IterableValue> = match exec_ctx.data_cache.get(variable_name) {
         Some(AValue::JValueRef(call_result)) => from_call_result(call_result.clone())?,
         Some(AValue::JValueAccumulatorRef(acc)) => {
@@ -62,7 +62,11 @@ fn handle_instruction_variable<'ctx>(
             let result = Rc::new(jvalue.into_owned());
             let triplet = as_triplet(&iterable_value);
 
-            let call_result = ResolvedCallResult { result, triplet };
+            let call_result = ResolvedCallResult {
+                result,
+                triplet,
+                json_path: String::new(),
+            };
             from_call_result(call_result)?
         }
         _ => return Err(ExecutionError::VariableNotFound(variable_name.to_string())),
@@ -83,7 +87,8 @@ fn from_call_result(call_result: ResolvedCallResult) -> ExecutionResult<Option<I
             }
             array.len()
         }
-        v => return Err(IncompatibleJValueType((*v).clone(), "array")),
+        JValue::Object(object) => return Ok(from_object(object, call_result.triplet.clone(), &call_result.json_path)),
+        v => return Err(IncompatibleJValueType((*v).clone(), "array or object")),
     };
 
     let foldable = IterableResolvedCall::init(call_result, len);
@@ -92,17 +97,21 @@ fn from_call_result(call_result: ResolvedCallResult) -> ExecutionResult<Option<I
     Ok(Some(foldable))
 }
 
-fn handle_instruction_json_path<'ctx>(
-    exec_ctx: &ExecutionCtx<'ctx>,
+fn handle_instruction_json_path(
+    exec_ctx: &ExecutionCtx,
     variable_name: &str,
-    json_path: &str,
+    json_path: &CompiledJsonPath<'_>,
 ) -> ExecutionResult<Option<IterableValue>> {
     use ExecutionError::JValueAccJsonPathError;
 
     let iterable: Option<IterableValue> = match exec_ctx.data_cache.get(variable_name) {
         Some(AValue::JValueRef(variable)) => {
             let jvalues = apply_json_path(&variable.result, json_path)?;
-            from_jvalues(jvalues, variable.triplet.clone(), json_path)
+            let tetraplet = SecurityTetraplet {
+                triplet: variable.triplet.clone(),
+                json_path: variable.source_json_path(json_path.as_str()),
+            };
+            from_jvalues(jvalues, tetraplet, json_path.is_definite())
         }
         Some(AValue::JValueAccumulatorRef(acc)) => {
             let acc = acc.borrow();
@@ -111,7 +120,8 @@ fn handle_instruction_json_path<'ctx>(
             }
 
             let acc_iter = acc.iter().map(|v| v.result.deref());
-            let (jvalues, tetraplet_indices) = select_with_iter(acc_iter, &json_path)
+            let (jvalues, tetraplet_indices) = json_path
+                .select_with_iter(acc_iter)
                 .map_err(|e| JValueAccJsonPathError(acc.clone(), json_path.to_string(), e))?;
 
             let jvalues = jvalues.into_iter().cloned().collect();
@@ -119,7 +129,7 @@ fn handle_instruction_json_path<'ctx>(
                 .into_iter()
                 .map(|id| SecurityTetraplet {
                     triplet: acc[id].triplet.clone(),
-                    json_path: json_path.to_string(),
+                    json_path: acc[id].source_json_path(json_path.as_str()),
                 })
                 .collect::<Vec<_>>();
 
@@ -129,9 +139,12 @@ fn handle_instruction_json_path<'ctx>(
         Some(AValue::JValueFoldCursor(fold_state)) => {
             let iterable_value = fold_state.iterable.peek().unwrap();
             let jvalues = iterable_value.apply_json_path(json_path)?;
-            let triplet = as_triplet(&iterable_value);
+            let tetraplet = SecurityTetraplet {
+                triplet: as_triplet(&iterable_value),
+                json_path: json_path.to_string(),
+            };
 
-            from_jvalues(jvalues, triplet, json_path)
+            from_jvalues(jvalues, tetraplet, json_path.is_definite())
         }
         _ => return Err(ExecutionError::VariableNotFound(variable_name.to_string())),
     };
@@ -139,32 +152,48 @@ fn handle_instruction_json_path<'ctx>(
     Ok(iterable)
 }
 
-fn apply_json_path<'jvalue, 'str>(
+fn apply_json_path<'jvalue>(
     jvalue: &'jvalue JValue,
-    json_path: &'str str,
+    json_path: &CompiledJsonPath<'_>,
 ) -> ExecutionResult<Vec<&'jvalue JValue>> {
     use ExecutionError::JValueJsonPathError;
 
-    select(jvalue, json_path).map_err(|e| JValueJsonPathError(jvalue.clone(), json_path.to_string(), e))
+    json_path
+        .select(jvalue)
+        .map_err(|e| JValueJsonPathError(jvalue.clone(), json_path.to_string(), e))
 }
 
-/// Applies json_path to provided jvalues and construct IterableValue from the result and given triplet.
-fn from_jvalues(jvalues: Vec<&JValue>, triplet: Rc<ResolvedTriplet>, json_path: &str) -> Option<IterableValue> {
-    if jvalues.is_empty() {
-        return None;
+/// Constructs IterableValue from jvalues selected by a json path and a tetraplet of their source,
+/// `is_definite` tells whether the json path addresses a single value.
+fn from_jvalues(jvalues: Vec<&JValue>, tetraplet: SecurityTetraplet, is_definite: bool) -> Option<IterableValue> {
+    match jvalues.as_slice() {
+        [] => return None,
+        // an object addressed by a path is iterated by its entries like an object in a variable,
+        // but an object selected by a wildcard or a filter is iterated as a single element
+        [JValue::Object(object)] if is_definite => return from_object(object, tetraplet.triplet, &tetraplet.json_path),
+        _ => {}
     }
 
     let jvalues = jvalues.into_iter().cloned().collect();
 
-    let tetraplet = SecurityTetraplet {
-        triplet,
-        json_path: json_path.to_string(),
-    };
-
     let foldable = IterableJsonPathResult::init(jvalues, tetraplet);
     Some(Box::new(foldable))
 }
 
+/// Constructs iterable value from entries of an object obtained by the json path.
+fn from_object(
+    object: &serde_json::Map<String, JValue>,
+    triplet: Rc<ResolvedTriplet>,
+    json_path: &str,
+) -> Option<IterableValue> {
+    if object.is_empty() {
+        return None;
+    }
+
+    let foldable = IterableObjectEntries::init(object, triplet, json_path);
+    Some(Box::new(foldable))
+}
+
 fn as_triplet(iterable: &IterableItem<'_>) -> Rc<ResolvedTriplet> {
     use IterableItem::*;
 
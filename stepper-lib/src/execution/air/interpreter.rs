@@ -0,0 +1,331 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::ExecutableInstruction;
+use super::ExecutionCtx;
+use super::ExecutionResult;
+use super::ExecutionTraceCtx;
+use super::FailedInstruction;
+
+use air_parser::ast::Define;
+use air_parser::ast::Instruction;
+
+use std::collections::HashMap;
+
+/// An instruction with nested ones, it doesn't execute them directly, but schedules them
+/// to the interpreter work stack together with a continuation handling their result.
+pub(crate) trait SchedulableInstruction<'i> {
+    fn schedule<'a>(
+        &'a self,
+        interpreter: &mut Interpreter<'a, 'i>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> Step;
+}
+
+/// The outcome of one step of an instruction.
+pub(crate) enum Step {
+    /// The instruction is completed with such result.
+    Completed(ExecutionResult<()>),
+    /// The instruction has scheduled other instructions, and it'll be completed after them.
+    Scheduled,
+}
+
+/// Handles a result of scheduled instructions, it's called by the interpreter after them.
+pub(crate) type Continuation<'a, 'i> = Box<
+    dyn FnOnce(ExecutionResult<()>, &mut Interpreter<'a, 'i>, &mut ExecutionCtx, &mut ExecutionTraceCtx) -> Step + 'a,
+>;
+
+enum Task<'a, 'i> {
+    Execute(&'a Instruction<'i>),
+    Continue(&'a Instruction<'i>, Continuation<'a, 'i>),
+}
+
+/// Executes instructions in a loop over an explicit work stack instead of the native one,
+/// so the nesting of instructions and the number of fold iterations are limited only by the heap.
+pub(crate) struct Interpreter<'a, 'i> {
+    tasks: Vec<Task<'a, 'i>>,
+    /// The instruction which is executed or continued at the moment.
+    current: &'a Instruction<'i>,
+    /// Bodies of folds being executed by their iterators, next schedules the innermost one.
+    fold_bodies: HashMap<&'a str, Vec<&'a Instruction<'i>>>,
+    /// Procedures defined by the already executed define instructions.
+    procedures: HashMap<&'a str, &'a Define<'i>>,
+}
+
+impl<'a, 'i> Interpreter<'a, 'i> {
+    pub(crate) fn run(
+        instruction: &'a Instruction<'i>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> ExecutionResult<()> {
+        let mut interpreter = Self {
+            tasks: vec![Task::Execute(instruction)],
+            current: instruction,
+            fold_bodies: HashMap::new(),
+            procedures: HashMap::new(),
+        };
+
+        let mut result = Ok(());
+        while let Some(task) = interpreter.tasks.pop() {
+            let step = match task {
+                Task::Execute(instruction) => {
+                    interpreter.current = instruction;
+                    match charge_gas(exec_ctx, trace_ctx) {
+                        Ok(()) => interpreter.step(instruction, exec_ctx, trace_ctx),
+                        Err(e) => Step::Completed(Err(e)),
+                    }
+                }
+                Task::Continue(instruction, continuation) => {
+                    interpreter.current = instruction;
+                    // the result is moved, but a continuation either completes the instruction
+                    // or schedules new instructions that set it again
+                    let result = std::mem::replace(&mut result, Ok(()));
+                    continuation(result, &mut interpreter, exec_ctx, trace_ctx)
+                }
+            };
+
+            // a value could fail to resolve only in the step that has resolved it
+            let failed_value = exec_ctx.failed_value.take();
+            if let Step::Completed(step_result) = step {
+                // the innermost instruction is the first one that sees an error,
+                // so it shouldn't be overwritten by the enclosing instructions
+                if step_result.is_err() && exec_ctx.failed_instruction.is_none() {
+                    let instruction = interpreter.current;
+                    exec_ctx.failed_instruction = Some(FailedInstruction {
+                        span: failed_value.unwrap_or_else(|| instruction.span()),
+                        instruction: instruction.to_string(),
+                        peer_id: exec_ctx.init_peer_id.clone(),
+                    });
+                }
+                result = step_result;
+            }
+        }
+
+        // states produced by the last instructions haven't been checked yet
+        result.and_then(|_| exec_ctx.gas.check_trace_states(trace_ctx.produced_states_count()))
+    }
+
+    /// Schedules an instruction instead of the current one, so its result becomes the result of the current one.
+    pub(crate) fn execute(&mut self, instruction: &'a Instruction<'i>) -> Step {
+        self.tasks.push(Task::Execute(instruction));
+        Step::Scheduled
+    }
+
+    /// Schedules an instruction and a continuation of the current one called with its result.
+    pub(crate) fn execute_then(
+        &mut self,
+        instruction: &'a Instruction<'i>,
+        continuation: impl FnOnce(ExecutionResult<()>, &mut Self, &mut ExecutionCtx, &mut ExecutionTraceCtx) -> Step + 'a,
+    ) -> Step {
+        self.tasks.push(Task::Continue(self.current, Box::new(continuation)));
+        self.tasks.push(Task::Execute(instruction));
+        Step::Scheduled
+    }
+
+    /// Makes a fold body available to next over the fold iterator.
+    pub(crate) fn enter_fold(&mut self, iterator: &'a str, body: &'a Instruction<'i>) {
+        self.fold_bodies.entry(iterator).or_default().push(body);
+    }
+
+    pub(crate) fn leave_fold(&mut self, iterator: &str) {
+        if let Some(bodies) = self.fold_bodies.get_mut(iterator) {
+            bodies.pop();
+        }
+    }
+
+    /// Returns the body of the innermost fold with such iterator.
+    pub(crate) fn fold_body(&self, iterator: &str) -> Option<&'a Instruction<'i>> {
+        self.fold_bodies.get(iterator).and_then(|bodies| bodies.last().copied())
+    }
+
+    /// Makes a procedure available to invoke instructions executed after this call.
+    pub(crate) fn define(&mut self, define: &'a Define<'i>) {
+        self.procedures.insert(&define.name, define);
+    }
+
+    pub(crate) fn procedure(&self, name: &str) -> Option<&'a Define<'i>> {
+        self.procedures.get(name).copied()
+    }
+
+    fn step(
+        &mut self,
+        instruction: &'a Instruction<'i>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> Step {
+        match instruction {
+            Instruction::Call(call) => Step::Completed(call.execute(exec_ctx, trace_ctx)),
+            Instruction::Ap(ap) => Step::Completed(ap.execute(exec_ctx, trace_ctx)),
+            Instruction::Null(null) => Step::Completed(null.execute(exec_ctx, trace_ctx)),
+            Instruction::Fail(fail) => Step::Completed(fail.execute(exec_ctx, trace_ctx)),
+            Instruction::Fold(fold) => fold.schedule(self, exec_ctx, trace_ctx),
+            Instruction::Next(next) => next.schedule(self, exec_ctx, trace_ctx),
+            Instruction::Par(par) => par.schedule(self, exec_ctx, trace_ctx),
+            Instruction::Seq(seq) => seq.schedule(self, exec_ctx, trace_ctx),
+            Instruction::Xor(xor) => xor.schedule(self, exec_ctx, trace_ctx),
+            Instruction::Match(match_) => match_.schedule(self, exec_ctx, trace_ctx),
+            Instruction::MisMatch(mismatch) => mismatch.schedule(self, exec_ctx, trace_ctx),
+            Instruction::Compare(compare) => compare.schedule(self, exec_ctx, trace_ctx),
+            Instruction::New(new) => new.schedule(self, exec_ctx, trace_ctx),
+            Instruction::Define(define) => define.schedule(self, exec_ctx, trace_ctx),
+            Instruction::Invoke(invoke) => invoke.schedule(self, exec_ctx, trace_ctx),
+            Instruction::Error => unreachable!("should not execute if parsing succeeded. QED."),
+        }
+    }
+}
+
+/// Charges gas for an instruction that is going to be executed and for trace states produced before it.
+fn charge_gas(exec_ctx: &mut ExecutionCtx, trace_ctx: &ExecutionTraceCtx) -> ExecutionResult<()> {
+    exec_ctx.gas.check_trace_states(trace_ctx.produced_states_count())?;
+    exec_ctx.gas.charge_instruction()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interpreter;
+    use crate::contexts::execution::ExecutionCtx;
+    use crate::contexts::execution_trace::ExecutionTraceCtx;
+    use crate::execution::ExecutionError;
+    use crate::Gas;
+    use crate::RunParameters;
+
+    fn execute_with_gas_limit(script: &str, gas_limit: Gas) -> (Result<(), ExecutionError>, Gas) {
+        let instruction = air_parser::parse(script).expect("script should be valid");
+        let params = RunParameters {
+            gas_limit,
+            ..RunParameters::default()
+        };
+
+        let mut exec_ctx = ExecutionCtx::new(String::from("local_peer_id"), params);
+        let mut trace_ctx = ExecutionTraceCtx::default();
+        let result = Interpreter::run(&instruction, &mut exec_ctx, &mut trace_ctx);
+
+        (result, exec_ctx.gas.used())
+    }
+
+    #[test]
+    fn instructions_limit() {
+        let script = r#"
+            (seq
+                (ap [1 2 3 4 5] array)
+                (fold array i
+                    (seq
+                        (null)
+                        (next i)
+                    )
+                )
+            )"#;
+
+        let (result, gas_used) = execute_with_gas_limit(script, Gas::default());
+        assert!(result.is_ok());
+        assert_eq!(gas_used.instructions, 18);
+        assert_eq!(gas_used.trace_states, 1);
+
+        let gas_limit = Gas {
+            instructions: 10,
+            ..Gas::default()
+        };
+        let (result, gas_used) = execute_with_gas_limit(script, gas_limit);
+        assert!(matches!(result, Err(ExecutionError::GasExhausted("instructions", 10))));
+        assert_eq!(gas_used.instructions, 10);
+    }
+
+    #[test]
+    fn trace_states_limit() {
+        let script = r#"
+            (seq
+                (ap 1 a)
+                (seq
+                    (ap 2 b)
+                    (ap 3 c)
+                )
+            )"#;
+
+        let gas_limit = Gas {
+            trace_states: 3,
+            ..Gas::default()
+        };
+        let (result, gas_used) = execute_with_gas_limit(script, gas_limit);
+        assert!(result.is_ok());
+        assert_eq!(gas_used.trace_states, 3);
+
+        let gas_limit = Gas {
+            trace_states: 2,
+            ..Gas::default()
+        };
+        let (result, gas_used) = execute_with_gas_limit(script, gas_limit);
+        assert!(matches!(result, Err(ExecutionError::GasExhausted("trace states", 2))));
+        assert_eq!(gas_used.trace_states, 3);
+    }
+
+    #[test]
+    fn replayed_trace_states_are_not_charged() {
+        use crate::contexts::execution_trace::ExecutedState;
+        use crate::contexts::execution_trace::ExecutionTrace;
+        use crate::JValue;
+
+        use std::rc::Rc;
+
+        let script = r#"
+            (seq
+                (ap 1 a)
+                (seq
+                    (ap 2 b)
+                    (ap 3 c)
+                )
+            )"#;
+        let instruction = air_parser::parse(script).expect("script should be valid");
+        let params = RunParameters {
+            gas_limit: Gas {
+                trace_states: 1,
+                ..Gas::default()
+            },
+            ..RunParameters::default()
+        };
+
+        let prev_trace = vec![
+            ExecutedState::Ap(Rc::new(JValue::from(1))),
+            ExecutedState::Ap(Rc::new(JValue::from(2))),
+        ];
+        let mut exec_ctx = ExecutionCtx::new(String::from("local_peer_id"), params);
+        let mut trace_ctx = ExecutionTraceCtx::new(ExecutionTrace::from(prev_trace));
+        let result = Interpreter::run(&instruction, &mut exec_ctx, &mut trace_ctx);
+
+        assert!(result.is_ok());
+        assert_eq!(trace_ctx.new_trace.len(), 3);
+        assert_eq!(exec_ctx.gas.used().trace_states, 1);
+    }
+
+    #[test]
+    fn gas_exhaustion_is_not_caught_by_xor() {
+        let script = r#"
+            (xor
+                (seq
+                    (null)
+                    (null)
+                )
+                (null)
+            )"#;
+
+        let gas_limit = Gas {
+            instructions: 3,
+            ..Gas::default()
+        };
+        let (result, _) = execute_with_gas_limit(script, gas_limit);
+        assert!(matches!(result, Err(ExecutionError::GasExhausted("instructions", 3))));
+    }
+}
@@ -0,0 +1,354 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::ap::resolve_value;
+use super::call::set_local_result;
+use super::joinable::is_joinable_error_type;
+use super::ExecutionCtx;
+use super::ExecutionError;
+use super::ExecutionResult;
+use super::ExecutionTraceCtx;
+use super::Interpreter;
+use super::Step;
+use crate::contexts::execution::AValue;
+use crate::contexts::execution::ResolvedCallResult;
+use crate::contexts::execution_trace::ExecutedState;
+use crate::joinable;
+use crate::log_instruction;
+use crate::log_targets::EXECUTED_STATE_CHANGING;
+use crate::JValue;
+use crate::SecurityTetraplet;
+
+use air_parser::ast::CallArgValue;
+use air_parser::ast::Define;
+use air_parser::ast::Invoke;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+impl<'i> super::SchedulableInstruction<'i> for Invoke<'i> {
+    fn schedule<'a>(
+        &'a self,
+        interpreter: &mut Interpreter<'a, 'i>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> Step {
+        log_instruction!(invoke, exec_ctx, trace_ctx);
+
+        let procedure = match find_procedure(self, interpreter) {
+            Ok(procedure) => procedure,
+            Err(e) => return Step::Completed(Err(e)),
+        };
+
+        // like a call, an invocation waits for all its arguments
+        let arguments = match bind_arguments(procedure, self, exec_ctx) {
+            Ok(arguments) => arguments,
+            Err(e) if is_joinable_error_type(&e) => {
+                exec_ctx.subtree_complete = false;
+                return Step::Completed(Ok(()));
+            }
+            Err(e) => return Step::Completed(Err(e)),
+        };
+
+        let region = match InvocationRegion::start(&self.name, trace_ctx) {
+            Ok(region) => region,
+            Err(e) => return Step::Completed(Err(e)),
+        };
+
+        // a procedure body sees only its parameters, so variables of the caller are hidden until it's completed
+        let caller_data_cache = std::mem::replace(&mut exec_ctx.data_cache, arguments);
+        let caller_scopes = std::mem::take(&mut exec_ctx.scopes);
+
+        interpreter.execute_then(&procedure.body, move |result, _, exec_ctx, trace_ctx| {
+            let output = procedure
+                .output
+                .as_deref()
+                .map(|output| resolve_value(&CallArgValue::Variable(Cow::Borrowed(output), self.span), exec_ctx));
+
+            exec_ctx.data_cache = caller_data_cache;
+            exec_ctx.scopes = caller_scopes;
+
+            let result = region
+                .finish(result, trace_ctx)
+                .and_then(|_| set_output(output, self, exec_ctx));
+
+            Step::Completed(result)
+        })
+    }
+}
+
+fn find_procedure<'a, 'i>(invoke: &Invoke<'i>, interpreter: &Interpreter<'a, 'i>) -> ExecutionResult<&'a Define<'i>> {
+    use ExecutionError::InstructionError;
+
+    let procedure = interpreter
+        .procedure(&invoke.name)
+        .ok_or_else(|| InstructionError(format!("procedure '{}' isn't defined", invoke.name)))?;
+
+    if procedure.parameters.len() != invoke.args.len() {
+        return Err(InstructionError(format!(
+            "procedure '{}' takes {} arguments, but {} are supplied",
+            invoke.name,
+            procedure.parameters.len(),
+            invoke.args.len()
+        )));
+    }
+
+    Ok(procedure)
+}
+
+/// Resolves arguments of an invocation to variables of a procedure body named by its parameters.
+fn bind_arguments(
+    procedure: &Define<'_>,
+    invoke: &Invoke<'_>,
+    exec_ctx: &ExecutionCtx,
+) -> ExecutionResult<HashMap<String, AValue>> {
+    let mut arguments = HashMap::with_capacity(procedure.parameters.len());
+
+    for (parameter, arg) in procedure.parameters.iter().zip(invoke.args.iter()) {
+        let (result, tetraplet) = resolve_value(arg, exec_ctx)?;
+        let argument = ResolvedCallResult::from_tetraplet(Rc::new(result), tetraplet);
+        arguments.insert(parameter.to_string(), AValue::JValueRef(argument));
+    }
+
+    Ok(arguments)
+}
+
+/// Sets a value returned by a procedure body to the invocation output,
+/// it isn't set if the body hasn't produced it yet.
+fn set_output(
+    output: Option<ExecutionResult<(JValue, SecurityTetraplet)>>,
+    invoke: &Invoke<'_>,
+    exec_ctx: &mut ExecutionCtx,
+) -> ExecutionResult<()> {
+    let (result, tetraplet) = match output {
+        Some(output) => joinable!(output, exec_ctx)?,
+        None => return Ok(()),
+    };
+
+    let executed_result = ResolvedCallResult::from_tetraplet(Rc::new(result), tetraplet);
+    set_local_result(executed_result, &invoke.output, exec_ctx)
+}
+
+/// States of a procedure body are placed after an Invoke state holding their count, so every invocation
+/// has its own region in the trace, the region is merged with the one of the same invocation on other peers.
+struct InvocationRegion {
+    invoke_pos: usize,
+    prev_size: usize,
+    before_subtree_size: usize,
+    before_new_trace_len: usize,
+}
+
+impl InvocationRegion {
+    fn start(name: &str, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<Self> {
+        let prev_size = prev_region_size(name, trace_ctx)?;
+
+        let invoke_pos = trace_ctx.new_trace.len();
+        trace_ctx.new_trace.push_back(ExecutedState::Invoke {
+            name: name.to_string(),
+            size: 0,
+        });
+
+        let before_subtree_size = trace_ctx.current_subtree_size;
+        trace_ctx.current_subtree_size = prev_size;
+
+        Ok(Self {
+            invoke_pos,
+            prev_size,
+            before_subtree_size,
+            before_new_trace_len: trace_ctx.new_trace.len(),
+        })
+    }
+
+    fn finish(self, result: ExecutionResult<()>, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
+        // as in par, the region is updated and left before an error is bubbled up
+        self.update_region_size(trace_ctx);
+        trace_ctx.current_subtree_size = self.before_subtree_size - self.prev_size;
+
+        result
+    }
+
+    fn update_region_size(&self, trace_ctx: &mut ExecutionTraceCtx) {
+        let new_size = trace_ctx.new_trace.len() - self.before_new_trace_len;
+
+        // unwrap is safe here, because this state is added at the beginning of the invocation
+        match trace_ctx.new_trace.get_mut(self.invoke_pos).unwrap() {
+            ExecutedState::Invoke { size, .. } => {
+                *size = new_size;
+                log::trace!(target: EXECUTED_STATE_CHANGING, "  set invoke region size to {}", new_size);
+            }
+            _ => unreachable!("invoke_pos must point to an invoke state"),
+        }
+    }
+}
+
+fn prev_region_size(name: &str, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<usize> {
+    if trace_ctx.current_subtree_size == 0 {
+        return Ok(0);
+    }
+
+    trace_ctx.current_subtree_size -= 1;
+    // unwrap is safe here because of length's been checked
+    let prev_state = trace_ctx.current_trace.pop_front().unwrap();
+
+    log::trace!(
+        target: EXECUTED_STATE_CHANGING,
+        "  previous invoke executed state was found {:?}",
+        prev_state
+    );
+
+    match prev_state {
+        ExecutedState::Invoke { name: prev_name, size } if prev_name == name => Ok(size),
+        state => Err(ExecutionError::InvalidExecutedState(format!("invoke {}", name), state)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contexts::execution_trace::ExecutionTrace;
+    use crate::JValue;
+
+    use aqua_test_utils::call_vm;
+    use aqua_test_utils::create_aqua_vm;
+    use aqua_test_utils::echo_string_call_service;
+
+    use std::rc::Rc;
+
+    // Check that parameters are bound to arguments and each invocation has its own region of the trace.
+    #[test]
+    fn invoke_binds_parameters() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (define echo [peer value] result
+                (call peer ("" "") [value] result)
+            )
+            (seq
+                (call "{0}" ("" "") ["outer"] value)
+                (seq
+                    (invoke echo ["{0}" "first"] first)
+                    (seq
+                        (invoke echo ["{0}" value] second)
+                        (call "{0}" ("" "") [first] result)
+                    )
+                )
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "", "");
+        assert_eq!(res.ret_code, 0);
+
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        let executed = |value: &str| Call(Executed(Rc::new(JValue::String(String::from(value)))));
+        let invoke = Invoke {
+            name: String::from("echo"),
+            size: 1,
+        };
+
+        let expected_trace = vec![
+            executed("outer"),
+            invoke.clone(),
+            executed("first"),
+            invoke,
+            executed("outer"),
+            executed("first"),
+        ];
+        assert_eq!(actual_trace, expected_trace.into_iter().collect::<ExecutionTrace>());
+    }
+
+    // Check that a procedure body doesn't see variables of the caller and doesn't change them.
+    #[test]
+    fn invoke_isolates_variables() {
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (define set [] result
+                (ap "inner" result)
+            )
+            (seq
+                (ap "outer" result)
+                (seq
+                    (invoke set [] inner)
+                    (seq
+                        (call "{0}" ("" "") [result] outer_result)
+                        (call "{0}" ("" "") [inner] inner_result)
+                    )
+                )
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "", "");
+        assert_eq!(res.ret_code, 0);
+
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        assert_eq!(actual_trace.len(), 5);
+        assert_eq!(
+            actual_trace[3].to_string(),
+            format!("Executed({:?})", JValue::String(String::from("outer")))
+        );
+        assert_eq!(
+            actual_trace[4].to_string(),
+            format!("Executed({:?})", JValue::String(String::from("inner")))
+        );
+    }
+
+    // Check that invocations executed on different peers are merged region by region.
+    #[test]
+    fn invocations_on_several_peers() {
+        let mut vm_a = create_aqua_vm(echo_string_call_service(), "A");
+        let mut vm_b = create_aqua_vm(echo_string_call_service(), "B");
+
+        let script = r#"
+            (define echo [peer] result
+                (call peer ("" "") [peer] result)
+            )
+            (par
+                (invoke echo ["A"] a)
+                (invoke echo ["B"] b)
+            )"#;
+
+        let res_b = call_vm!(vm_b, "asd", script, "", "");
+        assert_eq!(res_b.next_peer_pks, vec![String::from("A")]);
+
+        let res_a = call_vm!(vm_a, "asd", script, "", res_b.data.clone());
+        assert!(res_a.next_peer_pks.is_empty());
+
+        let res_b = call_vm!(vm_b, "asd", script, res_b.data, res_a.data.clone());
+        assert_eq!(res_b.data, res_a.data);
+
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res_a.data).expect("should be valid json");
+        let actual_trace = actual_trace.iter().map(|state| state.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            actual_trace,
+            vec![
+                String::from("Par(2, 2)"),
+                String::from("Invoke(echo, 1)"),
+                format!("Executed({:?})", JValue::String(String::from("A"))),
+                String::from("Invoke(echo, 1)"),
+                format!("Executed({:?})", JValue::String(String::from("B"))),
+            ]
+        );
+    }
+}
@@ -0,0 +1,60 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::ExecutionError;
+
+/// This macro converts joinable errors to Ok and sets subtree complete to false.
+#[macro_export]
+macro_rules! joinable {
+    ($cmd:expr, $exec_ctx:expr) => {
+        match $cmd {
+            Err(e) if $crate::execution::air::joinable::is_joinable_error_type(&e) => {
+                $exec_ctx.subtree_complete = false;
+                return Ok(());
+            }
+            v => v,
+        }
+    };
+}
+
+macro_rules! log_join {
+    ($($args:tt)*) => {
+        log::trace!(target: crate::log_targets::JOIN_BEHAVIOUR, $($args)*)
+    }
+}
+
+/// Returns true, if supplied error is related to variable not found errors type.
+/// Print log if this is joinable error type.
+#[rustfmt::skip::macros(log_join)]
+pub(super) fn is_joinable_error_type(exec_error: &ExecutionError) -> bool {
+    use ExecutionError::*;
+
+    match exec_error {
+        VariableNotFound(var_name) => {
+            log_join!("  instruction is waiting for an argument with name '{}'", var_name);
+            true
+        }
+        JValueJsonPathError(value, json_path, _) => {
+            log_join!("  instruction is waiting for an argument with path '{}' on jvalue '{:?}'", json_path, value);
+            true
+        }
+        JValueAccJsonPathError(acc, json_path, _) => {
+            log_join!("  instruction is waiting for an argument with path '{}' on accumulator '{:?}'", json_path, acc);
+            true
+        }
+        _ => false,
+    }
+}
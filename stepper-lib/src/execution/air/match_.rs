@@ -17,23 +17,32 @@
 use super::compare_matchable::are_matchable_eq;
 use super::ExecutionCtx;
 use super::ExecutionError;
-use super::ExecutionResult;
 use super::ExecutionTraceCtx;
+use super::Interpreter;
+use super::Step;
 use crate::log_instruction;
 
 use air_parser::ast::Match;
 
-impl<'i> super::ExecutableInstruction<'i> for Match<'i> {
-    fn execute(&self, exec_ctx: &mut ExecutionCtx<'i>, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
+impl<'i> super::SchedulableInstruction<'i> for Match<'i> {
+    fn schedule<'a>(
+        &'a self,
+        interpreter: &mut Interpreter<'a, 'i>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> Step {
         log_instruction!(match_, exec_ctx, trace_ctx);
 
-        let are_values_equal = are_matchable_eq(&self.left_value, &self.right_value, exec_ctx)?;
+        let are_values_equal = match are_matchable_eq(&self.left_value, &self.right_value, exec_ctx) {
+            Ok(are_values_equal) => are_values_equal,
+            Err(e) => return Step::Completed(Err(e)),
+        };
 
         if !are_values_equal {
-            return Err(ExecutionError::MatchWithoutXorError);
+            return Step::Completed(Err(ExecutionError::MatchWithoutXorError));
         }
 
-        self.instruction.execute(exec_ctx, trace_ctx)
+        interpreter.execute(&self.instruction)
     }
 }
 
@@ -45,6 +54,7 @@ mod tests {
     use aqua_test_utils::call_vm;
     use aqua_test_utils::create_aqua_vm;
     use aqua_test_utils::echo_string_call_service;
+    use aqua_test_utils::set_variable_call_service;
 
     use std::rc::Rc;
 
@@ -159,6 +169,205 @@ mod tests {
         assert_eq!(actual_trace[1], expected_executed_call_result);
     }
 
+    #[test]
+    fn match_with_escaped_string() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (seq
+                (call "{0}" ("" "") ["quote \" and \\ with\ttab"] value_1)
+                (xor
+                    (match value_1 "quote \" and \\ with\u{{9}}tab"
+                        (call "{0}" ("service_id_2" "local_fn_name") ["result_1"] result_1)
+                    )
+                    (call "{0}" ("service_id_2" "local_fn_name") ["result_2"] result_2)
+                )
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "", "");
+
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        let expected_value = Call(Executed(Rc::new(JValue::String(String::from(
+            "quote \" and \\ with\ttab",
+        )))));
+        let expected_executed_call_result = Call(Executed(Rc::new(JValue::String(String::from("result_1")))));
+
+        assert_eq!(actual_trace.len(), 2);
+        assert_eq!(actual_trace[0], expected_value);
+        assert_eq!(actual_trace[1], expected_executed_call_result);
+    }
+
+    #[test]
+    fn match_typed_literals() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let set_variable_peer_id = "set_variable_peer_id";
+        let mut set_variable_vm = create_aqua_vm(
+            set_variable_call_service(r#"{"number": 42, "flag": true, "array": [1, "a"]}"#),
+            set_variable_peer_id,
+        );
+
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (seq
+                (call "{0}" ("" "") [] value)
+                (seq
+                    (match value.$.number 42
+                        (match value.$.flag true
+                            (match value.$.array [1 "a"]
+                                (call "{1}" ("" "") ["matched"] result_1)
+                            )
+                        )
+                    )
+                    (mismatch value.$.number "42"
+                        (call "{1}" ("" "") ["mismatched"] result_2)
+                    )
+                )
+            )"#,
+            set_variable_peer_id, local_peer_id
+        );
+
+        let res = call_vm!(set_variable_vm, "asd", script.clone(), "", "");
+        let res = call_vm!(vm, "asd", script, "", res.data);
+
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        let expected_matched = Call(Executed(Rc::new(JValue::String(String::from("matched")))));
+        let expected_mismatched = Call(Executed(Rc::new(JValue::String(String::from("mismatched")))));
+
+        assert_eq!(actual_trace.len(), 3);
+        assert_eq!(actual_trace[1], expected_matched);
+        assert_eq!(actual_trace[2], expected_mismatched);
+    }
+
+    #[test]
+    fn match_variable_with_json_path() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let set_variable_peer_id = "set_variable_peer_id";
+        let mut set_variable_vm = create_aqua_vm(
+            set_variable_call_service(r#"{"number": 1.0, "object": {"a": [1, {"b": 2}]}}"#),
+            set_variable_peer_id,
+        );
+
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (seq
+                (seq
+                    (call "{0}" ("" "") [] value)
+                    (call "{0}" ("" "") [] other_value)
+                )
+                (seq
+                    (match value.$.number 1
+                        (match value.$.object other_value.$.object
+                            (match value value
+                                (call "{1}" ("" "") ["matched"] result_1)
+                            )
+                        )
+                    )
+                    (mismatch value value.$.object
+                        (call "{1}" ("" "") ["mismatched"] result_2)
+                    )
+                )
+            )"#,
+            set_variable_peer_id, local_peer_id
+        );
+
+        let res = call_vm!(set_variable_vm, "asd", script.clone(), "", "");
+        let res = call_vm!(vm, "asd", script, "", res.data);
+
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        let expected_matched = Call(Executed(Rc::new(JValue::String(String::from("matched")))));
+        let expected_mismatched = Call(Executed(Rc::new(JValue::String(String::from("mismatched")))));
+
+        assert_eq!(actual_trace.len(), 4);
+        assert_eq!(actual_trace[2], expected_matched);
+        assert_eq!(actual_trace[3], expected_mismatched);
+    }
+
+    #[test]
+    fn match_indefinite_json_path_as_array() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let set_variable_peer_id = "set_variable_peer_id";
+        let mut set_variable_vm = create_aqua_vm(
+            set_variable_call_service(r#"{"one": [1], "many": [1, 2]}"#),
+            set_variable_peer_id,
+        );
+
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (seq
+                (call "{0}" ("" "") [] value)
+                (seq
+                    (match value.$.one[*] [1]
+                        (match value.$.many[*] [1 2]
+                            (call "{1}" ("" "") ["matched"] result_1)
+                        )
+                    )
+                    (mismatch value.$.one[*] 1
+                        (call "{1}" ("" "") ["mismatched"] result_2)
+                    )
+                )
+            )"#,
+            set_variable_peer_id, local_peer_id
+        );
+
+        let res = call_vm!(set_variable_vm, "asd", script.clone(), "", "");
+        let res = call_vm!(vm, "asd", script, "", res.data);
+
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        let expected_matched = Call(Executed(Rc::new(JValue::String(String::from("matched")))));
+        let expected_mismatched = Call(Executed(Rc::new(JValue::String(String::from("mismatched")))));
+
+        assert_eq!(actual_trace.len(), 3);
+        assert_eq!(actual_trace[1], expected_matched);
+        assert_eq!(actual_trace[2], expected_mismatched);
+    }
+
+    #[test]
+    fn match_definite_json_path_without_value() {
+        let set_variable_peer_id = "set_variable_peer_id";
+        let mut set_variable_vm = create_aqua_vm(set_variable_call_service(r#"{"one": [1]}"#), set_variable_peer_id);
+
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (seq
+                (call "{0}" ("" "") [] value)
+                (match value.$.one[1] null
+                    (call "{1}" ("" "") ["matched"] result)
+                )
+            )"#,
+            set_variable_peer_id, local_peer_id
+        );
+
+        let res = call_vm!(set_variable_vm, "asd", script.clone(), "", "");
+        let res = call_vm!(vm, "asd", script, "", res.data);
+
+        assert_eq!(res.ret_code, 1010);
+    }
+
     #[test]
     fn match_without_xor() {
         let set_variable_peer_id = "set_variable_peer_id";
@@ -185,6 +394,8 @@ mod tests {
         let res = call_vm!(vm, "asd", script.clone(), "", res.data);
 
         assert_eq!(res.ret_code, 1015);
+        assert!(res.error_message.contains("--> 7:17"));
+        assert!(res.error_message.contains("| (match value_1 value_2 ..."));
 
         let res = call_vm!(vm, "asd", script, "", res.data);
 
@@ -17,23 +17,32 @@
 use super::compare_matchable::are_matchable_eq;
 use super::ExecutionCtx;
 use super::ExecutionError;
-use super::ExecutionResult;
 use super::ExecutionTraceCtx;
+use super::Interpreter;
+use super::Step;
 use crate::log_instruction;
 
 use air_parser::ast::MisMatch;
 
-impl<'i> super::ExecutableInstruction<'i> for MisMatch<'i> {
-    fn execute(&self, exec_ctx: &mut ExecutionCtx<'i>, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
+impl<'i> super::SchedulableInstruction<'i> for MisMatch<'i> {
+    fn schedule<'a>(
+        &'a self,
+        interpreter: &mut Interpreter<'a, 'i>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> Step {
         log_instruction!(match_, exec_ctx, trace_ctx);
 
-        let are_values_equal = are_matchable_eq(&self.left_value, &self.right_value, exec_ctx)?;
+        let are_values_equal = match are_matchable_eq(&self.left_value, &self.right_value, exec_ctx) {
+            Ok(are_values_equal) => are_values_equal,
+            Err(e) => return Step::Completed(Err(e)),
+        };
 
         if are_values_equal {
-            return Err(ExecutionError::MatchWithoutXorError);
+            return Step::Completed(Err(ExecutionError::MatchWithoutXorError));
         }
 
-        self.instruction.execute(exec_ctx, trace_ctx)
+        interpreter.execute(&self.instruction)
     }
 }
 
@@ -14,11 +14,19 @@
  * limitations under the License.
  */
 
+mod ap;
 mod call;
+mod compare;
 mod compare_matchable;
+mod define;
+mod fail;
 mod fold;
+mod interpreter;
+mod invoke;
+mod joinable;
 mod match_;
 mod mismatch;
+mod new;
 mod null;
 mod par;
 mod seq;
@@ -29,28 +37,22 @@ pub(crate) use fold::FoldState;
 pub(self) use super::ExecutionError;
 pub(self) use super::ExecutionResult;
 pub(self) use crate::contexts::execution::ExecutionCtx;
+pub(self) use crate::contexts::execution::FailedInstruction;
 pub(self) use crate::contexts::execution_trace::ExecutionTraceCtx;
+pub(self) use interpreter::Interpreter;
+pub(self) use interpreter::SchedulableInstruction;
+pub(self) use interpreter::Step;
 
 use air_parser::ast::Instruction;
 
+/// An instruction executed in one step, instructions with nested ones are executed by the interpreter.
 pub(crate) trait ExecutableInstruction<'i> {
-    fn execute(&self, exec_ctx: &mut ExecutionCtx<'i>, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()>;
+    fn execute(&self, exec_ctx: &mut ExecutionCtx, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()>;
 }
 
 impl<'i> ExecutableInstruction<'i> for Instruction<'i> {
-    fn execute(&self, exec_ctx: &mut ExecutionCtx<'i>, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
-        match self {
-            Instruction::Call(call) => call.execute(exec_ctx, trace_ctx),
-            Instruction::Fold(fold) => fold.execute(exec_ctx, trace_ctx),
-            Instruction::Next(next) => next.execute(exec_ctx, trace_ctx),
-            Instruction::Null(null) => null.execute(exec_ctx, trace_ctx),
-            Instruction::Par(par) => par.execute(exec_ctx, trace_ctx),
-            Instruction::Seq(seq) => seq.execute(exec_ctx, trace_ctx),
-            Instruction::Xor(xor) => xor.execute(exec_ctx, trace_ctx),
-            Instruction::Match(match_) => match_.execute(exec_ctx, trace_ctx),
-            Instruction::MisMatch(mismatch) => mismatch.execute(exec_ctx, trace_ctx),
-            Instruction::Error => unreachable!("should not execute if parsing succeeded. QED."),
-        }
+    fn execute(&self, exec_ctx: &mut ExecutionCtx, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
+        Interpreter::run(self, exec_ctx, trace_ctx)
     }
 }
 
@@ -59,15 +61,18 @@ macro_rules! log_instruction {
     ($instr_name:expr, $exec_ctx:expr, $trace_ctx:expr) => {
         log::debug!(target: crate::log_targets::INSTRUCTION, "> {}", stringify!($instr_name));
 
-        let mut data_cache_log = String::from("  data cache:");
-        if $exec_ctx.data_cache.is_empty() {
-            data_cache_log.push_str(" empty");
-        }
-        for (key, value) in $exec_ctx.data_cache.iter() {
-            data_cache_log.push_str(&format!("\n    {} => {}", key, value));
-        }
+        // formatting of the whole data cache is expensive, so it's done only if it's logged
+        if log::log_enabled!(target: crate::log_targets::DATA_CACHE, log::Level::Trace) {
+            let mut data_cache_log = String::from("  data cache:");
+            if $exec_ctx.data_cache.is_empty() {
+                data_cache_log.push_str(" empty");
+            }
+            for (key, value) in $exec_ctx.data_cache.iter() {
+                data_cache_log.push_str(&format!("\n    {} => {}", key, value));
+            }
 
-        log::trace!(target: crate::log_targets::DATA_CACHE, "{}", data_cache_log);
+            log::trace!(target: crate::log_targets::DATA_CACHE, "{}", data_cache_log);
+        }
         log::trace!(
             target: crate::log_targets::NEXT_PEER_PKS,
             "  next peers pk: {:?}",
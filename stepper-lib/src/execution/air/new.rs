@@ -0,0 +1,202 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::ExecutionCtx;
+use super::ExecutionError;
+use super::ExecutionTraceCtx;
+use super::Interpreter;
+use super::Step;
+use crate::log_instruction;
+
+use air_parser::ast::CallOutputValue;
+use air_parser::ast::New;
+
+impl<'i> super::SchedulableInstruction<'i> for New<'i> {
+    fn schedule<'a>(
+        &'a self,
+        interpreter: &mut Interpreter<'a, 'i>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> Step {
+        log_instruction!(new, exec_ctx, trace_ctx);
+
+        let name = match &self.variable {
+            CallOutputValue::Scalar(name) | CallOutputValue::Accumulator(name) => name,
+            CallOutputValue::None => {
+                return Step::Completed(Err(ExecutionError::InstructionError(String::from(
+                    "new should be used with a scalar or an accumulator",
+                ))))
+            }
+        };
+
+        exec_ctx.open_scope_for(name);
+
+        interpreter.execute_then(&self.instruction, |result, _, exec_ctx, _| {
+            exec_ctx.close_scope();
+            Step::Completed(result)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contexts::execution_trace::ExecutionTrace;
+    use crate::JValue;
+
+    use aqua_test_utils::call_vm;
+    use aqua_test_utils::create_aqua_vm;
+    use aqua_test_utils::echo_string_call_service;
+
+    use std::rc::Rc;
+
+    #[test]
+    fn new_scalar() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (seq
+                (seq
+                    (call "{0}" ("" "") ["outer"] value)
+                    (new value
+                        (seq
+                            (seq
+                                (call "{0}" ("" "") ["inner"] value)
+                                (call "{0}" ("" "") ["redefined"] value)
+                            )
+                            (call "{0}" ("" "") [value] inner_result)
+                        )
+                    )
+                )
+                (call "{0}" ("" "") [value] outer_result)
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "", "");
+        assert_eq!(res.ret_code, 0);
+
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        let expected_inner = Call(Executed(Rc::new(JValue::String(String::from("redefined")))));
+        let expected_outer = Call(Executed(Rc::new(JValue::String(String::from("outer")))));
+
+        assert_eq!(actual_trace.len(), 5);
+        assert_eq!(actual_trace[3], expected_inner);
+        assert_eq!(actual_trace[4], expected_outer);
+    }
+
+    #[test]
+    fn new_accumulator() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (seq
+                (seq
+                    (ap "outer" acc[])
+                    (new acc[]
+                        (seq
+                            (ap "inner" acc[])
+                            (match acc ["inner"]
+                                (call "{0}" ("" "") ["inner matched"] inner_result)
+                            )
+                        )
+                    )
+                )
+                (match acc ["outer"]
+                    (call "{0}" ("" "") ["outer matched"] outer_result)
+                )
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "", "");
+        assert_eq!(res.ret_code, 0);
+
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        let expected_inner = Call(Executed(Rc::new(JValue::String(String::from("inner matched")))));
+        let expected_outer = Call(Executed(Rc::new(JValue::String(String::from("outer matched")))));
+
+        assert_eq!(actual_trace.len(), 4);
+        assert_eq!(actual_trace[2], expected_inner);
+        assert_eq!(actual_trace[3], expected_outer);
+    }
+
+    #[test]
+    fn new_undefines_variable() {
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (seq
+                (call "{0}" ("" "") ["outer"] value)
+                (new value
+                    (call "{0}" ("" "") [value] result)
+                )
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "", "");
+        assert_eq!(res.ret_code, 0);
+
+        // the call waits for the variable to be set inside the scope
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        assert_eq!(actual_trace.len(), 1);
+    }
+
+    #[test]
+    fn new_keeps_other_variables() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_string_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (seq
+                (new value
+                    (seq
+                        (call "{0}" ("" "") ["value"] value)
+                        (call "{0}" ("" "") ["sibling"] sibling)
+                    )
+                )
+                (call "{0}" ("" "") [sibling] result)
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "", "");
+        assert_eq!(res.ret_code, 0);
+
+        // only the declared variable is scoped by new
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        let expected_result = Call(Executed(Rc::new(JValue::String(String::from("sibling")))));
+
+        assert_eq!(actual_trace.len(), 3);
+        assert_eq!(actual_trace[2], expected_result);
+    }
+}
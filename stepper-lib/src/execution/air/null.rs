@@ -22,7 +22,7 @@ use crate::log_instruction;
 use air_parser::ast::Null;
 
 impl<'i> super::ExecutableInstruction<'i> for Null {
-    fn execute(&self, exec_ctx: &mut ExecutionCtx<'i>, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
+    fn execute(&self, exec_ctx: &mut ExecutionCtx, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
         log_instruction!(null, exec_ctx, trace_ctx);
 
         Ok(())
@@ -14,11 +14,12 @@
  * limitations under the License.
  */
 
-use super::ExecutableInstruction;
 use super::ExecutionCtx;
 use super::ExecutionResult;
 use super::ExecutionTraceCtx;
 use super::Instruction;
+use super::Interpreter;
+use super::Step;
 use crate::contexts::execution_trace::ExecutedState;
 use crate::log_instruction;
 use crate::log_targets::EXECUTED_STATE_CHANGING;
@@ -39,29 +40,48 @@ impl std::fmt::Display for SubtreeType {
     }
 }
 
-impl<'i> ExecutableInstruction<'i> for Par<'i> {
-    fn execute(&self, exec_ctx: &mut ExecutionCtx<'i>, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
+impl<'i> super::SchedulableInstruction<'i> for Par<'i> {
+    fn schedule<'a>(
+        &'a self,
+        interpreter: &mut Interpreter<'a, 'i>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> Step {
         use SubtreeType::*;
 
         log_instruction!(par, exec_ctx, trace_ctx);
 
-        let (left_subtree_size, right_subtree_size) = extract_subtree_sizes(trace_ctx)?;
+        let (left_subtree_size, right_subtree_size) = match extract_subtree_sizes(trace_ctx) {
+            Ok(sizes) => sizes,
+            Err(e) => return Step::Completed(Err(e)),
+        };
 
         let par_pos = trace_ctx.new_trace.len();
         trace_ctx.new_trace.push_back(ExecutedState::Par(0, 0));
 
         // execute a left subtree of this par
-        execute_subtree(&self.0, left_subtree_size, exec_ctx, trace_ctx, par_pos, Left)?;
-        let left_subtree_complete = exec_ctx.subtree_complete;
-
-        // execute a right subtree of this par
-        execute_subtree(&self.1, right_subtree_size, exec_ctx, trace_ctx, par_pos, Right)?;
-        let right_subtree_complete = exec_ctx.subtree_complete;
-
-        // par is completed if at least one of its subtrees is completed
-        exec_ctx.subtree_complete = left_subtree_complete || right_subtree_complete;
-
-        Ok(())
+        let left_subtree = SubtreeExecution::start(&self.0, left_subtree_size, Left, exec_ctx, trace_ctx);
+        interpreter.execute_then(&self.0, move |result, interpreter, exec_ctx, trace_ctx| {
+            if let Err(e) = left_subtree.finish(result, par_pos, trace_ctx) {
+                skip_subtree(right_subtree_size, trace_ctx);
+                return Step::Completed(Err(e));
+            }
+            let left_subtree_complete = exec_ctx.subtree_complete;
+
+            // execute a right subtree of this par
+            let right_subtree = SubtreeExecution::start(&self.1, right_subtree_size, Right, exec_ctx, trace_ctx);
+            interpreter.execute_then(&self.1, move |result, _, exec_ctx, trace_ctx| {
+                if let Err(e) = right_subtree.finish(result, par_pos, trace_ctx) {
+                    return Step::Completed(Err(e));
+                }
+                let right_subtree_complete = exec_ctx.subtree_complete;
+
+                // par is completed if at least one of its subtrees is completed
+                exec_ctx.subtree_complete = left_subtree_complete || right_subtree_complete;
+
+                Step::Completed(Ok(()))
+            })
+        })
     }
 }
 
@@ -87,39 +107,57 @@ fn extract_subtree_sizes(trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<(
     }
 }
 
-/// Execute provided subtree and update Par state in trace_ctx.new_trace.
-fn execute_subtree<'i>(
-    subtree: &Instruction<'i>,
-    subtree_size: usize,
-    exec_ctx: &mut ExecutionCtx<'i>,
-    trace_ctx: &mut ExecutionTraceCtx,
-    current_par_pos: usize,
+/// Trace positions saved before a subtree execution to update Par state in trace_ctx.new_trace after it.
+struct SubtreeExecution {
     subtree_type: SubtreeType,
-) -> ExecutionResult<()> {
-    use super::ExecutionError::LocalServiceError;
-
-    let before_subtree_size = trace_ctx.current_subtree_size;
-    trace_ctx.current_subtree_size = subtree_size;
-    let before_new_path_len = trace_ctx.new_trace.len();
-
-    exec_ctx.subtree_complete = determine_subtree_complete(&subtree);
+    subtree_size: usize,
+    before_subtree_size: usize,
+    before_new_path_len: usize,
+}
 
-    // execute a subtree
-    match subtree.execute(exec_ctx, trace_ctx) {
-        res @ Ok(_) => {
-            update_par_state(trace_ctx, subtree_type, current_par_pos, before_new_path_len);
-            trace_ctx.current_subtree_size = before_subtree_size - subtree_size;
-            res
-        }
-        // if there is a service error, update already added Par state
-        // and then bubble the error up
-        err @ Err(LocalServiceError(_)) => {
-            update_par_state(trace_ctx, subtree_type, current_par_pos, before_new_path_len);
-            trace_ctx.current_subtree_size = before_subtree_size - subtree_size;
-            err
+impl SubtreeExecution {
+    fn start(
+        subtree: &Instruction<'_>,
+        subtree_size: usize,
+        subtree_type: SubtreeType,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> Self {
+        let before_subtree_size = trace_ctx.current_subtree_size;
+        trace_ctx.current_subtree_size = subtree_size;
+        let before_new_path_len = trace_ctx.new_trace.len();
+
+        exec_ctx.subtree_complete = determine_subtree_complete(subtree);
+
+        Self {
+            subtree_type,
+            subtree_size,
+            before_subtree_size,
+            before_new_path_len,
         }
-        err @ Err(_) => err,
     }
+
+    fn finish(
+        self,
+        result: ExecutionResult<()>,
+        current_par_pos: usize,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> ExecutionResult<()> {
+        // errors could be caught by xor, e.g. a service error or a fail leave their states in the trace,
+        // so already added Par state is updated and the subtree region is left before bubbling an error up
+        update_par_state(trace_ctx, self.subtree_type, current_par_pos, self.before_new_path_len);
+        trace_ctx.current_subtree_size = self.before_subtree_size - self.subtree_size;
+
+        result
+    }
+}
+
+/// Drops previous states of a subtree that isn't executed because the other one has failed,
+/// so they aren't taken by instructions executed after the error is caught.
+fn skip_subtree(subtree_size: usize, trace_ctx: &mut ExecutionTraceCtx) {
+    let subtree_size = subtree_size.min(trace_ctx.current_subtree_size);
+    trace_ctx.current_trace.drain(..subtree_size);
+    trace_ctx.current_subtree_size -= subtree_size;
 }
 
 fn determine_subtree_complete(next_instruction: &Instruction<'_>) -> bool {
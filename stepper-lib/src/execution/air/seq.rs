@@ -15,24 +15,30 @@
  */
 
 use super::ExecutionCtx;
-use super::ExecutionResult;
 use super::ExecutionTraceCtx;
+use super::Interpreter;
+use super::Step;
 use crate::log_instruction;
 
 use air_parser::ast::Seq;
 
-impl<'i> super::ExecutableInstruction<'i> for Seq<'i> {
-    fn execute(&self, exec_ctx: &mut ExecutionCtx<'i>, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
+impl<'i> super::SchedulableInstruction<'i> for Seq<'i> {
+    fn schedule<'a>(
+        &'a self,
+        interpreter: &mut Interpreter<'a, 'i>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> Step {
         log_instruction!(seq, exec_ctx, trace_ctx);
 
         exec_ctx.subtree_complete = true;
-        self.0.execute(exec_ctx, trace_ctx)?;
+        interpreter.execute_then(&self.0, move |result, interpreter, exec_ctx, _| {
+            if result.is_err() || !exec_ctx.subtree_complete {
+                return Step::Completed(result);
+            }
 
-        if exec_ctx.subtree_complete {
-            self.1.execute(exec_ctx, trace_ctx)?;
-        }
-
-        Ok(())
+            interpreter.execute(&self.1)
+        })
     }
 }
 
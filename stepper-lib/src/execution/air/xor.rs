@@ -16,24 +16,55 @@
 
 use super::ExecutionCtx;
 use super::ExecutionError;
-use super::ExecutionResult;
 use super::ExecutionTraceCtx;
+use super::FailedInstruction;
+use super::Interpreter;
+use super::Step;
+use crate::contexts::execution::LastError;
 use crate::log_instruction;
 
 use air_parser::ast::Xor;
 
-impl<'i> super::ExecutableInstruction<'i> for Xor<'i> {
-    fn execute(&self, exec_ctx: &mut ExecutionCtx<'i>, trace_ctx: &mut ExecutionTraceCtx) -> ExecutionResult<()> {
+impl<'i> super::SchedulableInstruction<'i> for Xor<'i> {
+    fn schedule<'a>(
+        &'a self,
+        interpreter: &mut Interpreter<'a, 'i>,
+        exec_ctx: &mut ExecutionCtx,
+        trace_ctx: &mut ExecutionTraceCtx,
+    ) -> Step {
         log_instruction!(xor, exec_ctx, trace_ctx);
 
         exec_ctx.subtree_complete = true;
-        match self.0.execute(exec_ctx, trace_ctx) {
+        interpreter.execute_then(&self.0, move |result, interpreter, exec_ctx, _| match result {
             Err(e) if is_catchable_by_xor(&e) => {
                 exec_ctx.subtree_complete = true;
-                self.1.execute(exec_ctx, trace_ctx)
+                exec_ctx.last_error = Some(to_last_error(&e, exec_ctx.failed_instruction.take()));
+                interpreter.execute(&self.1)
             }
-            res => res,
-        }
+            res => Step::Completed(res),
+        })
+    }
+}
+
+/// Builds the error accessible through `%last_error%`, it depends only on the error and the script,
+/// so it's the same on all peers replaying the trace.
+fn to_last_error(error: &ExecutionError, failed_instruction: Option<FailedInstruction>) -> LastError {
+    let (instruction, peer_id) = match failed_instruction {
+        Some(failed) => (failed.instruction, failed.peer_id),
+        None => (String::new(), String::new()),
+    };
+
+    let error_code = match error {
+        // a script raises its errors with its own codes
+        ExecutionError::FailError(ret_code, _) => *ret_code,
+        error => error.to_error_code() as i32,
+    };
+
+    LastError {
+        message: error.to_string(),
+        error_code,
+        instruction,
+        peer_id,
     }
 }
 
@@ -44,6 +75,8 @@ fn is_catchable_by_xor(exec_error: &ExecutionError) -> bool {
     match exec_error {
         // this type of errors related to invalid data and should treat as hard errors.
         InvalidExecutedState(..) => false,
+        // the gas limit should stop execution regardless of the script
+        GasExhausted(..) => false,
         _ => true,
     }
 }
@@ -59,6 +92,8 @@ mod tests {
     use aqua_test_utils::IValue;
     use aqua_test_utils::NEVec;
 
+    use serde_json::json;
+
     use std::rc::Rc;
 
     fn fallible_call_service(fallible_service_id: String) -> CallServiceClosure {
@@ -82,6 +117,19 @@ mod tests {
         })
     }
 
+    fn echo_args_call_service() -> CallServiceClosure {
+        Box::new(|_, args| -> Option<IValue> {
+            let args = match &args[2] {
+                IValue::String(args) => args.clone(),
+                _ => unreachable!(),
+            };
+
+            Some(IValue::Record(
+                NEVec::new(vec![IValue::S32(0), IValue::String(args)]).unwrap(),
+            ))
+        })
+    }
+
     #[test]
     fn xor() {
         use crate::contexts::execution_trace::CallResult::*;
@@ -124,6 +172,38 @@ mod tests {
         assert_eq!(actual_trace[0], executed_call_result);
     }
 
+    #[test]
+    fn nary_xor() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let local_peer_id = "local_peer_id";
+        let fallible_service_id = String::from("service_id_1");
+        let mut vm = create_aqua_vm(fallible_call_service(fallible_service_id), local_peer_id);
+
+        // branches are tried in order until the first one completed without an error
+        let script = format!(
+            r#"
+            (xor
+                (call "{0}" ("service_id_1" "local_fn_name") [] result_1)
+                (call "{0}" ("service_id_1" "local_fn_name") [] result_2)
+                (call "{0}" ("service_id_2" "local_fn_name") [] result_3)
+                (call "{0}" ("service_id_2" "local_fn_name") [] result_4)
+            )"#,
+            local_peer_id,
+        );
+
+        let res = call_vm!(vm, "asd", script, "[]", "[]");
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+        let failed_call_result = Call(CallServiceFailed(String::from(r#""error""#)));
+        let executed_call_result = Call(Executed(Rc::new(JValue::String(String::from("res")))));
+
+        assert_eq!(actual_trace.len(), 3);
+        assert_eq!(actual_trace[0], failed_call_result);
+        assert_eq!(actual_trace[1], failed_call_result);
+        assert_eq!(actual_trace[2], executed_call_result);
+    }
+
     #[test]
     fn xor_var_not_found() {
         use aqua_test_utils::echo_string_call_service;
@@ -234,4 +314,113 @@ mod tests {
         let actual_trace: ExecutionTrace = serde_json::from_slice(&result.data).expect("should be valid json");
         assert_eq!(actual_trace, expected_trace);
     }
+
+    // Check that %last_error% describes the error caught by the enclosing xor.
+    #[test]
+    fn last_error_of_failed_call() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let local_peer_id = "local_peer_id";
+        let fallible_service_id = String::from("service_id_1");
+        let mut vm = create_aqua_vm(fallible_call_service(fallible_service_id), local_peer_id);
+
+        let failed_call = format!(
+            r#"(call "{0}" ("service_id_1" "local_fn_name") [] result)"#,
+            local_peer_id
+        );
+        let script = format!(
+            r#"
+            (seq
+                (ap %last_error% no_error)
+                (xor
+                    {0}
+                    (ap %last_error% error)
+                )
+            )"#,
+            failed_call
+        );
+
+        let res = call_vm!(vm, "asd", script, "[]", "[]");
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+
+        let expected_error = json!({
+            "message": r#""error""#,
+            "error_code": 3,
+            "instruction": failed_call,
+            "peer_id": local_peer_id,
+        });
+        let expected_trace = vec![
+            Ap(Rc::new(JValue::Null)),
+            Call(CallServiceFailed(String::from(r#""error""#))),
+            Ap(Rc::new(expected_error)),
+        ];
+        assert_eq!(actual_trace, expected_trace);
+    }
+
+    // Check that json paths could be applied to %last_error% and it's passed to calls.
+    #[test]
+    fn last_error_with_json_path() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_args_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (xor
+                (fail 10042 "some error")
+                (call "{0}" ("service_id" "local_fn_name") [%last_error%.$.message] result)
+            )"#,
+            local_peer_id
+        );
+
+        let res = call_vm!(vm, "asd", script, "[]", "[]");
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+
+        let expected_trace = vec![
+            Fail {
+                ret_code: 10042,
+                message: String::from("some error"),
+            },
+            Call(Executed(Rc::new(json!([["some error"]])))),
+        ];
+        assert_eq!(actual_trace, expected_trace);
+    }
+
+    // Check that %last_error% is the same on a peer replaying the failed call.
+    #[test]
+    fn last_error_on_replaying_peer() {
+        use crate::contexts::execution_trace::CallResult::*;
+        use crate::contexts::execution_trace::ExecutedState::*;
+
+        let fallible_peer_id = "fallible_peer_id";
+        let fallible_service_id = String::from("service_id_1");
+        let mut fallible_vm = create_aqua_vm(fallible_call_service(fallible_service_id), fallible_peer_id);
+
+        let local_peer_id = "local_peer_id";
+        let mut vm = create_aqua_vm(echo_args_call_service(), local_peer_id);
+
+        let script = format!(
+            r#"
+            (xor
+                (call "{0}" ("service_id_1" "local_fn_name") [] result)
+                (call "{1}" ("service_id_2" "local_fn_name") [%last_error%.$.peer_id %last_error%.$.error_code] result)
+            )"#,
+            fallible_peer_id, local_peer_id
+        );
+
+        let res = call_vm!(fallible_vm, "asd", script.clone(), "[]", "[]");
+        assert_eq!(res.next_peer_pks, vec![String::from(local_peer_id)]);
+
+        let res = call_vm!(vm, "asd", script, "[]", res.data);
+        let actual_trace: ExecutionTrace = serde_json::from_slice(&res.data).expect("should be valid json");
+
+        let expected_trace = vec![
+            Call(CallServiceFailed(String::from(r#""error""#))),
+            Call(Executed(Rc::new(json!([[fallible_peer_id], [3]])))),
+        ];
+        assert_eq!(actual_trace, expected_trace);
+    }
 }
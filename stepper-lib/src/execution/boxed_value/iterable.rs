@@ -15,11 +15,13 @@
  */
 
 mod json_path_result;
+mod object_entries;
 mod resolved_call;
 mod vec_json_path_result;
 mod vec_resolved_call;
 
 pub(crate) use json_path_result::IterableJsonPathResult;
+pub(crate) use object_entries::IterableObjectEntries;
 pub(crate) use resolved_call::IterableResolvedCall;
 pub(crate) use vec_json_path_result::IterableVecJsonPathResult;
 pub(crate) use vec_resolved_call::IterableVecResolvedCall;
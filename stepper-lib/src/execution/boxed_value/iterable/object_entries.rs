@@ -0,0 +1,94 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::Iterable;
+use super::IterableItem;
+use crate::foldable_next;
+use crate::foldable_prev;
+use crate::JValue;
+use crate::ResolvedTriplet;
+use crate::SecurityTetraplet;
+
+use serde_json::json;
+use serde_json::Map;
+
+use std::rc::Rc;
+
+/// Used for iterating over JValue of object type, its entries are iterated in the order of keys,
+/// so it's the same on all peers, and each of them is represented as `{"key": key, "value": value}`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct IterableObjectEntries {
+    pub(crate) entries: Vec<JValue>,
+    // each tetraplet points to a value of the corresponding entry
+    pub(crate) tetraplets: Vec<SecurityTetraplet>,
+    pub(crate) cursor: usize,
+}
+
+impl IterableObjectEntries {
+    /// Takes an object and a json path that was applied to obtain it.
+    pub(crate) fn init(object: &Map<String, JValue>, triplet: Rc<ResolvedTriplet>, json_path: &str) -> Self {
+        let mut entries = object.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(key, _)| *key);
+
+        let json_path = match json_path.trim_end_matches('!') {
+            "" => "$",
+            json_path => json_path,
+        };
+
+        let tetraplets = entries
+            .iter()
+            .map(|(key, _)| SecurityTetraplet {
+                triplet: triplet.clone(),
+                json_path: format!("{}[{}]", json_path, JValue::String((*key).clone())),
+            })
+            .collect();
+
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| json!({ "key": key, "value": value }))
+            .collect();
+
+        Self {
+            entries,
+            tetraplets,
+            cursor: 0,
+        }
+    }
+}
+
+impl<'ctx> Iterable<'ctx> for IterableObjectEntries {
+    type Item = IterableItem<'ctx>;
+
+    fn next(&mut self) -> bool {
+        foldable_next!(self, self.entries.len())
+    }
+
+    fn prev(&mut self) -> bool {
+        foldable_prev!(self)
+    }
+
+    fn peek(&'ctx self) -> Option<Self::Item> {
+        if self.entries.is_empty() {
+            return None;
+        }
+
+        let entry = &self.entries[self.cursor];
+        let tetraplet = &self.tetraplets[self.cursor];
+        let result = IterableItem::RefRef((entry, tetraplet));
+
+        Some(result)
+    }
+}
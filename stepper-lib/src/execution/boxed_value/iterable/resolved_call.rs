@@ -20,7 +20,6 @@ use crate::contexts::execution::ResolvedCallResult;
 use crate::foldable_next;
 use crate::foldable_prev;
 use crate::JValue;
-use crate::SecurityTetraplet;
 
 /// Used for iterating over JValue of array type.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -58,12 +57,8 @@ impl<'ctx> Iterable<'ctx> for IterableResolvedCall {
             return None;
         }
 
-        let triplet = self.call_result.triplet.clone();
-        let tetraplet = SecurityTetraplet {
-            triplet,
-            // TODO: consider set json_path to the current cursor here
-            json_path: String::new(),
-        };
+        // TODO: consider set json_path to the current cursor here
+        let tetraplet = self.call_result.tetraplet();
 
         let jvalue = match &self.call_result.result.deref() {
             JValue::Array(array) => &array[self.cursor],
@@ -19,7 +19,6 @@ use super::IterableItem;
 use crate::contexts::execution::ResolvedCallResult;
 use crate::foldable_next;
 use crate::foldable_prev;
-use crate::SecurityTetraplet;
 
 /// Used for iterating over accumulator with JValues.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -53,13 +52,8 @@ impl<'ctx> Iterable<'ctx> for IterableVecResolvedCall {
             return None;
         }
 
-        let ResolvedCallResult { result, triplet } = self.call_results[self.cursor].clone();
-        let tetraplet = SecurityTetraplet {
-            triplet,
-            json_path: String::new(),
-        };
-
-        let result = IterableItem::RcValue((result, tetraplet));
+        let call_result = &self.call_results[self.cursor];
+        let result = IterableItem::RcValue((call_result.result.clone(), call_result.tetraplet()));
         Some(result)
     }
 }
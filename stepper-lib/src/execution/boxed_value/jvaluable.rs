@@ -24,17 +24,19 @@ use super::ExecutionResult;
 use crate::JValue;
 use crate::SecurityTetraplet;
 
+use air_parser::ast::CompiledJsonPath;
+
 use std::borrow::Cow;
 
 /// Represent a value that could be transform to a JValue with or without tetraplets.
 pub(crate) trait JValuable {
     /// Applies json path to the internal value, produces JValue.
-    fn apply_json_path(&self, json_path: &str) -> ExecutionResult<Vec<&JValue>>;
+    fn apply_json_path(&self, json_path: &CompiledJsonPath<'_>) -> ExecutionResult<Vec<&JValue>>;
 
     /// Applies json path to the internal value, produces JValue with tetraplet.
     fn apply_json_path_with_tetraplets(
         &self,
-        json_path: &str,
+        json_path: &CompiledJsonPath<'_>,
     ) -> ExecutionResult<(Vec<&JValue>, Vec<SecurityTetraplet>)>;
 
     /// Return internal value as borrowed if it's possible, owned otherwise.
@@ -21,15 +21,16 @@ use crate::contexts::execution::ResolvedCallResult;
 use crate::JValue;
 use crate::SecurityTetraplet;
 
-use jsonpath_lib::select_with_iter;
+use air_parser::ast::CompiledJsonPath;
 
 use std::borrow::Cow;
 use std::ops::Deref;
 
 impl JValuable for std::cell::Ref<'_, Vec<ResolvedCallResult>> {
-    fn apply_json_path(&self, json_path: &str) -> ExecutionResult<Vec<&JValue>> {
+    fn apply_json_path(&self, json_path: &CompiledJsonPath<'_>) -> ExecutionResult<Vec<&JValue>> {
         let acc_iter = self.iter().map(|r| r.result.deref());
-        let (selected_values, _) = select_with_iter(acc_iter, json_path)
+        let (selected_values, _) = json_path
+            .select_with_iter(acc_iter)
             .map_err(|e| JValueAccJsonPathError(self.iter().cloned().collect::<Vec<_>>(), json_path.to_string(), e))?;
 
         Ok(selected_values)
@@ -37,18 +38,19 @@ impl JValuable for std::cell::Ref<'_, Vec<ResolvedCallResult>> {
 
     fn apply_json_path_with_tetraplets(
         &self,
-        json_path: &str,
+        json_path: &CompiledJsonPath<'_>,
     ) -> ExecutionResult<(Vec<&JValue>, Vec<SecurityTetraplet>)> {
         let acc_iter = self.iter().map(|r| r.result.deref());
 
-        let (selected_values, tetraplet_indices) = select_with_iter(acc_iter, json_path)
+        let (selected_values, tetraplet_indices) = json_path
+            .select_with_iter(acc_iter)
             .map_err(|e| JValueAccJsonPathError(self.iter().cloned().collect::<Vec<_>>(), json_path.to_string(), e))?;
 
         let tetraplets = tetraplet_indices
             .into_iter()
             .map(|id| SecurityTetraplet {
                 triplet: self[id].triplet.clone(),
-                json_path: json_path.to_string(),
+                json_path: self[id].source_json_path(json_path.as_str()),
             })
             .collect::<Vec<_>>();
 
@@ -66,11 +68,6 @@ impl JValuable for std::cell::Ref<'_, Vec<ResolvedCallResult>> {
     }
 
     fn as_tetraplets(&self) -> Vec<SecurityTetraplet> {
-        self.iter()
-            .map(|r| SecurityTetraplet {
-                triplet: r.triplet.clone(),
-                json_path: String::new(),
-            })
-            .collect::<Vec<_>>()
+        self.iter().map(ResolvedCallResult::tetraplet).collect::<Vec<_>>()
     }
 }
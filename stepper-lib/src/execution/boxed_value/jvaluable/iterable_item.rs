@@ -21,13 +21,13 @@ use super::JValuable;
 use crate::JValue;
 use crate::SecurityTetraplet;
 
-use jsonpath_lib::select;
+use air_parser::ast::CompiledJsonPath;
 
 use std::borrow::Cow;
 use std::ops::Deref;
 
 impl<'ctx> JValuable for IterableItem<'ctx> {
-    fn apply_json_path(&self, json_path: &str) -> ExecutionResult<Vec<&JValue>> {
+    fn apply_json_path(&self, json_path: &CompiledJsonPath<'_>) -> ExecutionResult<Vec<&JValue>> {
         use super::IterableItem::*;
 
         let jvalue = match self {
@@ -36,14 +36,15 @@ impl<'ctx> JValuable for IterableItem<'ctx> {
             RcValue((jvalue, _)) => jvalue.deref(),
         };
 
-        let selected_jvalues =
-            select(jvalue, json_path).map_err(|e| JsonPathError(jvalue.clone(), String::from(json_path), e))?;
+        let selected_jvalues = json_path
+            .select(jvalue)
+            .map_err(|e| JsonPathError(jvalue.clone(), String::from(json_path.as_str()), e))?;
         Ok(selected_jvalues)
     }
 
     fn apply_json_path_with_tetraplets(
         &self,
-        json_path: &str,
+        json_path: &CompiledJsonPath<'_>,
     ) -> ExecutionResult<(Vec<&JValue>, Vec<SecurityTetraplet>)> {
         use super::IterableItem::*;
 
@@ -53,8 +54,9 @@ impl<'ctx> JValuable for IterableItem<'ctx> {
             RcValue((jvalue, tetraplet)) => (jvalue.deref(), tetraplet),
         };
 
-        let selected_jvalues =
-            select(jvalue, json_path).map_err(|e| JsonPathError(jvalue.clone(), String::from(json_path), e))?;
+        let selected_jvalues = json_path
+            .select(jvalue)
+            .map_err(|e| JsonPathError(jvalue.clone(), String::from(json_path.as_str()), e))?;
         Ok((selected_jvalues, vec![tetraplet.clone()]))
     }
 
@@ -20,32 +20,34 @@ use crate::contexts::execution::ResolvedCallResult;
 use crate::JValue;
 use crate::SecurityTetraplet;
 
-use jsonpath_lib::select;
+use air_parser::ast::CompiledJsonPath;
 
 use std::borrow::Cow;
 use std::ops::Deref;
 
 impl JValuable for ResolvedCallResult {
-    fn apply_json_path(&self, json_path: &str) -> ExecutionResult<Vec<&JValue>> {
+    fn apply_json_path(&self, json_path: &CompiledJsonPath<'_>) -> ExecutionResult<Vec<&JValue>> {
         use super::ExecutionError::JValueJsonPathError as JsonPathError;
 
-        let selected_jvalues = select(&self.result, json_path)
-            .map_err(|e| JsonPathError(self.result.deref().clone(), String::from(json_path), e))?;
+        let selected_jvalues = json_path
+            .select(&self.result)
+            .map_err(|e| JsonPathError(self.result.deref().clone(), String::from(json_path.as_str()), e))?;
         Ok(selected_jvalues)
     }
 
     fn apply_json_path_with_tetraplets(
         &self,
-        json_path: &str,
+        json_path: &CompiledJsonPath<'_>,
     ) -> ExecutionResult<(Vec<&JValue>, Vec<SecurityTetraplet>)> {
         use super::ExecutionError::JValueJsonPathError as JsonPathError;
 
-        let selected_jvalues = select(&self.result, json_path)
-            .map_err(|e| JsonPathError(self.result.deref().clone(), String::from(json_path), e))?;
+        let selected_jvalues = json_path
+            .select(&self.result)
+            .map_err(|e| JsonPathError(self.result.deref().clone(), String::from(json_path.as_str()), e))?;
 
         let tetraplet = SecurityTetraplet {
             triplet: self.triplet.clone(),
-            json_path: json_path.to_string(),
+            json_path: self.source_json_path(json_path.as_str()),
         };
 
         Ok((selected_jvalues, vec![tetraplet]))
@@ -60,11 +62,6 @@ impl JValuable for ResolvedCallResult {
     }
 
     fn as_tetraplets(&self) -> Vec<SecurityTetraplet> {
-        let tetraplet = SecurityTetraplet {
-            triplet: self.triplet.clone(),
-            json_path: String::new(),
-        };
-
-        vec![tetraplet]
+        vec![self.tetraplet()]
     }
 }
@@ -19,6 +19,7 @@ use crate::contexts::execution::ResolvedCallResult;
 use crate::contexts::execution_trace::ExecutedState;
 use crate::JValue;
 
+use air_parser::ast::CompareOperator;
 use jsonpath_lib::JsonPathError;
 use serde_json::Error as SerdeJsonError;
 use thiserror::Error as ThisError;
@@ -85,6 +86,22 @@ pub(crate) enum ExecutionError {
     /// This error type is produced by a match to notify xor that compared values aren't equal.
     #[error("match is used without corresponding xor")]
     MatchWithoutXorError,
+
+    /// An error raised by a fail instruction with the provided code and message.
+    #[error("{1}")]
+    FailError(i32, String),
+
+    /// Values couldn't be ordered, only numbers and strings could be.
+    #[error("values '{0}' and '{1}' can't be compared")]
+    IncomparableValues(JValue, JValue),
+
+    /// This error type is produced by a comparison instruction to notify xor that the comparison doesn't hold.
+    #[error("{0} is used without corresponding xor")]
+    CompareWithoutXorError(CompareOperator),
+
+    /// Execution consumed more gas of such kind than the limit allows.
+    #[error("gas limit is exhausted: more than {1} {0} are required")]
+    GasExhausted(&'static str, u64),
 }
 
 impl ExecutionError {
@@ -107,6 +124,10 @@ impl ExecutionError {
             InvalidExecutedState(..) => 13,
             ShadowingError(_) => 14,
             MatchWithoutXorError => 15,
+            FailError(..) => 16,
+            IncomparableValues(..) => 17,
+            CompareWithoutXorError(_) => 18,
+            GasExhausted(..) => 19,
         }
     }
 }
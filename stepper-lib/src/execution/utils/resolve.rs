@@ -27,26 +27,41 @@ use air_parser::ast::CallArgValue;
 /// Resolve value to called function arguments.
 pub(crate) fn resolve_to_args<'i>(
     value: &CallArgValue<'i>,
-    ctx: &ExecutionCtx<'i>,
+    ctx: &ExecutionCtx,
 ) -> ExecutionResult<(JValue, Vec<SecurityTetraplet>)> {
-    fn handle_string_arg<'i>(arg: &str, ctx: &ExecutionCtx<'i>) -> ExecutionResult<(JValue, Vec<SecurityTetraplet>)> {
-        let jvalue = JValue::String(arg.to_string());
+    let result = resolve_to_args_impl(value, ctx);
+    ctx.point_at_value(value.span(), result)
+}
+
+fn resolve_to_args_impl<'i>(
+    value: &CallArgValue<'i>,
+    ctx: &ExecutionCtx,
+) -> ExecutionResult<(JValue, Vec<SecurityTetraplet>)> {
+    fn handle_literal_arg(jvalue: JValue, ctx: &ExecutionCtx) -> ExecutionResult<(JValue, Vec<SecurityTetraplet>)> {
         let tetraplet = SecurityTetraplet::literal_tetraplet(ctx.init_peer_id.clone());
 
         Ok((jvalue, vec![tetraplet]))
     }
 
     match value {
-        CallArgValue::InitPeerId => handle_string_arg(ctx.init_peer_id.as_str(), ctx),
-        CallArgValue::Literal(value) => handle_string_arg(value, ctx),
-        CallArgValue::Variable(name) => {
+        CallArgValue::InitPeerId => handle_literal_arg(JValue::String(ctx.init_peer_id.clone()), ctx),
+        CallArgValue::CurrentPeerId => handle_literal_arg(JValue::String(ctx.current_peer_id.clone()), ctx),
+        CallArgValue::ParticleId => handle_literal_arg(JValue::String(ctx.particle_id.clone()), ctx),
+        CallArgValue::Timestamp => handle_literal_arg(JValue::from(ctx.timestamp), ctx),
+        CallArgValue::Ttl => handle_literal_arg(JValue::from(ctx.ttl), ctx),
+        CallArgValue::Literal(value) => handle_literal_arg(JValue::String(value.to_string()), ctx),
+        CallArgValue::Number(value) => handle_literal_arg(JValue::Number(value.clone()), ctx),
+        CallArgValue::Boolean(value) => handle_literal_arg(JValue::Bool(*value), ctx),
+        CallArgValue::Null => handle_literal_arg(JValue::Null, ctx),
+        CallArgValue::Json(value) => handle_literal_arg(value.clone(), ctx),
+        CallArgValue::Variable(name, _) => {
             let resolved = resolve_to_jvaluable(name, ctx)?;
             let tetraplets = resolved.as_tetraplets();
             let jvalue = resolved.into_jvalue();
 
             Ok((jvalue, tetraplets))
         }
-        CallArgValue::JsonPath { variable, path } => {
+        CallArgValue::JsonPath { variable, path, .. } => {
             let resolved = resolve_to_jvaluable(variable, ctx)?;
             let (jvalue, tetraplets) = resolved.apply_json_path_with_tetraplets(path)?;
             let jvalue = jvalue.into_iter().cloned().collect::<Vec<_>>();
@@ -54,13 +69,29 @@ pub(crate) fn resolve_to_args<'i>(
 
             Ok((jvalue, tetraplets))
         }
+        CallArgValue::LastError(path) => {
+            let last_error = match &ctx.last_error {
+                Some(last_error) => last_error.to_call_result(),
+                None => return handle_literal_arg(JValue::Null, ctx),
+            };
+
+            match path {
+                Some(path) => {
+                    let (jvalue, tetraplets) = last_error.apply_json_path_with_tetraplets(path)?;
+                    let jvalue = jvalue.into_iter().cloned().collect::<Vec<_>>();
+
+                    Ok((JValue::Array(jvalue), tetraplets))
+                }
+                None => Ok((last_error.result.as_ref().clone(), last_error.as_tetraplets())),
+            }
+        }
     }
 }
 
 /// Constructs jvaluable result from `ExecutionCtx::data_cache` by name.
-pub(crate) fn resolve_to_jvaluable<'name, 'i, 'ctx>(
+pub(crate) fn resolve_to_jvaluable<'name, 'ctx>(
     name: &'name str,
-    ctx: &'ctx ExecutionCtx<'i>,
+    ctx: &'ctx ExecutionCtx,
 ) -> ExecutionResult<Box<dyn JValuable + 'ctx>> {
     use ExecutionError::VariableNotFound;
 
@@ -78,3 +109,32 @@ pub(crate) fn resolve_to_jvaluable<'name, 'i, 'ctx>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use stepper_interface::RunParameters;
+
+    use serde_json::json;
+
+    // Check that run parameters of a particle are resolved as literal values.
+    #[test]
+    fn run_parameters() {
+        let params = RunParameters {
+            init_peer_id: String::from("asd"),
+            particle_id: String::from("some_particle_id"),
+            timestamp: 1_600_000_000_000,
+            ttl: 42_000,
+            ..RunParameters::default()
+        };
+        let ctx = ExecutionCtx::new(String::from("A"), params);
+
+        let resolve = |value: CallArgValue<'_>| resolve_to_args(&value, &ctx).expect("literals are always resolved").0;
+        assert_eq!(resolve(CallArgValue::CurrentPeerId), json!("A"));
+        assert_eq!(resolve(CallArgValue::InitPeerId), json!("asd"));
+        assert_eq!(resolve(CallArgValue::ParticleId), json!("some_particle_id"));
+        assert_eq!(resolve(CallArgValue::Timestamp), json!(1_600_000_000_000u64));
+        assert_eq!(resolve(CallArgValue::Ttl), json!(42_000));
+    }
+}
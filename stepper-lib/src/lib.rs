@@ -27,6 +27,7 @@
 )]
 
 mod build_targets;
+mod builtins;
 mod contexts;
 mod execution;
 mod preparation;
@@ -36,10 +37,18 @@ pub mod log_targets;
 
 pub use polyplets::ResolvedTriplet;
 pub use polyplets::SecurityTetraplet;
+pub use stepper_interface::Gas;
+pub use stepper_interface::RunParameters;
 pub use stepper_interface::StepperOutcome;
 pub use stepper_interface::STEPPER_SUCCESS;
 
 pub use aqua::execute_aqua;
+pub use aqua::execute_aqua_ast;
+pub use aqua::execute_aqua_ast_with_builtins;
+pub use aqua::execute_aqua_with_builtins;
+pub use builtins::BuiltinFunction;
+pub use builtins::Builtins;
+pub use builtins::BUILTIN_SERVICE_ID;
 
 pub mod execution_trace {
     pub use crate::contexts::execution_trace::CallResult;
@@ -49,9 +58,13 @@ pub mod execution_trace {
 
 pub mod parser {
     pub use air_parser::ast::Instruction;
+    pub use air_parser::format;
+    pub use air_parser::validate;
+    pub use air_parser::ParserError;
+    pub use air_parser::ValidationReport;
 
     /// Parse an AIR script to AST.
-    pub fn parse(script: &str) -> Result<Box<Instruction<'_>>, String> {
+    pub fn parse(script: &str) -> Result<Box<Instruction<'_>>, ParserError> {
         air_parser::parse(script)
     }
 }
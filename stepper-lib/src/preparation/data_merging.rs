@@ -75,6 +75,19 @@ fn merge_subtree(
                 let resulted_call = merge_call(prev_call, call)?;
                 result_trace.push_back(Call(resulted_call));
             }
+            (Some(Ap(prev_result)), Some(Ap(result))) => {
+                // ap is executed locally from the same data, so its results must be equal
+                if prev_result != result {
+                    return Err(IncompatibleExecutedStates(Ap(prev_result), Ap(result)));
+                }
+                result_trace.push_back(Ap(prev_result));
+            }
+            (Some(prev_fail @ Fail { .. }), Some(fail @ Fail { .. })) => {
+                if prev_fail != fail {
+                    return Err(IncompatibleExecutedStates(prev_fail, fail));
+                }
+                result_trace.push_back(prev_fail);
+            }
             (Some(Par(prev_left, prev_right)), Some(Par(current_left, current_right))) => {
                 let par_position = result_trace.len();
                 // place temporary Par value to avoid insert in the middle
@@ -94,6 +107,58 @@ fn merge_subtree(
                 prev_subtree_size -= prev_left + prev_right;
                 current_subtree_size -= current_left + current_right;
             }
+            (Some(FanOut(prev_calls_count)), Some(FanOut(calls_count))) => {
+                // each peer has its own position, so states are merged pairwise
+                // regardless of the order in which the peers have responded
+                if prev_calls_count != calls_count {
+                    return Err(IncompatibleExecutedStates(
+                        FanOut(prev_calls_count),
+                        FanOut(calls_count),
+                    ));
+                }
+                result_trace.push_back(FanOut(calls_count));
+
+                merge_subtree(prev_trace, prev_calls_count, current_trace, calls_count, result_trace)?;
+
+                prev_subtree_size -= prev_calls_count;
+                current_subtree_size -= calls_count;
+            }
+            (
+                Some(Invoke {
+                    name: prev_name,
+                    size: prev_size,
+                }),
+                Some(Invoke { name, size }),
+            ) => {
+                // both regions belong to the same invocation only if the procedure is the same
+                if prev_name != name {
+                    let prev_state = Invoke {
+                        name: prev_name,
+                        size: prev_size,
+                    };
+                    return Err(IncompatibleExecutedStates(prev_state, Invoke { name, size }));
+                }
+
+                let invoke_position = result_trace.len();
+                // place temporary Invoke value to avoid insert in the middle
+                result_trace.push_back(Invoke {
+                    name: name.clone(),
+                    size: 0,
+                });
+
+                let before_result_len = result_trace.len();
+                merge_subtree(prev_trace, prev_size, current_trace, size, result_trace)?;
+                let merged_size = result_trace.len() - before_result_len;
+
+                // update temporary Invoke with the final size
+                result_trace[invoke_position] = Invoke {
+                    name,
+                    size: merged_size,
+                };
+
+                prev_subtree_size -= prev_size;
+                current_subtree_size -= size;
+            }
             (None, Some(s)) => {
                 if current_trace.len() < current_subtree_size {
                     return Err(ExecutedTraceTooSmall(current_trace.len(), current_subtree_size));
@@ -113,7 +178,7 @@ fn merge_subtree(
                 break;
             }
             (None, None) => break,
-            // this match arn represents (Call, Par) and (Par, Call) states
+            // this match arm represents pairs of states with different types
             (Some(prev_state), Some(current_state)) => {
                 return Err(IncompatibleExecutedStates(prev_state, current_state))
             }
@@ -287,4 +352,143 @@ mod tests {
 
         assert_eq!(actual_merged_trace, expected_merged_trace);
     }
+
+    #[test]
+    fn merge_ap_states() {
+        use super::DataMergingError::IncompatibleExecutedStates;
+        use CallResult::*;
+        use ExecutedState::*;
+
+        let mut prev_trace = ExecutionTrace::new();
+        prev_trace.push_back(Call(Executed(Rc::new(JValue::Null))));
+        prev_trace.push_back(Ap(Rc::new(JValue::Null)));
+
+        let mut current_trace = ExecutionTrace::new();
+        current_trace.push_back(Call(Executed(Rc::new(JValue::Null))));
+        current_trace.push_back(Ap(Rc::new(JValue::Null)));
+        current_trace.push_back(Call(RequestSentBy(String::from("peer_1"))));
+
+        let actual_merged_trace =
+            merge_execution_traces(prev_trace.clone(), current_trace.clone()).expect("merging should be successful");
+        assert_eq!(actual_merged_trace, current_trace);
+
+        let mut current_trace = ExecutionTrace::new();
+        current_trace.push_back(Call(Executed(Rc::new(JValue::Null))));
+        current_trace.push_back(Ap(Rc::new(JValue::Bool(true))));
+
+        let merge_error = merge_execution_traces(prev_trace, current_trace).expect_err("merging should fail");
+        assert!(matches!(merge_error, IncompatibleExecutedStates(Ap(_), Ap(_))));
+    }
+
+    #[test]
+    fn merge_fail_states() {
+        use super::DataMergingError::IncompatibleExecutedStates;
+        use ExecutedState::*;
+
+        let fail = |message: &str| Fail {
+            ret_code: 1,
+            message: String::from(message),
+        };
+
+        let prev_trace = vec![fail("error")].into_iter().collect::<ExecutionTrace>();
+        let current_trace = vec![fail("error")].into_iter().collect::<ExecutionTrace>();
+        let actual_merged_trace =
+            merge_execution_traces(prev_trace.clone(), current_trace).expect("merging should be successful");
+        assert_eq!(actual_merged_trace, prev_trace);
+
+        let current_trace = vec![fail("another error")].into_iter().collect::<ExecutionTrace>();
+        let merge_error = merge_execution_traces(prev_trace, current_trace).expect_err("merging should fail");
+        assert!(matches!(
+            merge_error,
+            IncompatibleExecutedStates(Fail { .. }, Fail { .. })
+        ));
+    }
+
+    #[test]
+    fn merge_fan_out_states() {
+        use super::DataMergingError::IncompatibleExecutedStates;
+        use CallResult::*;
+        use ExecutedState::*;
+
+        let executed = || Call(Executed(Rc::new(JValue::Null)));
+        let sent = || Call(RequestSentBy(String::from("peer_1")));
+
+        // responses of the second and the third peers come back to different peers
+        let prev_trace = vec![FanOut(3), executed(), sent(), executed()];
+        let current_trace = vec![FanOut(3), executed(), executed(), sent()];
+        let expected_merged_trace = vec![FanOut(3), executed(), executed(), executed()]
+            .into_iter()
+            .collect::<ExecutionTrace>();
+
+        let actual_merged_trace = merge_execution_traces(
+            prev_trace.clone().into_iter().collect(),
+            current_trace.clone().into_iter().collect(),
+        )
+        .expect("merging should be successful");
+        assert_eq!(actual_merged_trace, expected_merged_trace);
+
+        let actual_merged_trace = merge_execution_traces(
+            current_trace.into_iter().collect(),
+            prev_trace.clone().into_iter().collect(),
+        )
+        .expect("merging should be successful");
+        assert_eq!(actual_merged_trace, expected_merged_trace);
+
+        let current_trace = vec![FanOut(2), executed(), executed()].into_iter().collect();
+        let merge_error =
+            merge_execution_traces(prev_trace.into_iter().collect(), current_trace).expect_err("merging should fail");
+        assert!(matches!(merge_error, IncompatibleExecutedStates(FanOut(3), FanOut(2))));
+    }
+
+    #[test]
+    fn merge_invoke_states() {
+        use super::DataMergingError::IncompatibleExecutedStates;
+        use CallResult::*;
+        use ExecutedState::*;
+
+        let invoke = |name: &str, size: usize| Invoke {
+            name: String::from(name),
+            size,
+        };
+
+        // the first invocation is completed by the previous peer, the second one by the current
+        let prev_trace = vec![
+            invoke("relay", 1),
+            Call(Executed(Rc::new(JValue::Null))),
+            invoke("relay", 1),
+            Call(RequestSentBy(String::from("peer_1"))),
+        ];
+        let current_trace = vec![
+            invoke("relay", 0),
+            invoke("relay", 2),
+            Call(Executed(Rc::new(JValue::Null))),
+            Ap(Rc::new(JValue::Null)),
+        ];
+
+        let actual_merged_trace = merge_execution_traces(
+            prev_trace.clone().into_iter().collect(),
+            current_trace.into_iter().collect(),
+        )
+        .expect("merging should be successful");
+
+        let expected_merged_trace = vec![
+            invoke("relay", 1),
+            Call(Executed(Rc::new(JValue::Null))),
+            invoke("relay", 2),
+            Call(Executed(Rc::new(JValue::Null))),
+            Ap(Rc::new(JValue::Null)),
+        ];
+        assert_eq!(
+            actual_merged_trace,
+            expected_merged_trace.into_iter().collect::<ExecutionTrace>()
+        );
+
+        let current_trace = vec![invoke("another", 0)].into_iter().collect();
+        let merge_error =
+            merge_execution_traces(prev_trace.into_iter().collect(), current_trace).expect_err("merging should fail");
+        assert!(matches!(
+            merge_error,
+            IncompatibleExecutedStates(Invoke { .. }, Invoke { .. })
+        ));
+    }
 }
@@ -17,6 +17,8 @@
 use super::CallResult;
 use super::ExecutedState;
 
+use air_parser::ParserError;
+use air_parser::ValidationReport;
 use serde_json::Error as SerdeJsonError;
 use thiserror::Error as ThisError;
 
@@ -26,8 +28,14 @@ use std::error::Error;
 /// Errors happened during the stepper preparation step.
 #[derive(Debug)]
 pub enum PreparationError {
-    /// Error occurred while parsing AIR script
-    AIRParseError(String),
+    /// Error occurred while parsing AIR script, contains all found errors with their locations.
+    AIRParseError(ParserError),
+
+    /// AIR script has semantic errors found by the static validation, contains all found diagnostics.
+    AIRValidationError(ValidationReport),
+
+    /// Error occurred while deserializing a pre-compiled JSON AST.
+    AIRAstDeError(SerdeJsonError),
 
     /// Errors occurred on executed trace deserialization.
     ExecutedTraceDeError(SerdeJsonError, Vec<u8>),
@@ -69,6 +77,8 @@ impl PreparationError {
             StateMergingError(IncompatibleExecutedStates(..)) => 4,
             StateMergingError(IncompatibleCallResults(..)) => 5,
             StateMergingError(ExecutedTraceTooSmall(..)) => 6,
+            AIRValidationError(_) => 7,
+            AIRAstDeError(_) => 8,
         }
     }
 }
@@ -80,7 +90,9 @@ impl fmt::Display for PreparationError {
         use PreparationError::*;
 
         match self {
-            AIRParseError(err_msg) => write!(f, "aqua script can't be parsed:\n{}", err_msg),
+            AIRParseError(err) => write!(f, "aqua script can't be parsed:\n{}", err),
+            AIRValidationError(report) => write!(f, "aqua script is invalid:\n{}", report),
+            AIRAstDeError(err) => write!(f, "aqua ast can't be deserialized: {}", err),
             ExecutedTraceDeError(serde_error, executed_trace) => {
                 fn print_error(
                     f: &mut fmt::Formatter<'_>,
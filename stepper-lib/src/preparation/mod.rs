@@ -17,10 +17,12 @@
 mod data_merging;
 mod errors;
 mod preparation;
+mod script_cache;
 
 pub(crate) use errors::DataMergingError;
 pub(crate) use errors::PreparationError;
 pub(crate) use preparation::prepare;
+pub(crate) use preparation::AquaSource;
 pub(crate) use preparation::PreparationDescriptor;
 
 pub(self) use crate::contexts::execution::*;
@@ -15,6 +15,7 @@
  */
 
 use super::merge_execution_traces;
+use super::script_cache::get_or_parse;
 use super::ExecutionCtx;
 use super::ExecutionTrace;
 use super::ExecutionTraceCtx;
@@ -22,24 +23,36 @@ use super::PreparationError;
 use crate::build_targets::get_current_peer_id;
 use crate::log_targets::RUN_PARAMS;
 
-use air_parser::ast::Instruction;
+use air_parser::ParsedScript;
+use stepper_interface::RunParameters;
+
+use std::rc::Rc;
 
 type PreparationResult<T> = Result<T, PreparationError>;
 
 /// Represents result of the preparation step.
-pub(crate) struct PreparationDescriptor<'ctx, 'i> {
-    pub(crate) exec_ctx: ExecutionCtx<'ctx>,
+pub(crate) struct PreparationDescriptor {
+    pub(crate) exec_ctx: ExecutionCtx,
     pub(crate) trace_ctx: ExecutionTraceCtx,
-    pub(crate) aqua: Instruction<'i>,
+    pub(crate) aqua: Rc<ParsedScript>,
+}
+
+/// Aqua script supplied to the stepper.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum AquaSource<'a> {
+    /// AIR script in the text form.
+    Script(&'a str),
+    /// AST serialized to JSON, it's executed without parsing.
+    JsonAst(&'a str),
 }
 
 /// Parse and prepare supplied data and aqua script.
-pub(crate) fn prepare<'i>(
+pub(crate) fn prepare(
     prev_data: &[u8],
     data: &[u8],
-    raw_aqua: &'i str,
-    init_peer_id: String,
-) -> PreparationResult<PreparationDescriptor<'static, 'i>> {
+    aqua_source: AquaSource<'_>,
+    params: RunParameters,
+) -> PreparationResult<PreparationDescriptor> {
     fn to_executed_trace(raw_data: &[u8]) -> PreparationResult<ExecutionTrace> {
         use PreparationError::ExecutedTraceDeError as CallDeError;
 
@@ -55,7 +68,15 @@ pub(crate) fn prepare<'i>(
     let prev_trace = to_executed_trace(prev_data)?;
     let trace = to_executed_trace(data)?;
 
-    let aqua: Instruction<'i> = *air_parser::parse(raw_aqua).map_err(PreparationError::AIRParseError)?;
+    let aqua = match aqua_source {
+        // repeated invocations with the same script skip parsing and validation
+        AquaSource::Script(raw_aqua) => get_or_parse(raw_aqua)?,
+        AquaSource::JsonAst(aqua_ast) => {
+            let aqua = ParsedScript::from_json(aqua_ast).map_err(PreparationError::AIRAstDeError)?;
+            validate_script(&aqua)?;
+            Rc::new(aqua)
+        }
+    };
 
     log::trace!(
         target: RUN_PARAMS,
@@ -65,7 +86,7 @@ pub(crate) fn prepare<'i>(
         trace
     );
 
-    let (exec_ctx, trace_ctx) = make_contexts(prev_trace, trace, init_peer_id)?;
+    let (exec_ctx, trace_ctx) = make_contexts(prev_trace, trace, params)?;
     let result = PreparationDescriptor {
         exec_ctx,
         trace_ctx,
@@ -75,17 +96,30 @@ pub(crate) fn prepare<'i>(
     Ok(result)
 }
 
+/// Check a script with the static validation, only warnings are allowed to be found.
+pub(super) fn validate_script(script: &ParsedScript) -> PreparationResult<()> {
+    let validation_report = air_parser::validate(script.instruction());
+    if validation_report.has_errors() {
+        return Err(PreparationError::AIRValidationError(validation_report));
+    }
+    for warning in validation_report.warnings.iter() {
+        log::debug!(target: RUN_PARAMS, "aqua script warning: {}", warning);
+    }
+
+    Ok(())
+}
+
 /// Make execution and execution trace contexts from supplied data.
 /// Internally, it unites variable from previous and current data and merges executed traces.
 fn make_contexts(
     prev_trace: ExecutionTrace,
     trace: ExecutionTrace,
-    init_peer_id: String,
-) -> PreparationResult<(ExecutionCtx<'static>, ExecutionTraceCtx)> {
+    params: RunParameters,
+) -> PreparationResult<(ExecutionCtx, ExecutionTraceCtx)> {
     let current_peer_id = get_current_peer_id().map_err(|e| PreparationError::CurrentPeerIdEnvError(e))?;
     log::trace!(target: RUN_PARAMS, "current peer id {}", current_peer_id);
 
-    let exec_ctx = ExecutionCtx::new(current_peer_id, init_peer_id);
+    let exec_ctx = ExecutionCtx::new(current_peer_id, params);
     let current_trace = merge_execution_traces(prev_trace, trace)?;
     let trace_ctx = ExecutionTraceCtx::new(current_trace);
 
@@ -0,0 +1,182 @@
+/*
+ * Copyright 2020 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::preparation::validate_script;
+use super::PreparationError;
+use crate::log_targets::RUN_PARAMS;
+
+use air_parser::ParsedScript;
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::rc::Rc;
+
+/// How many parsed scripts are kept by the stepper between invocations.
+pub(crate) const SCRIPT_CACHE_CAPACITY: usize = 64;
+
+thread_local!(static SCRIPT_CACHE: RefCell<ScriptCache> = RefCell::new(ScriptCache::new(SCRIPT_CACHE_CAPACITY)));
+
+/// Returns a parsed and validated script from the cache, parses and validates it on a cache miss.
+/// Only valid scripts are cached, so the validation runs only once for each script as well.
+pub(crate) fn get_or_parse(raw_aqua: &str) -> Result<Rc<ParsedScript>, PreparationError> {
+    if let Some(script) = SCRIPT_CACHE.with(|cache| cache.borrow_mut().get(raw_aqua)) {
+        log::trace!(target: RUN_PARAMS, "aqua script is found in the cache");
+        return Ok(script);
+    }
+
+    let script = ParsedScript::parse(raw_aqua).map_err(PreparationError::AIRParseError)?;
+
+    validate_script(&script)?;
+
+    let script = Rc::new(script);
+    SCRIPT_CACHE.with(|cache| cache.borrow_mut().insert(script.clone()));
+
+    Ok(script)
+}
+
+/// Bounded cache of parsed scripts keyed by a script hash, evicts the least recently used script.
+pub(crate) struct ScriptCache {
+    capacity: usize,
+    scripts: HashMap<u64, Rc<ParsedScript>>,
+    // hashes ordered from the least to the most recently used one
+    usage_order: VecDeque<u64>,
+}
+
+impl ScriptCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            scripts: HashMap::with_capacity(capacity),
+            usage_order: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub(crate) fn get(&mut self, raw_aqua: &str) -> Option<Rc<ParsedScript>> {
+        let hash = script_hash(raw_aqua);
+        let script = self.scripts.get(&hash)?;
+        // different scripts with the same hash are treated as a miss
+        if script.script() != raw_aqua {
+            return None;
+        }
+
+        let script = script.clone();
+        self.touch(hash);
+        Some(script)
+    }
+
+    pub(crate) fn insert(&mut self, script: Rc<ParsedScript>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let hash = script_hash(script.script());
+        if self.scripts.insert(hash, script).is_some() {
+            self.touch(hash);
+            return;
+        }
+
+        if self.usage_order.len() == self.capacity {
+            if let Some(evicted) = self.usage_order.pop_front() {
+                self.scripts.remove(&evicted);
+            }
+        }
+        self.usage_order.push_back(hash);
+    }
+
+    fn touch(&mut self, hash: u64) {
+        if let Some(position) = self.usage_order.iter().position(|&h| h == hash) {
+            self.usage_order.remove(position);
+        }
+        self.usage_order.push_back(hash);
+    }
+}
+
+fn script_hash(raw_aqua: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    raw_aqua.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(raw_aqua: &str) -> Rc<ParsedScript> {
+        Rc::new(ParsedScript::parse(raw_aqua).expect("script should be valid"))
+    }
+
+    #[test]
+    fn cache_hit_returns_same_ast() {
+        let raw_aqua = r#"(call "peer" ("service" "function") [] result)"#;
+        let mut cache = ScriptCache::new(2);
+
+        assert!(cache.get(raw_aqua).is_none());
+
+        let script = parsed(raw_aqua);
+        cache.insert(script.clone());
+
+        let cached = cache.get(raw_aqua).expect("script should be cached");
+        assert!(Rc::ptr_eq(&script, &cached));
+        assert!(cache.get("(null)").is_none());
+    }
+
+    #[test]
+    fn least_recently_used_script_is_evicted() {
+        let mut cache = ScriptCache::new(2);
+
+        cache.insert(parsed("(null)"));
+        cache.insert(parsed("(seq (null) (null))"));
+        // now "(seq (null) (null))" is the least recently used one
+        assert!(cache.get("(null)").is_some());
+
+        cache.insert(parsed("(par (null) (null))"));
+
+        assert_eq!(cache.scripts.len(), 2);
+        assert!(cache.get("(null)").is_some());
+        assert!(cache.get("(par (null) (null))").is_some());
+        assert!(cache.get("(seq (null) (null))").is_none());
+    }
+
+    #[test]
+    fn hash_collision_is_miss() {
+        let raw_aqua = "(null)";
+        let other_aqua = "(seq (null) (null))";
+        let mut cache = ScriptCache::new(2);
+
+        // emulate a collision by storing another script under the hash of the requested one
+        cache.scripts.insert(script_hash(raw_aqua), parsed(other_aqua));
+        cache.usage_order.push_back(script_hash(raw_aqua));
+
+        assert!(cache.get(raw_aqua).is_none());
+    }
+
+    #[test]
+    fn get_or_parse_caches_valid_scripts_only() {
+        let raw_aqua = r#"(seq (null) (call "peer" ("service" "function") [] cached_result))"#;
+
+        let first = get_or_parse(raw_aqua).expect("script should be valid");
+        let second = get_or_parse(raw_aqua).expect("script should be valid");
+        assert!(Rc::ptr_eq(&first, &second));
+
+        let invalid_aqua = "(next i)";
+        assert!(get_or_parse(invalid_aqua).is_err());
+        assert!(SCRIPT_CACHE.with(|cache| cache.borrow_mut().get(invalid_aqua).is_none()));
+    }
+}
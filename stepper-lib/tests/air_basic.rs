@@ -176,3 +176,97 @@ fn create_service() {
     assert_eq!(actual_trace, expected_trace);
     assert_eq!(res.next_peer_pks, vec![String::from("remote_peer_id")]);
 }
+
+#[test]
+fn invalid_script_rejected() {
+    let mut vm = create_aqua_vm(unit_call_service(), "A");
+
+    let script = r#"
+        (seq
+            (call "A" ("service_id" "fn_name") [] result)
+            (next result)
+        )"#;
+
+    let res = call_vm!(vm, "asd", script, "[]", "[]");
+
+    assert_eq!(res.ret_code, 7);
+    assert!(res
+        .error_message
+        .contains("next over 'result' is used outside of a fold with such iterator"));
+    assert!(res.next_peer_pks.is_empty());
+}
+
+#[test]
+fn malformed_json_path_rejected() {
+    let mut vm = create_aqua_vm(unit_call_service(), "A");
+
+    let script = r#"
+        (seq
+            (call "A" ("service_id" "fn_name") [] result)
+            (call "A" ("service_id" "fn_name") [result.$.a.] void)
+        )"#;
+
+    let res = call_vm!(vm, "asd", script, "[]", "[]");
+
+    assert_eq!(res.ret_code, 1);
+    assert!(res.error_message.contains("this json path has invalid syntax"));
+}
+
+#[test]
+fn malformed_ast_rejected() {
+    use stepper_lib::execute_aqua_ast;
+    use stepper_lib::RunParameters;
+
+    let params = RunParameters {
+        init_peer_id: String::from("asd"),
+        ..RunParameters::default()
+    };
+    let outcome = execute_aqua_ast(params, String::from(r#"{"Seq":"#), vec![], vec![]);
+
+    assert_eq!(outcome.ret_code, 8);
+    assert!(outcome.error_message.starts_with("aqua ast can't be deserialized"));
+}
+
+#[test]
+fn invalid_ast_rejected() {
+    use stepper_lib::execute_aqua_ast;
+    use stepper_lib::parser::parse;
+    use stepper_lib::RunParameters;
+
+    let script = r#"(seq (null) (next i))"#;
+    let aqua_ast = serde_json::to_string(&parse(script).unwrap()).unwrap();
+
+    let params = RunParameters {
+        init_peer_id: String::from("asd"),
+        ..RunParameters::default()
+    };
+    let outcome = execute_aqua_ast(params, aqua_ast, vec![], vec![]);
+
+    assert_eq!(outcome.ret_code, 7);
+    assert!(outcome
+        .error_message
+        .contains("next over 'i' is used outside of a fold with such iterator"));
+}
+
+#[test]
+fn ast_with_escaped_json_path_executed() {
+    use stepper_lib::execute_aqua_ast;
+    use stepper_lib::parser::parse;
+    use stepper_lib::RunParameters;
+
+    let script = r#"
+        (seq
+            (ap {"peer": "peer_b"} peers)
+            (call peers.$["peer"] ("service_id" "fn_name") [])
+        )"#;
+    let aqua_ast = serde_json::to_string(&parse(script).unwrap()).unwrap();
+
+    let params = RunParameters {
+        init_peer_id: String::from("asd"),
+        ..RunParameters::default()
+    };
+    let outcome = execute_aqua_ast(params, aqua_ast, vec![], vec![]);
+
+    assert_eq!(outcome.ret_code, 0, "{}", outcome.error_message);
+    assert_eq!(outcome.next_peer_pks, vec![String::from("peer_b")]);
+}
@@ -16,6 +16,7 @@
 
 use aqua_test_utils::call_vm;
 use aqua_test_utils::create_aqua_vm;
+use aqua_test_utils::set_variable_call_service;
 use aqua_test_utils::CallServiceClosure;
 use aqua_test_utils::IValue;
 use aqua_test_utils::NEVec;
@@ -195,6 +196,164 @@ fn fold_json_path() {
     assert_eq!(arg_tetraplets, expected_tetraplets);
 }
 
+#[test]
+fn fold_object_json_path() {
+    let set_variable_vm_peer_id = String::from("some_peer_id_1");
+    let mut set_variable_vm = create_aqua_vm(
+        set_variable_call_service(r#"{"peers": {"peer_b": "info_b", "peer_a": "info_a"}}"#),
+        set_variable_vm_peer_id.clone(),
+    );
+
+    let (arg_host_func, arg_tetraplets) = arg_host_function();
+    let client_peer_id = String::from("client_id");
+    let mut client_vm = create_aqua_vm(arg_host_func, client_peer_id.clone());
+
+    let service_id = String::from("some_service_id");
+    let function_name = String::from("some_function_name");
+    let script = format!(
+        r#"
+        (seq
+            (call "{}" ("{}" "{}") [] result)
+            (fold result.$.peers entry
+                (seq
+                    (call "{}" ("local_service_id" "local_fn_name") [entry] acc[])
+                    (next entry)
+                )
+            )
+        )
+        "#,
+        set_variable_vm_peer_id, service_id, function_name, client_peer_id
+    );
+
+    let init_peer_id = String::from("some_init_peer_id");
+    let res = call_vm!(set_variable_vm, init_peer_id.clone(), script.clone(), "", "");
+
+    let triplet = ResolvedTriplet {
+        peer_pk: set_variable_vm_peer_id,
+        service_id,
+        function_name,
+    };
+    // entries are iterated in the order of keys, so the last call gets the entry of peer_b
+    let tetraplet = SecurityTetraplet {
+        triplet: Rc::new(triplet),
+        json_path: String::from(r#"$.peers["peer_b"]"#),
+    };
+
+    let expected_tetraplets = Rc::new(RefCell::new(vec![vec![tetraplet]]));
+    call_vm!(client_vm, init_peer_id, script, "[]", res.data);
+    assert_eq!(arg_tetraplets, expected_tetraplets);
+}
+
+#[test]
+fn ap_keeps_source_tetraplet() {
+    let set_variable_vm_peer_id = String::from("some_peer_id_1");
+    let mut set_variable_vm = create_aqua_vm(
+        set_variable_call_service(r#"{"field": "value"}"#),
+        set_variable_vm_peer_id.clone(),
+    );
+
+    let (arg_host_func, arg_tetraplets) = arg_host_function();
+    let client_peer_id = String::from("client_id");
+    let mut client_vm = create_aqua_vm(arg_host_func, client_peer_id.clone());
+
+    let service_id = String::from("some_service_id");
+    let function_name = String::from("some_function_name");
+    let script = format!(
+        r#"
+        (seq
+            (call "{}" ("{}" "{}") [] result)
+            (seq
+                (seq
+                    (ap result.$.field scalar)
+                    (ap "some_text_literal" acc[])
+                )
+                (call "{}" ("local_service_id" "local_fn_name") [scalar acc] client_result)
+            )
+        )
+        "#,
+        set_variable_vm_peer_id, service_id, function_name, client_peer_id
+    );
+
+    let init_peer_id = String::from("some_init_peer_id");
+    let res = call_vm!(set_variable_vm, init_peer_id.clone(), script.clone(), "", "");
+
+    let first_arg_triplet = ResolvedTriplet {
+        peer_pk: set_variable_vm_peer_id,
+        service_id,
+        function_name,
+    };
+    let first_arg_tetraplet = SecurityTetraplet {
+        triplet: Rc::new(first_arg_triplet),
+        json_path: String::from("$.field"),
+    };
+
+    let second_arg_tetraplet = SecurityTetraplet::literal_tetraplet(init_peer_id.clone());
+
+    let expected_tetraplets = vec![vec![first_arg_tetraplet], vec![second_arg_tetraplet]];
+    let expected_tetraplets = Rc::new(RefCell::new(expected_tetraplets));
+    call_vm!(client_vm, init_peer_id.clone(), script.clone(), "[]", res.data);
+    assert_eq!(arg_tetraplets, expected_tetraplets);
+}
+
+#[test]
+fn ap_tetraplets_of_composed_values() {
+    let set_variable_vm_peer_id = String::from("some_peer_id_1");
+    let mut set_variable_vm = create_aqua_vm(
+        set_variable_call_service(r#"{"field": {"inner": "value"}}"#),
+        set_variable_vm_peer_id.clone(),
+    );
+
+    let (arg_host_func, arg_tetraplets) = arg_host_function();
+    let client_peer_id = String::from("client_id");
+    let mut client_vm = create_aqua_vm(arg_host_func, client_peer_id.clone());
+
+    let service_id = String::from("some_service_id");
+    let function_name = String::from("some_function_name");
+    let script = format!(
+        r#"
+        (seq
+            (call "{}" ("{}" "{}") [] result)
+            (seq
+                (seq
+                    (seq
+                        (ap result.$.field scalar)
+                        (ap scalar acc[])
+                    )
+                    (seq
+                        (ap "some_text_literal" acc[])
+                        (ap acc mixed)
+                    )
+                )
+                (call "{}" ("local_service_id" "local_fn_name") [scalar.$.inner mixed] client_result)
+            )
+        )
+        "#,
+        set_variable_vm_peer_id, service_id, function_name, client_peer_id
+    );
+
+    let init_peer_id = String::from("some_init_peer_id");
+    let res = call_vm!(set_variable_vm, init_peer_id.clone(), script.clone(), "", "");
+
+    let first_arg_triplet = ResolvedTriplet {
+        peer_pk: set_variable_vm_peer_id,
+        service_id,
+        function_name,
+    };
+    // a json path applied to a value keeps the one it was selected with
+    let first_arg_tetraplet = SecurityTetraplet {
+        triplet: Rc::new(first_arg_triplet),
+        json_path: String::from("$.field.inner"),
+    };
+
+    // a value composed from several sources is treated as a literal
+    let second_arg_tetraplet = SecurityTetraplet::literal_tetraplet(init_peer_id.clone());
+
+    let expected_tetraplets = vec![vec![first_arg_tetraplet], vec![second_arg_tetraplet]];
+    let expected_tetraplets = Rc::new(RefCell::new(expected_tetraplets));
+    call_vm!(client_vm, init_peer_id.clone(), script.clone(), "[]", res.data);
+    assert_eq!(arg_tetraplets, expected_tetraplets);
+}
+
 use fluence_app_service::AppService;
 use fluence_app_service::AppServiceConfig;
 use fluence_app_service::FaaSConfig;
@@ -18,12 +18,12 @@ use stepper_lib::parser::parse;
 
 /// Parse AIR script and return it as minified JSON
 pub fn ast(script: String) -> String {
-    let do_parse = || -> std::result::Result<_, Box<dyn std::error::Error>> {
-        let ast = parse(&script)?;
-        serde_json::to_string(&ast).map_err(Into::into)
+    let ast = match parse(&script) {
+        Ok(ast) => ast,
+        Err(err) => return err.render(&script),
     };
 
-    match do_parse() {
+    match serde_json::to_string(&ast) {
         Ok(json) => json,
         Err(err) => err.to_string(),
     }
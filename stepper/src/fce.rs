@@ -32,6 +32,8 @@ mod logger;
 use fluence::fce;
 use logger::DEFAULT_LOG_LEVEL;
 use stepper_lib::execute_aqua;
+use stepper_lib::execute_aqua_ast;
+use stepper_lib::RunParameters;
 use stepper_lib::StepperOutcome;
 
 use log::Level as LogLevel;
@@ -43,11 +45,20 @@ pub fn main() {
 }
 
 #[fce]
-pub fn invoke(init_peer_id: String, aqua: String, prev_data: Vec<u8>, data: Vec<u8>) -> StepperOutcome {
+pub fn invoke(params: RunParameters, aqua: String, prev_data: Vec<u8>, data: Vec<u8>) -> StepperOutcome {
     let log_level = get_log_level();
     log::set_max_level(log_level.to_level_filter());
 
-    execute_aqua(init_peer_id, aqua, prev_data, data)
+    execute_aqua(params, aqua, prev_data, data)
+}
+
+/// The same as `invoke`, but takes an AST serialized to JSON, as it's returned by `ast`.
+#[fce]
+pub fn invoke_ast(params: RunParameters, aqua_ast: String, prev_data: Vec<u8>, data: Vec<u8>) -> StepperOutcome {
+    let log_level = get_log_level();
+    log::set_max_level(log_level.to_level_filter());
+
+    execute_aqua_ast(params, aqua_ast, prev_data, data)
 }
 
 #[fce]
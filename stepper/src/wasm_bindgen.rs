@@ -31,6 +31,9 @@ mod logger;
 
 use logger::DEFAULT_LOG_LEVEL;
 use stepper_lib::execute_aqua;
+use stepper_lib::execute_aqua_ast;
+use stepper_lib::Gas;
+use stepper_lib::RunParameters;
 
 use wasm_bindgen::prelude::*;
 
@@ -39,14 +42,80 @@ pub fn main() {
     logger::init_logger();
 }
 
+/// Fields of `RunParameters` are passed separately, because wasm-bindgen can't take foreign structs,
+/// a zero gas limit means that such work isn't limited.
 #[wasm_bindgen]
-pub fn invoke(init_peer_id: String, aqua: String, prev_data: Vec<u8>, data: Vec<u8>, log_level: &str) -> String {
+#[allow(clippy::too_many_arguments)]
+pub fn invoke(
+    init_peer_id: String,
+    particle_id: String,
+    timestamp: u64,
+    ttl: u32,
+    max_instructions: u64,
+    max_service_calls: u64,
+    max_trace_states: u64,
+    disable_builtins: bool,
+    aqua: String,
+    prev_data: Vec<u8>,
+    data: Vec<u8>,
+    log_level: &str,
+) -> String {
     use std::str::FromStr;
 
     let log_level = log::Level::from_str(log_level).unwrap_or(DEFAULT_LOG_LEVEL);
     log::set_max_level(log_level.to_level_filter());
 
-    let outcome = execute_aqua(init_peer_id, aqua, prev_data, data);
+    let params = RunParameters {
+        init_peer_id,
+        particle_id,
+        timestamp,
+        ttl,
+        gas_limit: Gas {
+            instructions: max_instructions,
+            service_calls: max_service_calls,
+            trace_states: max_trace_states,
+        },
+        disable_builtins,
+    };
+    let outcome = execute_aqua(params, aqua, prev_data, data);
+    serde_json::to_string(&outcome).expect("Cannot parse StepperOutcome")
+}
+
+/// The same as `invoke`, but takes an AST serialized to JSON, as it's returned by `ast`.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn invoke_ast(
+    init_peer_id: String,
+    particle_id: String,
+    timestamp: u64,
+    ttl: u32,
+    max_instructions: u64,
+    max_service_calls: u64,
+    max_trace_states: u64,
+    disable_builtins: bool,
+    aqua_ast: String,
+    prev_data: Vec<u8>,
+    data: Vec<u8>,
+    log_level: &str,
+) -> String {
+    use std::str::FromStr;
+
+    let log_level = log::Level::from_str(log_level).unwrap_or(DEFAULT_LOG_LEVEL);
+    log::set_max_level(log_level.to_level_filter());
+
+    let params = RunParameters {
+        init_peer_id,
+        particle_id,
+        timestamp,
+        ttl,
+        gas_limit: Gas {
+            instructions: max_instructions,
+            service_calls: max_service_calls,
+            trace_states: max_trace_states,
+        },
+        disable_builtins,
+    };
+    let outcome = execute_aqua_ast(params, aqua_ast, prev_data, data);
     serde_json::to_string(&outcome).expect("Cannot parse StepperOutcome")
 }
 